@@ -12,11 +12,13 @@
 //! pointer. Either way, the VM binding code needs to guarantee the safety.
 
 use crate::mmtk::MMTK;
+use crate::mmtk::SFT_MAP;
 use crate::plan::AllocationSemantics;
-use crate::plan::{Mutator, MutatorContext};
+use crate::plan::{ArrayCopy, BarrierFastPath, Mutator, MutatorContext};
 use crate::scheduler::WorkBucketStage;
 use crate::scheduler::{GCController, GCWork, GCWorker};
 use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::alloc::AllocationFastPath;
 use crate::util::constants::{LOG_BYTES_IN_PAGE, MIN_OBJECT_SIZE};
 use crate::util::heap::layout::vm_layout_constants::HEAP_END;
 use crate::util::heap::layout::vm_layout_constants::HEAP_START;
@@ -139,6 +141,44 @@ pub fn post_alloc<VM: VMBinding>(
     mutator.post_alloc(refer, bytes, semantics);
 }
 
+/// Copy references between the reference slots of two objects, like `System.arraycopy` in Java, with
+/// the barriers of the plan. The barriers are applied to the whole range at once, rather than once per slot.
+/// A VM that copies the references itself should call [`MutatorContext::record_modifying_array`] before the copy,
+/// and [`MutatorContext::record_modified_array`] after the copy instead.
+///
+/// Arguments:
+/// * `mutator`: The mutator that performs the copy.
+/// * `src`: The source object.
+/// * `src_slots`: The address of the first source slot in `src`.
+/// * `dst`: The destination object.
+/// * `dst_slots`: The address of the first destination slot in `dst`.
+/// * `count`: The number of references to copy. The source and the destination slots may overlap.
+pub fn object_reference_array_copy<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    src: ObjectReference,
+    src_slots: Address,
+    dst: ObjectReference,
+    dst_slots: Address,
+    count: usize,
+) {
+    let copy = ArrayCopy {
+        src,
+        src_slots,
+        dst,
+        dst_slots,
+        count,
+    };
+    mutator.record_modifying_array(copy);
+    unsafe {
+        std::ptr::copy(
+            src_slots.to_ptr::<ObjectReference>(),
+            dst_slots.to_mut_ptr::<ObjectReference>(),
+            count,
+        )
+    };
+    mutator.record_modified_array(copy);
+}
+
 /// Return an AllocatorSelector for the given allocation semantic. This method is provided
 /// so that VM compilers may call it to help generate allocation fast-path.
 ///
@@ -152,6 +192,29 @@ pub fn get_allocator_mapping<VM: VMBinding>(
     mmtk.plan.get_allocator_mapping()[semantics]
 }
 
+/// Return a descriptor of the allocation fast path for the given allocation semantic, so VM compilers
+/// can inline allocation without hard-coding the layout of the mutator or the allocator a plan uses.
+/// The descriptor is the same for all the mutators of an MMTk instance.
+///
+/// Arguments:
+/// * `mutator`: A mutator of the MMTk instance.
+/// * `semantics`: The allocation semantic to query.
+pub fn get_allocation_fastpath<VM: VMBinding>(
+    mutator: &Mutator<VM>,
+    semantics: AllocationSemantics,
+) -> AllocationFastPath<VM> {
+    AllocationFastPath::new(mutator, semantics)
+}
+
+/// Return a descriptor of the barrier fast path of the plan, so VM compilers can inline the log bit check
+/// of the object barrier without hard-coding the location of the log bit.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn get_barrier_fastpath<VM: VMBinding>(mmtk: &MMTK<VM>) -> BarrierFastPath<VM> {
+    BarrierFastPath::new(mmtk.plan.constraints().barrier)
+}
+
 /// Run the main loop for the GC controller thread. This method does not return.
 ///
 /// Arguments:
@@ -267,6 +330,25 @@ pub fn used_bytes<VM: VMBinding>(mmtk: &MMTK<VM>) -> usize {
     mmtk.plan.get_used_pages() << LOG_BYTES_IN_PAGE
 }
 
+/// Return the memory reserved by MMTk spaces in bytes. This includes used memory, and the memory
+/// reserved for GC, such as the copy reserve.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn reserved_bytes<VM: VMBinding>(mmtk: &MMTK<VM>) -> usize {
+    mmtk.plan.get_reserved_pages() << LOG_BYTES_IN_PAGE
+}
+
+/// Return the memory committed for MMTk spaces in bytes, i.e. used memory and free memory that
+/// has not been returned to the OS yet (see the options `uncommit_free_pages` and `uncommit_delay_ms`).
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn committed_bytes<VM: VMBinding>(mmtk: &MMTK<VM>) -> usize {
+    let free_committed_pages = crate::util::heap::uncommit::UNCOMMITTER.free_committed_pages();
+    (mmtk.plan.get_used_pages() + free_committed_pages) << LOG_BYTES_IN_PAGE
+}
+
 /// Return free memory in bytes.
 ///
 /// Arguments:
@@ -405,6 +487,42 @@ pub fn is_mapped_address(address: Address) -> bool {
     address.is_mapped()
 }
 
+/// Pin an object. MMTk will not move the object until it is unpinned, so the VM can hand out a raw
+/// pointer to the object, e.g. to native code. Pins are not counted: an object pinned twice is unpinned
+/// by one call to `unpin_object()`. This should be called by a mutator, not during a GC.
+///
+/// Objects in Immix spaces can only be pinned with the `object_pinning` feature, which keeps a pin bit
+/// for each object in side metadata. Objects in spaces that never move objects are always pinned.
+/// Other policies (e.g. copying and mark-compact spaces) cannot pin objects.
+///
+/// Returns true if the object is pinned, and false if the policy of the object cannot pin it.
+///
+/// Arguments:
+/// * `object`: The object to pin.
+pub fn pin_object(object: ObjectReference) -> bool {
+    SFT_MAP.get(object.to_address()).pin_object(object)
+}
+
+/// Unpin an object that was pinned by `pin_object()`, so MMTk may move it again.
+///
+/// Returns true if the object was pinned by `pin_object()`, and false otherwise (e.g. the object
+/// is in a space that never moves objects).
+///
+/// Arguments:
+/// * `object`: The object to unpin.
+pub fn unpin_object(object: ObjectReference) -> bool {
+    SFT_MAP.get(object.to_address()).unpin_object(object)
+}
+
+/// Is the object pinned? This is true if the object is pinned by `pin_object()`, or if it is in a
+/// space that never moves objects.
+///
+/// Arguments:
+/// * `object`: The object reference to query.
+pub fn is_pinned(object: ObjectReference) -> bool {
+    SFT_MAP.get(object.to_address()).is_object_pinned(object)
+}
+
 /// Check that if a garbage collection is in progress and if the given
 /// object is not movable.  If it is movable error messages are
 /// logged and the system exits.
@@ -457,7 +575,8 @@ pub fn harness_begin<VM: VMBinding>(mmtk: &MMTK<VM>, tls: VMMutatorThread) {
 }
 
 /// Generic hook to allow benchmarks to be harnessed. We stop collecting
-/// statistics, and print stats values.
+/// statistics, and print stats values. If the option `gc_timeline_file` is set,
+/// the GC timeline since `harness_begin` is also written to the file.
 ///
 /// Arguments:
 /// * `mmtk`: A reference to an MMTk instance.
@@ -465,6 +584,17 @@ pub fn harness_end<VM: VMBinding>(mmtk: &'static MMTK<VM>) {
     mmtk.harness_end();
 }
 
+/// Write the GC timeline recorded so far to a file, in the Chrome Trace Event format.
+/// The timeline is only recorded if the option `gc_timeline_file` is set, otherwise
+/// the file will contain no events.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `path`: The path of the file to write.
+pub fn dump_gc_timeline<VM: VMBinding>(mmtk: &MMTK<VM>, path: &str) -> std::io::Result<()> {
+    mmtk.scheduler.timeline.write_to_file(path)
+}
+
 /// Register a finalizable object. MMTk will retain the liveness of
 /// the object even if it is not reachable from the program.
 /// Note that finalization upon exit is not supported.
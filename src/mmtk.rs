@@ -91,11 +91,18 @@ impl<VM: VMBinding> MMTK<VM> {
         self.inside_harness.store(true, Ordering::SeqCst);
         self.plan.base().stats.start_all();
         self.scheduler.enable_stat();
+        self.scheduler.timeline.reset();
     }
 
     pub fn harness_end(&'static self) {
         self.plan.base().stats.stop_all(self);
         self.inside_harness.store(false, Ordering::SeqCst);
+        if self.scheduler.timeline.is_enabled() {
+            let path = &*self.options.gc_timeline_file;
+            if let Err(e) = self.scheduler.timeline.write_to_file(path) {
+                warn!("Failed to write the GC timeline to {}: {}", path, e);
+            }
+        }
     }
 
     pub fn get_plan(&self) -> &dyn Plan<VM = VM> {
@@ -1,19 +1,40 @@
 //! Read/Write barrier implementations.
 
+use std::sync::atomic::AtomicBool;
+
 use atomic::Ordering;
 
+use crate::plan::{Mutator, MutatorContext};
+use crate::policy::space::Space;
 use crate::scheduler::gc_work::*;
 use crate::scheduler::WorkBucketStage;
+use crate::util::constants::{BITS_IN_BYTE, LOG_BITS_IN_BYTE, LOG_BYTES_IN_ADDRESS};
 use crate::util::metadata::load_metadata;
+use crate::util::metadata::side_metadata::{self, SideMetadataSpec};
 use crate::util::metadata::{compare_exchange_metadata, MetadataSpec};
 use crate::util::*;
+use crate::vm::{EdgeVisitor, ObjectModel, Scanning, VMBinding};
 use crate::MMTK;
 
 /// BarrierSelector describes which barrier to use.
+#[repr(u8)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BarrierSelector {
     NoBarrier,
     ObjectBarrier,
+    /// An object barrier in the coalescing mode (see [`ObjectRememberingBarrier::new_coalescing`]).
+    /// Objects are logged before they are modified, so bindings need to call the pre-write barrier.
+    /// `WriteTarget::Slot` is supported as well, but the slots are remembered without their old referents.
+    CoalescingObjectBarrier,
+    /// A snapshot-at-the-beginning pre-write barrier for concurrent marking.
+    SATBBarrier,
+    /// A field barrier that remembers the modified slots (see [`FieldRememberingBarrier`]).
+    /// This barrier supports `WriteTarget::Slot`, so bindings that only know the address of the field can use it.
+    FieldBarrier,
+    /// A card-marking barrier (see [`CardMarkingBarrier`]). Bindings should pass both the object and the
+    /// modified slot (`WriteTarget::Field`) so that only the dirty cards of the object are scanned.
+    /// Slots written without their objects (`WriteTarget::Slot`) are remembered individually.
+    CardBarrier,
 }
 
 impl BarrierSelector {
@@ -27,18 +48,209 @@ impl BarrierSelector {
 pub enum WriteTarget {
     Object(ObjectReference),
     Slot(Address),
+    /// An object and the address of its modified field.
+    Field(ObjectReference, Address),
+}
+
+/// A bulk copy of `count` contiguous reference slots, from the slots starting at `src_slots` in the object `src`,
+/// to the slots starting at `dst_slots` in the object `dst`. The source and the destination may be the same object.
+#[derive(Copy, Clone, Debug)]
+pub struct ArrayCopy {
+    pub src: ObjectReference,
+    pub src_slots: Address,
+    pub dst: ObjectReference,
+    pub dst_slots: Address,
+    pub count: usize,
+}
+
+impl ArrayCopy {
+    /// The destination slots of the copy.
+    pub fn dst_slots(&self) -> impl Iterator<Item = Address> {
+        let dst_slots = self.dst_slots;
+        (0..self.count).map(move |i| dst_slots + (i << LOG_BYTES_IN_ADDRESS))
+    }
+
+    /// The end of the destination slots of the copy (exclusive).
+    pub fn dst_slots_end(&self) -> Address {
+        self.dst_slots + (self.count << LOG_BYTES_IN_ADDRESS)
+    }
 }
 
 pub trait Barrier: 'static + Send {
     fn flush(&mut self);
+    /// Invoked before a reference field of the target is modified.
+    fn pre_write_barrier(&mut self, target: WriteTarget);
+    /// Invoked after a reference field of the target is modified.
     fn post_write_barrier(&mut self, target: WriteTarget);
+    /// Invoked before the destination slots of an array copy are overwritten.
+    /// By default, this is treated as a write to the destination object.
+    fn pre_array_copy_barrier(&mut self, copy: ArrayCopy) {
+        self.pre_write_barrier(WriteTarget::Object(copy.dst));
+    }
+    /// Invoked after the destination slots of an array copy are overwritten.
+    /// By default, this is treated as a write to the destination object.
+    fn post_array_copy_barrier(&mut self, copy: ArrayCopy) {
+        self.post_write_barrier(WriteTarget::Object(copy.dst));
+    }
 }
 
 pub struct NoBarrier;
 
 impl Barrier for NoBarrier {
     fn flush(&mut self) {}
+    fn pre_write_barrier(&mut self, _target: WriteTarget) {}
     fn post_write_barrier(&mut self, _target: WriteTarget) {}
+    fn pre_array_copy_barrier(&mut self, _copy: ArrayCopy) {}
+    fn post_array_copy_barrier(&mut self, _copy: ArrayCopy) {}
+}
+
+/// Attempt to atomically log an object with the log bit `meta` (0 means logged, and 1 means unlogged).
+/// Returns true if the object is not logged previously.
+#[inline(always)]
+fn log_object<VM: VMBinding>(meta: &MetadataSpec, object: ObjectReference) -> bool {
+    loop {
+        let old_value = load_metadata::<VM>(meta, object, None, Some(Ordering::SeqCst));
+        if old_value == 0 {
+            return false;
+        }
+        if compare_exchange_metadata::<VM>(
+            meta,
+            object,
+            1,
+            0,
+            None,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            return true;
+        }
+    }
+}
+
+/// How compiled code can do the barrier of a plan without calling into MMTk.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BarrierFastPathKind {
+    /// The plan does not use a barrier.
+    NoBarrier,
+    /// After a reference field of an object is modified, check the log bit of the object.
+    /// If the object is unlogged, call the slow path with the object.
+    PostWriteLogBit,
+    /// Before a reference field of an object is modified, check the log bit of the object.
+    /// If the object is unlogged, call the slow path with the object.
+    PreWriteLogBit,
+    /// There is no fast path. Compiled code should call the methods of [`MutatorContext`] that
+    /// the barrier needs (see [`BarrierSelector`]).
+    SlowPathOnly,
+}
+
+/// Where the object log bit (see [`VMGlobalLogBitSpec`](crate::vm::VMGlobalLogBitSpec)) is. The log bit
+/// of an object is 1 if the object is unlogged, and 0 if it is logged.
+#[repr(C, u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LogBitLocation {
+    /// The barrier does not use the log bit.
+    None,
+    /// The log bit is in the side metadata. For an object reference `o`, the log bit is the bit
+    /// `(o >> bit_shift) & bit_mask` of the byte at `base + (o >> byte_shift)`.
+    OnSide {
+        base: Address,
+        byte_shift: usize,
+        bit_shift: usize,
+        bit_mask: usize,
+    },
+    /// The log bit is in the object header. For an object reference `o`, the log bit is the bit `bit`
+    /// of the byte at `o + byte_offset`. This assumes the binding accesses the header metadata
+    /// with the default implementation in [`header_metadata`](crate::util::metadata::header_metadata).
+    InHeader { byte_offset: isize, bit: u8 },
+}
+
+/// The slow path of a barrier for an object, which compiled code calls when the fast path fails.
+pub type BarrierSlowPath<VM> = extern "C" fn(&mut Mutator<VM>, ObjectReference);
+
+/// A descriptor of the barrier fast path of a plan, so a VM compiler can inline the log bit check
+/// of the object barrier without depending on the log bit spec or the side metadata layout.
+/// The fast path does the same check as `ObjectRememberingBarrier::log_object()`, and the slow path
+/// logs the object atomically.
+#[repr(C)]
+pub struct BarrierFastPath<VM: VMBinding> {
+    /// The kind of the fast path.
+    pub kind: BarrierFastPathKind,
+    /// The barrier that the plan uses.
+    pub selector: BarrierSelector,
+    /// The location of the log bit. This is `LogBitLocation::None` unless the fast path checks the log bit.
+    pub log_bit: LogBitLocation,
+    /// The slow path for an unlogged object. This is `None` unless the fast path checks the log bit.
+    pub slow_path: Option<BarrierSlowPath<VM>>,
+}
+
+impl<VM: VMBinding> BarrierFastPath<VM> {
+    pub(crate) fn new(selector: BarrierSelector) -> Self {
+        let (kind, slow_path): (_, Option<BarrierSlowPath<VM>>) = match selector {
+            BarrierSelector::NoBarrier => (BarrierFastPathKind::NoBarrier, None),
+            BarrierSelector::ObjectBarrier => (
+                BarrierFastPathKind::PostWriteLogBit,
+                Some(object_barrier_post_write_slow::<VM>),
+            ),
+            BarrierSelector::CoalescingObjectBarrier => (
+                BarrierFastPathKind::PreWriteLogBit,
+                Some(object_barrier_pre_write_slow::<VM>),
+            ),
+            // The SATB barrier checks whether concurrent marking is in progress, which is not a log bit.
+            BarrierSelector::SATBBarrier => (BarrierFastPathKind::SlowPathOnly, None),
+            // The field barrier logs slots with its own side metadata rather than the object log bit.
+            BarrierSelector::FieldBarrier => (BarrierFastPathKind::SlowPathOnly, None),
+            // The card-marking barrier dirties the cards before it checks the log bit.
+            BarrierSelector::CardBarrier => (BarrierFastPathKind::SlowPathOnly, None),
+        };
+        let log_bit = if slow_path.is_some() {
+            Self::log_bit_location()
+        } else {
+            LogBitLocation::None
+        };
+        BarrierFastPath {
+            kind,
+            selector,
+            log_bit,
+            slow_path,
+        }
+    }
+
+    fn log_bit_location() -> LogBitLocation {
+        match *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC {
+            MetadataSpec::OnSide(spec) => {
+                debug_assert_eq!(spec.log_num_of_bits, 0);
+                // The log bit is global side metadata, which is always contiguous.
+                LogBitLocation::OnSide {
+                    base: spec.get_absolute_offset(),
+                    byte_shift: spec.log_bytes_in_region + LOG_BITS_IN_BYTE as usize,
+                    bit_shift: spec.log_bytes_in_region,
+                    bit_mask: BITS_IN_BYTE - 1,
+                }
+            }
+            MetadataSpec::InHeader(spec) => {
+                debug_assert_eq!(spec.num_of_bits, 1);
+                LogBitLocation::InHeader {
+                    byte_offset: spec.bit_offset >> LOG_BITS_IN_BYTE,
+                    bit: (spec.bit_offset & (BITS_IN_BYTE as isize - 1)) as u8,
+                }
+            }
+        }
+    }
+}
+
+extern "C" fn object_barrier_post_write_slow<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    object: ObjectReference,
+) {
+    mutator.record_modified_node(object);
+}
+
+extern "C" fn object_barrier_pre_write_slow<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    object: ObjectReference,
+) {
+    mutator.record_modifying_node(object);
 }
 
 pub struct ObjectRememberingBarrier<E: ProcessEdgesWork> {
@@ -47,6 +259,19 @@ pub struct ObjectRememberingBarrier<E: ProcessEdgesWork> {
     /// The metadata used for log bit. Though this allows taking an arbitrary metadata spec,
     /// for this field, 0 means logged, and 1 means unlogged (the same as the vm::object_model::VMGlobalLogBitSpec).
     meta: MetadataSpec,
+    /// Only used in the coalescing mode.
+    coalescing: Option<Coalescing<E::VM>>,
+}
+
+/// The states of an [`ObjectRememberingBarrier`] in the coalescing mode.
+struct Coalescing<VM: VMBinding> {
+    tls: VMMutatorThread,
+    /// The referents of the logged objects at the time when they were logged.
+    decbuf: Vec<ObjectReference>,
+    /// The slots modified through `WriteTarget::Slot`. They are logged with [`FIELD_LOG_BIT_SPEC`].
+    slots: Vec<Address>,
+    /// Hand over the recorded referents to the plan.
+    flush_decbuf: fn(&'static MMTK<VM>, Vec<ObjectReference>),
 }
 
 impl<E: ProcessEdgesWork> ObjectRememberingBarrier<E> {
@@ -56,31 +281,45 @@ impl<E: ProcessEdgesWork> ObjectRememberingBarrier<E> {
             mmtk,
             modbuf: vec![],
             meta,
+            coalescing: None,
         }
     }
 
-    /// Attepmt to atomically log an object.
+    /// Create a barrier in the coalescing mode, as used by coalescing reference counting.
+    /// In this mode, an object is logged in the pre-write barrier, when it is modified for the first time
+    /// since it was last unlogged. Besides the object itself, the barrier records the objects that the
+    /// fields of the object point to right before the first modification. Those are the referents that
+    /// were counted for the object, and they are handed over to the plan with `flush_decbuf`.
+    ///
+    /// A write with `WriteTarget::Slot` does not tell which object is modified, so the barrier logs the slot
+    /// with [`FIELD_LOG_BIT_SPEC`] instead, and the slot is processed by a `ProcessSlotBuf` work packet.
+    /// The old referent of the slot is not recorded: the slot may also be covered by a logged object, and
+    /// recording it twice could free a live object. So the old referent is only reclaimed by a full heap GC.
+    /// Plans that use this mode need [`FIELD_LOG_BIT_SPEC`] in their global side metadata.
+    pub fn new_coalescing(
+        mmtk: &'static MMTK<E::VM>,
+        tls: VMMutatorThread,
+        meta: MetadataSpec,
+        flush_decbuf: fn(&'static MMTK<E::VM>, Vec<ObjectReference>),
+    ) -> Self {
+        Self {
+            mmtk,
+            modbuf: vec![],
+            meta,
+            coalescing: Some(Coalescing {
+                tls,
+                decbuf: vec![],
+                slots: vec![],
+                flush_decbuf,
+            }),
+        }
+    }
+
+    /// Attempt to atomically log an object.
     /// Returns true if the object is not logged previously.
     #[inline(always)]
     fn log_object(&self, object: ObjectReference) -> bool {
-        loop {
-            let old_value =
-                load_metadata::<E::VM>(&self.meta, object, None, Some(Ordering::SeqCst));
-            if old_value == 0 {
-                return false;
-            }
-            if compare_exchange_metadata::<E::VM>(
-                &self.meta,
-                object,
-                1,
-                0,
-                None,
-                Ordering::SeqCst,
-                Ordering::SeqCst,
-            ) {
-                return true;
-            }
-        }
+        log_object::<E::VM>(&self.meta, object)
     }
 
     #[inline(always)]
@@ -93,6 +332,41 @@ impl<E: ProcessEdgesWork> ObjectRememberingBarrier<E> {
             }
         }
     }
+
+    /// Log the object and record its current referents. This is used in the coalescing mode.
+    #[inline(always)]
+    fn enqueue_node_and_referents(&mut self, obj: ObjectReference) {
+        if self.log_object(obj) {
+            self.enqueue_referents(obj);
+            self.modbuf.push(obj);
+            if self.modbuf.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
+
+    #[cold]
+    fn enqueue_referents(&mut self, obj: ObjectReference) {
+        let coalescing = self.coalescing.as_mut().unwrap();
+        let mut fields = FieldValuesVisitor { fields: vec![] };
+        <E::VM as VMBinding>::VMScanning::scan_object_in_mutator(coalescing.tls, obj, &mut fields);
+        coalescing.decbuf.append(&mut fields.fields);
+        if coalescing.decbuf.len() >= E::CAPACITY {
+            self.flush();
+        }
+    }
+
+    /// Log a slot that is modified without its object. This is used in the coalescing mode.
+    #[inline(always)]
+    fn enqueue_slot(&mut self, slot: Address) {
+        if log_slot(slot) {
+            let coalescing = self.coalescing.as_mut().unwrap();
+            coalescing.slots.push(slot);
+            if coalescing.slots.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
 }
 
 impl<E: ProcessEdgesWork> Barrier for ObjectRememberingBarrier<E> {
@@ -109,15 +383,465 @@ impl<E: ProcessEdgesWork> Barrier for ObjectRememberingBarrier<E> {
             self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
                 .add(ProcessModBuf::<E>::new(modbuf, self.meta));
         }
+        if let Some(coalescing) = self.coalescing.as_mut() {
+            if !coalescing.decbuf.is_empty() {
+                let decbuf = std::mem::take(&mut coalescing.decbuf);
+                (coalescing.flush_decbuf)(self.mmtk, decbuf);
+            }
+            if !coalescing.slots.is_empty() {
+                let slots = std::mem::take(&mut coalescing.slots);
+                self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                    .add(ProcessSlotBuf::<E>::new(slots));
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn pre_write_barrier(&mut self, target: WriteTarget) {
+        if self.coalescing.is_none() {
+            return;
+        }
+        match target {
+            WriteTarget::Object(obj) | WriteTarget::Field(obj, _) => {
+                self.enqueue_node_and_referents(obj);
+            }
+            WriteTarget::Slot(slot) => self.enqueue_slot(slot),
+        }
+    }
+
+    #[inline(always)]
+    fn post_write_barrier(&mut self, target: WriteTarget) {
+        if self.coalescing.is_some() {
+            // The object has been logged in the pre-write barrier.
+            return;
+        }
+        match target {
+            WriteTarget::Object(obj) | WriteTarget::Field(obj, _) => {
+                self.enqueue_node(obj);
+            }
+            WriteTarget::Slot(_) => panic!(
+                "BarrierSelector::ObjectBarrier cannot log slots. Use BarrierSelector::FieldBarrier, \
+                 BarrierSelector::CardBarrier or BarrierSelector::CoalescingObjectBarrier instead."
+            ),
+        }
+    }
+}
+
+/// The log bits of the slots remembered by [`FieldRememberingBarrier`]. Unlike the object log bit, 1 means logged, and 0 means unlogged,
+/// so that all the slots are unlogged when the metadata is mapped. Plans that use the field barrier need this in their global side metadata.
+pub const FIELD_LOG_BIT_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::FIELD_LOG_BIT;
+
+/// Attempt to atomically log a slot. Returns true if the slot is not logged previously.
+#[inline(always)]
+fn log_slot(slot: Address) -> bool {
+    side_metadata::load_atomic(&FIELD_LOG_BIT_SPEC, slot, Ordering::Relaxed) == 0
+        && side_metadata::compare_exchange_atomic(
+            &FIELD_LOG_BIT_SPEC,
+            slot,
+            0,
+            1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+}
+
+/// Unlog a slot, so the field barrier will remember it again when it is modified.
+#[inline(always)]
+pub(crate) fn unlog_slot(slot: Address) {
+    side_metadata::store_atomic(&FIELD_LOG_BIT_SPEC, slot, 0, Ordering::SeqCst);
+}
+
+/// A field barrier for generational plans.
+///
+/// Instead of logging the modified objects like [`ObjectRememberingBarrier`], this barrier remembers the modified
+/// slots. A slot is logged when it is modified for the first time since the last GC. The remembered slots
+/// are processed by `ProcessSlotBuf` work packets, which trace from them in nursery GCs.
+/// Slots in the nursery are not remembered, as the live nursery objects are scanned in a nursery GC anyway,
+/// and remembering the slots of dead nursery objects would keep their referents alive.
+pub struct FieldRememberingBarrier<E: ProcessEdgesWork> {
+    mmtk: &'static MMTK<E::VM>,
+    tls: VMMutatorThread,
+    slots: Vec<Address>,
+    nursery: &'static dyn Space<E::VM>,
+}
+
+impl<E: ProcessEdgesWork> FieldRememberingBarrier<E> {
+    pub fn new(
+        mmtk: &'static MMTK<E::VM>,
+        tls: VMMutatorThread,
+        nursery: &'static dyn Space<E::VM>,
+    ) -> Self {
+        Self {
+            mmtk,
+            tls,
+            slots: vec![],
+            nursery,
+        }
+    }
+
+    #[inline(always)]
+    fn enqueue_slot(&mut self, slot: Address) {
+        if !self.nursery.address_in_space(slot) && log_slot(slot) {
+            self.slots.push(slot);
+            if self.slots.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
+
+    /// Remember all the reference fields of an object. This is used when we do not know which field was modified.
+    #[cold]
+    fn enqueue_fields(&mut self, object: ObjectReference) {
+        if self.nursery.in_space(object) {
+            return;
+        }
+        let mut slots = FieldSlotsVisitor { slots: vec![] };
+        <E::VM as VMBinding>::VMScanning::scan_object_in_mutator(self.tls, object, &mut slots);
+        for slot in slots.slots {
+            self.enqueue_slot(slot);
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> Barrier for FieldRememberingBarrier<E> {
+    #[cold]
+    fn flush(&mut self) {
+        let slots = std::mem::take(&mut self.slots);
+        debug_assert!(
+            !self.mmtk.scheduler.work_buckets[WorkBucketStage::Final].is_activated(),
+            "{:?}",
+            self as *const _
+        );
+        if !slots.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessSlotBuf::<E>::new(slots));
+        }
+    }
+
+    #[inline(always)]
+    fn pre_write_barrier(&mut self, _target: WriteTarget) {}
+
+    #[inline(always)]
+    fn post_write_barrier(&mut self, target: WriteTarget) {
+        match target {
+            WriteTarget::Object(obj) => self.enqueue_fields(obj),
+            WriteTarget::Slot(slot) | WriteTarget::Field(_, slot) => self.enqueue_slot(slot),
+        }
     }
 
+    #[inline(always)]
+    fn pre_array_copy_barrier(&mut self, _copy: ArrayCopy) {}
+
+    /// Only remember the destination slots.
+    fn post_array_copy_barrier(&mut self, copy: ArrayCopy) {
+        for slot in copy.dst_slots() {
+            self.enqueue_slot(slot);
+        }
+    }
+}
+
+/// The card table of [`CardMarkingBarrier`]. Each card is a byte, and a non-zero card is dirty.
+/// Plans that use the card-marking barrier need this in their global side metadata.
+pub const CARD_TABLE_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::CARD_TABLE;
+
+/// The size of a card in bytes.
+pub const BYTES_IN_CARD: usize = 1 << crate::util::constants::LOG_CARD_BYTES;
+
+/// Return the start of the card that contains the address.
+#[inline(always)]
+pub(crate) fn card_of(addr: Address) -> Address {
+    addr.align_down(BYTES_IN_CARD)
+}
+
+#[inline(always)]
+pub(crate) fn is_card_dirty(card: Address) -> bool {
+    side_metadata::load_atomic(&CARD_TABLE_SPEC, card, Ordering::Relaxed) != 0
+}
+
+/// Attempt to atomically mark a card as dirty. Returns true if the card was clean.
+#[inline(always)]
+fn mark_card(card: Address) -> bool {
+    !is_card_dirty(card)
+        && side_metadata::compare_exchange_atomic(
+            &CARD_TABLE_SPEC,
+            card,
+            0,
+            1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+}
+
+#[inline(always)]
+pub(crate) fn clear_card(card: Address) {
+    side_metadata::store_atomic(&CARD_TABLE_SPEC, card, 0, Ordering::SeqCst);
+}
+
+/// A card-marking barrier for generational plans.
+///
+/// The barrier marks the card of each modified slot as dirty, and logs the modified object with the
+/// object log bit like [`ObjectRememberingBarrier`]. In a nursery GC, `ProcessDirtyCards` work packets
+/// only scan the fields of the logged objects that are in dirty cards, so a large array is not rescanned
+/// as a whole after one of its elements is modified. The dirty cards are cleared by `ClearDirtyCards`
+/// work packets at the end of each GC.
+///
+/// The barrier does not know which objects are in the nursery, so it also marks the cards of nursery objects.
+/// Those cards are never scanned, as nursery objects are never logged.
+///
+/// A slot that is modified without its object (`WriteTarget::Slot`) cannot be found from a dirty card, as there
+/// is no logged object to scan. Such slots are remembered like [`FieldRememberingBarrier`] does, with the
+/// slot log bits, and are processed by `ProcessSlotBuf` work packets.
+pub struct CardMarkingBarrier<E: ProcessEdgesWork> {
+    mmtk: &'static MMTK<E::VM>,
+    modbuf: Vec<ObjectReference>,
+    /// The cards dirtied by this barrier since its last flush.
+    cards: Vec<Address>,
+    /// The slots modified without their objects since the last flush.
+    slots: Vec<Address>,
+    /// The object log bit.
+    meta: MetadataSpec,
+    nursery: &'static dyn Space<E::VM>,
+}
+
+impl<E: ProcessEdgesWork> CardMarkingBarrier<E> {
+    pub fn new(
+        mmtk: &'static MMTK<E::VM>,
+        meta: MetadataSpec,
+        nursery: &'static dyn Space<E::VM>,
+    ) -> Self {
+        Self {
+            mmtk,
+            modbuf: vec![],
+            cards: vec![],
+            slots: vec![],
+            meta,
+            nursery,
+        }
+    }
+
+    /// Remember a slot that is modified without its object. Slots in the nursery are not remembered.
+    #[inline(always)]
+    fn enqueue_slot(&mut self, slot: Address) {
+        if !self.nursery.address_in_space(slot) && log_slot(slot) {
+            self.slots.push(slot);
+            if self.slots.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn enqueue_card(&mut self, card: Address) {
+        if mark_card(card) {
+            self.cards.push(card);
+            if self.cards.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
+
+    /// Mark all the cards of an object as dirty. This is used when we do not know which field was modified.
+    #[cold]
+    fn enqueue_cards_of(&mut self, object: ObjectReference) {
+        let start = <E::VM as VMBinding>::VMObjectModel::object_start_ref(object);
+        let end = start + <E::VM as VMBinding>::VMObjectModel::get_current_size(object);
+        let mut card = card_of(start);
+        while card < end {
+            self.enqueue_card(card);
+            card += BYTES_IN_CARD;
+        }
+    }
+
+    #[inline(always)]
+    fn enqueue_node(&mut self, obj: ObjectReference) {
+        if log_object::<E::VM>(&self.meta, obj) {
+            self.modbuf.push(obj);
+            if self.modbuf.len() >= E::CAPACITY {
+                self.flush();
+            }
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> Barrier for CardMarkingBarrier<E> {
+    #[cold]
+    fn flush(&mut self) {
+        let modbuf = std::mem::take(&mut self.modbuf);
+        let cards = std::mem::take(&mut self.cards);
+        let slots = std::mem::take(&mut self.slots);
+        debug_assert!(
+            !self.mmtk.scheduler.work_buckets[WorkBucketStage::Final].is_activated(),
+            "{:?}",
+            self as *const _
+        );
+        if !modbuf.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessDirtyCards::<E>::new(modbuf, self.meta));
+        }
+        if !cards.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Release]
+                .add(ClearDirtyCards::new(cards));
+        }
+        if !slots.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessSlotBuf::<E>::new(slots));
+        }
+    }
+
+    #[inline(always)]
+    fn pre_write_barrier(&mut self, _target: WriteTarget) {}
+
     #[inline(always)]
     fn post_write_barrier(&mut self, target: WriteTarget) {
         match target {
+            WriteTarget::Field(obj, slot) => {
+                self.enqueue_card(card_of(slot));
+                self.enqueue_node(obj);
+            }
             WriteTarget::Object(obj) => {
+                self.enqueue_cards_of(obj);
                 self.enqueue_node(obj);
             }
-            _ => unreachable!(),
+            WriteTarget::Slot(slot) => self.enqueue_slot(slot),
+        }
+    }
+
+    #[inline(always)]
+    fn pre_array_copy_barrier(&mut self, _copy: ArrayCopy) {}
+
+    /// Only mark the cards of the destination slots.
+    fn post_array_copy_barrier(&mut self, copy: ArrayCopy) {
+        if copy.count == 0 {
+            return;
+        }
+        let mut card = card_of(copy.dst_slots);
+        while card < copy.dst_slots_end() {
+            self.enqueue_card(card);
+            card += BYTES_IN_CARD;
+        }
+        self.enqueue_node(copy.dst);
+    }
+}
+
+/// A snapshot-at-the-beginning (SATB) barrier for concurrent marking.
+///
+/// While concurrent marking is in progress, this barrier records the references that are about
+/// to be overwritten. Thus every object that is reachable when marking starts will be marked, even
+/// if the mutators remove all the references to it during marking. The recorded objects are traced
+/// by `ProcessSATBBuffer` work packets in the `Concurrent` bucket.
+pub struct SATBBarrier<E: ProcessEdgesWork> {
+    mmtk: &'static MMTK<E::VM>,
+    tls: VMMutatorThread,
+    satb: Vec<ObjectReference>,
+    /// Set by the plan while concurrent marking is in progress. The barrier does nothing when this is false.
+    marking: &'static AtomicBool,
+}
+
+impl<E: ProcessEdgesWork> SATBBarrier<E> {
+    pub fn new(
+        mmtk: &'static MMTK<E::VM>,
+        tls: VMMutatorThread,
+        marking: &'static AtomicBool,
+    ) -> Self {
+        Self {
+            mmtk,
+            tls,
+            satb: vec![],
+            marking,
+        }
+    }
+
+    #[inline(always)]
+    fn enqueue(&mut self, object: ObjectReference) {
+        if object.is_null() {
+            return;
+        }
+        self.satb.push(object);
+        if self.satb.len() >= E::CAPACITY {
+            self.flush();
+        }
+    }
+
+    /// Record all the objects that the fields of the given object currently point to.
+    /// This is used when we do not know which field of the object is going to be overwritten.
+    #[cold]
+    fn enqueue_fields(&mut self, object: ObjectReference) {
+        let mut fields = FieldValuesVisitor { fields: vec![] };
+        <E::VM as VMBinding>::VMScanning::scan_object_in_mutator(self.tls, object, &mut fields);
+        for field in fields.fields {
+            self.enqueue(field);
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> Barrier for SATBBarrier<E> {
+    #[cold]
+    fn flush(&mut self) {
+        let mut satb = vec![];
+        std::mem::swap(&mut satb, &mut self.satb);
+        if !satb.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Concurrent]
+                .add(ProcessSATBBuffer::<E>::new(satb));
+        }
+    }
+
+    #[inline(always)]
+    fn pre_write_barrier(&mut self, target: WriteTarget) {
+        if !self.marking.load(Ordering::Acquire) {
+            return;
+        }
+        match target {
+            WriteTarget::Object(obj) => self.enqueue_fields(obj),
+            WriteTarget::Slot(slot) | WriteTarget::Field(_, slot) => {
+                let old = unsafe { slot.load::<ObjectReference>() };
+                self.enqueue(old);
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn post_write_barrier(&mut self, _target: WriteTarget) {}
+
+    /// Only record the references in the destination slots that are about to be overwritten.
+    fn pre_array_copy_barrier(&mut self, copy: ArrayCopy) {
+        if !self.marking.load(Ordering::Acquire) {
+            return;
+        }
+        for slot in copy.dst_slots() {
+            let old = unsafe { slot.load::<ObjectReference>() };
+            self.enqueue(old);
+        }
+    }
+
+    #[inline(always)]
+    fn post_array_copy_barrier(&mut self, _copy: ArrayCopy) {}
+}
+
+/// Collect the addresses of the reference fields of an object.
+pub(crate) struct FieldSlotsVisitor {
+    pub(crate) slots: Vec<Address>,
+}
+
+impl EdgeVisitor for FieldSlotsVisitor {
+    #[inline(always)]
+    fn visit_edge(&mut self, edge: Address) {
+        self.slots.push(edge);
+    }
+}
+
+/// Collect the current values of the reference fields of an object.
+pub(crate) struct FieldValuesVisitor {
+    pub(crate) fields: Vec<ObjectReference>,
+}
+
+impl EdgeVisitor for FieldValuesVisitor {
+    #[inline(always)]
+    fn visit_edge(&mut self, edge: Address) {
+        let object = unsafe { edge.load::<ObjectReference>() };
+        if !object.is_null() {
+            self.fields.push(object);
         }
     }
 }
@@ -0,0 +1,79 @@
+use super::global::ConcurrentImmix;
+use crate::policy::immix::ScanObjectsAndMarkLines;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::WorkBucketStage;
+use crate::util::{Address, ObjectReference};
+use crate::vm::VMBinding;
+use crate::MMTK;
+use std::ops::{Deref, DerefMut};
+
+/// Object tracing for concurrent immix. This is used for the roots in the pauses, the objects recorded by
+/// the SATB barrier, and the concurrent marking. Objects are never moved, and the marked objects
+/// are scanned by work packets in the `Concurrent` bucket, so the transitive closure from the roots
+/// in an initial-mark pause is computed after the mutators are resumed.
+pub(super) struct ConcurrentImmixProcessEdges<VM: VMBinding> {
+    // Use a static ref to the specific plan to avoid overhead from dynamic dispatch or
+    // downcast for each traced object.
+    plan: &'static ConcurrentImmix<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for ConcurrentImmixProcessEdges<VM> {
+    type VM = VM;
+
+    // Objects are not moved. We must not write to the slots, as mutators may be modifying them at the same time.
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<Address>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[cold]
+    fn flush(&mut self) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let scan_objects_work =
+            ScanObjectsAndMarkLines::<Self>::new(self.pop_nodes(), true, &self.plan.immix_space);
+        // Do not scan the objects immediately. In an initial-mark pause, the `Concurrent` bucket
+        // is held back, and the objects will be scanned after the mutators are resumed.
+        self.mmtk().scheduler.work_buckets[WorkBucketStage::Concurrent].add(scan_objects_work);
+    }
+
+    #[inline(always)]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if self.plan.immix_space.in_space(object) {
+            self.plan.immix_space.fast_trace_object(self, object)
+        } else {
+            self.plan.common.trace_object::<Self>(self, object)
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for ConcurrentImmixProcessEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for ConcurrentImmixProcessEdges<VM> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+pub(super) struct ConcurrentImmixGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for ConcurrentImmixGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = ConcurrentImmix<VM>;
+    type ProcessEdgesWorkType = ConcurrentImmixProcessEdges<VM>;
+}
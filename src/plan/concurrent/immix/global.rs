@@ -0,0 +1,219 @@
+use super::gc_work::{ConcurrentImmixGCWorkContext, ConcurrentImmixProcessEdges};
+use super::mutator::ALLOCATOR_MAPPING;
+use crate::plan::barriers::BarrierSelector;
+use crate::plan::concurrent::Pause;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::GcStatus;
+use crate::plan::AllocationSemantics;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::{Prepare, StopMutators};
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::heap::layout::heap_layout::Mmapper;
+use crate::util::heap::layout::heap_layout::VMMap;
+use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::HeapMeta;
+use crate::util::metadata::side_metadata::SideMetadataContext;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::options::UnsafeOptionsWrapper;
+use crate::vm::VMBinding;
+use crate::{policy::immix::ImmixSpace, util::opaque_pointer::VMWorkerThread};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use atomic::{Atomic, Ordering};
+use enum_map::EnumMap;
+
+/// Start concurrent marking when this percentage of the heap is reserved.
+const CONCURRENT_MARKING_THRESHOLD: usize = 70;
+
+/// A mostly-concurrent immix plan. Objects in the immix space and the large object space are marked
+/// while the mutators are running, with a snapshot-at-the-beginning barrier. A collection cycle has two short
+/// pauses: the initial-mark pause that scans the roots, and the final-mark pause that finishes marking and
+/// sweeps the spaces. This plan does not move objects.
+pub struct ConcurrentImmix<VM: VMBinding> {
+    pub immix_space: ImmixSpace<VM>,
+    pub common: CommonPlan<VM>,
+    /// True between an initial-mark pause and the following final-mark pause. The SATB barrier is active
+    /// when this is true.
+    pub(super) concurrent_marking: AtomicBool,
+    /// The kind of the current pause (or the last pause if we are not in a GC).
+    pause: Atomic<Pause>,
+}
+
+pub const CONCURRENT_IMMIX_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: false,
+    gc_header_bits: 2,
+    gc_header_words: 0,
+    num_specialized_scans: 1,
+    /// Max immix object size is half of a block.
+    max_non_los_default_alloc_bytes: crate::policy::immix::MAX_IMMIX_OBJECT_SIZE,
+    barrier: BarrierSelector::SATBBarrier,
+    needs_concurrent_workers: true,
+    ..PlanConstraints::default()
+};
+
+impl<VM: VMBinding> Plan for ConcurrentImmix<VM> {
+    type VM = VM;
+
+    fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
+        self.base().collection_required(self, space_full, space)
+    }
+
+    fn concurrent_collection_required(&self) -> bool {
+        !self.concurrent_marking_in_progress()
+            && !self.base().gc_in_progress()
+            && self.get_reserved_pages() * 100
+                >= self.get_total_pages() * CONCURRENT_MARKING_THRESHOLD
+    }
+
+    fn concurrent_work_drained(&self) {
+        // Concurrent marking is done. Trigger the final-mark pause.
+        if self.concurrent_marking_in_progress() && !self.base().gc_in_progress() {
+            self.base().trigger_internal_collection_request();
+        }
+    }
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &CONCURRENT_IMMIX_CONSTRAINTS
+    }
+
+    fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
+        self.common.gc_init(heap_size, vm_map);
+        self.immix_space.init(vm_map);
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+        let pause = self.select_pause();
+        self.pause.store(pause, Ordering::SeqCst);
+        match pause {
+            Pause::InitialMark => {
+                // Marking from the roots is done in the `Concurrent` bucket after the mutators are resumed.
+                scheduler.pause_concurrent_work();
+                scheduler.work_buckets[WorkBucketStage::Unconstrained]
+                    .add(StopMutators::<ConcurrentImmixProcessEdges<VM>>::new());
+                scheduler.work_buckets[WorkBucketStage::Prepare]
+                    .add(Prepare::<ConcurrentImmixGCWorkContext<VM>>::new(self));
+            }
+            Pause::FinalMark | Pause::Full => {
+                scheduler.schedule_common_work::<ConcurrentImmixGCWorkContext<VM>>(self);
+            }
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &*ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let pause = self.pause.load(Ordering::SeqCst);
+        if pause == Pause::FinalMark {
+            // The spaces were prepared in the initial-mark pause.
+            return;
+        }
+        self.common.prepare(tls, true);
+        self.immix_space.prepare(true);
+        if pause == Pause::InitialMark {
+            self.set_concurrent_marking(true);
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        debug_assert_ne!(self.pause.load(Ordering::SeqCst), Pause::InitialMark);
+        self.common.release(tls, true);
+        self.immix_space.release(true);
+        self.set_concurrent_marking(false);
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.immix_space.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+}
+
+impl<VM: VMBinding> ConcurrentImmix<VM> {
+    pub fn new(
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+    ) -> Self {
+        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
+        let plan = ConcurrentImmix {
+            immix_space: ImmixSpace::new(
+                "immix",
+                vm_map,
+                mmapper,
+                &mut heap,
+                scheduler.clone(),
+                global_metadata_specs.clone(),
+                &CONCURRENT_IMMIX_CONSTRAINTS,
+            ),
+            common: CommonPlan::new(
+                vm_map,
+                mmapper,
+                options,
+                scheduler,
+                heap,
+                &CONCURRENT_IMMIX_CONSTRAINTS,
+                global_metadata_specs,
+            ),
+            concurrent_marking: AtomicBool::new(false),
+            pause: Atomic::new(Pause::Full),
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            plan.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            plan.immix_space
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        plan
+    }
+
+    /// Is concurrent marking in progress?
+    pub fn concurrent_marking_in_progress(&self) -> bool {
+        self.concurrent_marking.load(Ordering::SeqCst)
+    }
+
+    fn set_concurrent_marking(&self, active: bool) {
+        self.concurrent_marking.store(active, Ordering::SeqCst);
+        // Objects allocated during concurrent marking are not in the snapshot. They are live in this cycle.
+        self.immix_space.set_mark_on_alloc(active);
+        self.common.nonmoving.set_mark_on_alloc(active);
+    }
+
+    /// Decide which pause to do for the current GC.
+    fn select_pause(&self) -> Pause {
+        if self.concurrent_marking_in_progress() {
+            Pause::FinalMark
+        } else if self
+            .base()
+            .internal_triggered_collection
+            .load(Ordering::SeqCst)
+            && !self.base().is_user_triggered_collection()
+            && !self.is_emergency_collection()
+        {
+            Pause::InitialMark
+        } else {
+            // The GC is triggered because the heap is full, or by the user. We cannot wait
+            // for concurrent marking, so we do a full stop-the-world GC.
+            Pause::Full
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! Plan: concurrent immix
+
+pub(in crate::plan) mod gc_work;
+pub(in crate::plan) mod global;
+pub(in crate::plan) mod mutator;
+
+pub use self::global::ConcurrentImmix;
+
+pub use self::global::CONCURRENT_IMMIX_CONSTRAINTS;
@@ -0,0 +1,21 @@
+//! Concurrent plans
+
+// Concurrent plans:
+
+/// Concurrent immix (ConcurrentImmix)
+pub mod immix;
+
+// Common concurrent code
+
+/// The kind of a pause in a concurrent plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Pause {
+    /// A stop-the-world collection that finishes in one pause, e.g. for a user-triggered or
+    /// an emergency collection.
+    Full,
+    /// The pause that starts concurrent marking. It prepares the spaces and scans the roots.
+    InitialMark,
+    /// The pause that finishes concurrent marking. It rescans the roots, finishes the remaining
+    /// marking work, and releases the spaces.
+    FinalMark,
+}
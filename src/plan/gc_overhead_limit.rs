@@ -0,0 +1,240 @@
+//! The GC overhead limit.
+//!
+//! When the heap is nearly full, each GC recovers little memory, and the mutators trigger another GC soon
+//! after. The program thrashes for a long time before a GC fails to recover enough memory for an allocation.
+//! If the option `gc_time_limit` is set, and in `gc_overhead_limit_gcs` GCs in a row, the GCs take more than
+//! `gc_time_limit` percent of the time and recover less than `gc_heap_free_limit` percent of the heap, MMTk
+//! reports [`AllocationError::GCOverheadLimitExceeded`](crate::util::alloc::AllocationError::GCOverheadLimitExceeded)
+//! to the next allocation that fails, instead of collecting again.
+
+use crate::util::options::Options;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// What we observed about the GCs.
+struct Observations {
+    /// When the current GC started
+    gc_start: Instant,
+    /// When the last GC ended
+    last_gc_end: Instant,
+    /// The reserved pages when the current GC started
+    pages_before_gc: usize,
+    /// The number of GCs in a row that exceeded the limit
+    consecutive_gcs: usize,
+}
+
+pub struct GCOverheadLimit {
+    /// The fraction of time spent in GC above which a GC counts towards the limit. None if there is no limit.
+    time_limit: Option<f64>,
+    /// The fraction of the heap recovered by a GC below which the GC counts towards the limit
+    heap_free_limit: f64,
+    /// The number of GCs in a row that need to exceed the limit
+    gcs: usize,
+    observations: Mutex<Observations>,
+    /// Is the limit exceeded, and not reported yet?
+    exceeded: AtomicBool,
+}
+
+impl GCOverheadLimit {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        GCOverheadLimit {
+            time_limit: None,
+            heap_free_limit: 0f64,
+            gcs: 0,
+            observations: Mutex::new(Observations {
+                gc_start: now,
+                last_gc_end: now,
+                pages_before_gc: 0,
+                consecutive_gcs: 0,
+            }),
+            exceeded: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the limit from the options.
+    pub fn init(&mut self, options: &Options) {
+        self.time_limit = match *options.gc_time_limit {
+            0 => None,
+            percent => Some(percent as f64 / 100f64),
+        };
+        self.heap_free_limit = *options.gc_heap_free_limit as f64 / 100f64;
+        self.gcs = *options.gc_overhead_limit_gcs;
+        self.observations.lock().unwrap().last_gc_end = Instant::now();
+    }
+
+    /// A GC starts. `reserved_pages` is the number of reserved pages before the GC.
+    pub fn on_gc_start(&self, reserved_pages: usize) {
+        if self.time_limit.is_none() {
+            return;
+        }
+        let mut obs = self.observations.lock().unwrap();
+        obs.gc_start = Instant::now();
+        obs.pages_before_gc = reserved_pages;
+    }
+
+    /// A GC ends. `reserved_pages` is the number of reserved pages after the GC, and `total_pages` is the
+    /// heap size in pages. GCs requested by the user do not count towards the limit.
+    pub fn on_gc_end(&self, reserved_pages: usize, total_pages: usize, user_triggered: bool) {
+        let time_limit = match self.time_limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        let mut obs = self.observations.lock().unwrap();
+        let now = Instant::now();
+        let gc_time = now.duration_since(obs.gc_start).as_secs_f64();
+        let mutator_time = obs.gc_start.duration_since(obs.last_gc_end).as_secs_f64();
+        obs.last_gc_end = now;
+        if user_triggered || total_pages == 0 {
+            return;
+        }
+
+        let gc_overhead = gc_time / (gc_time + mutator_time).max(f64::MIN_POSITIVE);
+        let recovered =
+            obs.pages_before_gc.saturating_sub(reserved_pages) as f64 / total_pages as f64;
+        self.count_gc(&mut obs, time_limit, gc_overhead, recovered);
+    }
+
+    /// Count a GC that spent `gc_overhead` of the time in GC, and recovered `recovered` of the heap.
+    fn count_gc(&self, obs: &mut Observations, time_limit: f64, gc_overhead: f64, recovered: f64) {
+        if gc_overhead > time_limit && recovered < self.heap_free_limit {
+            obs.consecutive_gcs += 1;
+            debug!(
+                "GC overhead limit: {} GCs in a row (GC overhead = {:.3}, recovered = {:.3})",
+                obs.consecutive_gcs, gc_overhead, recovered
+            );
+            if obs.consecutive_gcs >= self.gcs {
+                obs.consecutive_gcs = 0;
+                self.exceeded.store(true, Ordering::SeqCst);
+            }
+        } else {
+            // The program is making progress again.
+            obs.consecutive_gcs = 0;
+            self.exceeded.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Is the limit exceeded? If so, this returns true once, and the caller should report the error.
+    pub fn take_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed) && self.exceeded.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Default for GCOverheadLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_util::serial_test;
+    use std::time::Duration;
+
+    const TOTAL_PAGES: usize = 1000;
+
+    /// A limit of 50% of the time, 2% of the heap, and 3 GCs in a row.
+    fn new_limit() -> GCOverheadLimit {
+        let mut options = Options::default();
+        options.gc_time_limit.value = 50;
+        options.gc_heap_free_limit.value = 2;
+        options.gc_overhead_limit_gcs.value = 3;
+        let mut limit = GCOverheadLimit::new();
+        limit.init(&options);
+        limit
+    }
+
+    /// Count a GC that recovers `recovered_pages` without measuring the time. `thrashing` decides whether
+    /// the GC overhead is above the time limit.
+    fn gc(limit: &GCOverheadLimit, recovered_pages: usize, thrashing: bool) {
+        let mut obs = limit.observations.lock().unwrap();
+        let gc_overhead = if thrashing { 0.9 } else { 0.1 };
+        let recovered = recovered_pages as f64 / TOTAL_PAGES as f64;
+        limit.count_gc(&mut obs, limit.time_limit.unwrap(), gc_overhead, recovered);
+    }
+
+    #[test]
+    fn exceeded_after_consecutive_gcs() {
+        serial_test(|| {
+            let limit = new_limit();
+            gc(&limit, 10, true);
+            gc(&limit, 10, true);
+            assert!(!limit.take_exceeded());
+            gc(&limit, 10, true);
+            assert!(limit.take_exceeded());
+            // It is only reported once, and the count starts again.
+            assert!(!limit.take_exceeded());
+            gc(&limit, 10, true);
+            gc(&limit, 10, true);
+            assert!(!limit.take_exceeded());
+            gc(&limit, 10, true);
+            assert!(limit.take_exceeded());
+        })
+    }
+
+    #[test]
+    fn progress_resets_count() {
+        serial_test(|| {
+            let limit = new_limit();
+            gc(&limit, 10, true);
+            gc(&limit, 10, true);
+            // Recovers enough memory.
+            gc(&limit, 100, true);
+            gc(&limit, 10, true);
+            gc(&limit, 10, true);
+            assert!(!limit.take_exceeded());
+            // Does not take enough time.
+            gc(&limit, 10, false);
+            gc(&limit, 10, true);
+            gc(&limit, 10, true);
+            assert!(!limit.take_exceeded());
+            gc(&limit, 10, true);
+            assert!(limit.take_exceeded());
+        })
+    }
+
+    #[test]
+    fn progress_clears_unreported_limit() {
+        serial_test(|| {
+            let limit = new_limit();
+            for _ in 0..3 {
+                gc(&limit, 10, true);
+            }
+            gc(&limit, 100, true);
+            assert!(!limit.take_exceeded());
+        })
+    }
+
+    #[test]
+    fn measure_gcs() {
+        serial_test(|| {
+            let limit = new_limit();
+            // Each GC takes much longer than the mutators between GCs, and recovers 1% of the heap.
+            for _ in 0..3 {
+                limit.on_gc_start(TOTAL_PAGES);
+                std::thread::sleep(Duration::from_millis(20));
+                limit.on_gc_end(TOTAL_PAGES - 10, TOTAL_PAGES, false);
+            }
+            assert!(limit.take_exceeded());
+            // GCs requested by the user do not count.
+            for _ in 0..3 {
+                limit.on_gc_start(TOTAL_PAGES);
+                std::thread::sleep(Duration::from_millis(20));
+                limit.on_gc_end(TOTAL_PAGES - 10, TOTAL_PAGES, true);
+            }
+            assert!(!limit.take_exceeded());
+        })
+    }
+
+    #[test]
+    fn no_limit_by_default() {
+        let limit = GCOverheadLimit::new();
+        for _ in 0..10 {
+            limit.on_gc_start(TOTAL_PAGES);
+            limit.on_gc_end(TOTAL_PAGES, TOTAL_PAGES, false);
+        }
+        assert!(!limit.take_exceeded());
+    }
+}
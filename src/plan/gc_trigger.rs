@@ -0,0 +1,38 @@
+//! GC triggering policies.
+//!
+//! A [`GCTriggerPolicy`] decides whether an allocation poll should trigger a stop-the-world GC,
+//! unless the allocation cannot succeed without a GC.
+//! By default, MMTk uses [`PlanGCTrigger`], which follows the decision of the plan (see
+//! [`Plan::collection_required`]). A VM binding can supply its own policy through
+//! [`Collection::create_gc_trigger`](crate::vm::Collection::create_gc_trigger).
+
+use crate::plan::Plan;
+use crate::vm::VMBinding;
+
+/// A policy that decides when to trigger a GC.
+pub trait GCTriggerPolicy<VM: VMBinding>: Sync + Send {
+    /// This is called when an allocation polls for a GC after it acquires new pages from a space.
+    /// Return true to trigger a stop-the-world GC. If a space fails to acquire pages, the allocation
+    /// cannot succeed without a GC, so MMTk always triggers a GC without calling this method.
+    ///
+    /// Arguments:
+    /// * `plan_requires_gc`: Whether the plan itself requires a GC, as decided by [`Plan::collection_required`].
+    ///   This considers the heap size, the nursery size and stress GCs.
+    /// * `plan`: The current plan.
+    fn is_gc_required(&self, plan_requires_gc: bool, plan: &dyn Plan<VM = VM>) -> bool;
+
+    /// This is called at the start of each GC, before the mutators are stopped.
+    fn on_gc_start(&self, _plan: &dyn Plan<VM = VM>) {}
+
+    /// This is called at the end of each GC, before the mutators are resumed.
+    fn on_gc_end(&self, _plan: &dyn Plan<VM = VM>) {}
+}
+
+/// The default policy: trigger a GC whenever the plan requires one.
+pub struct PlanGCTrigger;
+
+impl<VM: VMBinding> GCTriggerPolicy<VM> for PlanGCTrigger {
+    fn is_gc_required(&self, plan_requires_gc: bool, _plan: &dyn Plan<VM = VM>) -> bool {
+        plan_requires_gc
+    }
+}
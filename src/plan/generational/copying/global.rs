@@ -1,6 +1,9 @@
 use super::gc_work::GenCopyGCWorkContext;
 use super::mutator::ALLOCATOR_MAPPING;
 use crate::plan::generational::global::Gen;
+use crate::plan::generational::{
+    gen_constraints_for_barriers, select_gen_constraints, GEN_BARRIERS,
+};
 use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::GcStatus;
@@ -33,12 +36,15 @@ pub struct GenCopy<VM: VMBinding> {
 }
 
 pub const GENCOPY_CONSTRAINTS: PlanConstraints = crate::plan::generational::GEN_CONSTRAINTS;
+/// The constraints of GenCopy with each barrier of generational plans.
+static GENCOPY_BARRIER_CONSTRAINTS: [PlanConstraints; GEN_BARRIERS] =
+    gen_constraints_for_barriers(GENCOPY_CONSTRAINTS);
 
 impl<VM: VMBinding> Plan for GenCopy<VM> {
     type VM = VM;
 
     fn constraints(&self) -> &'static PlanConstraints {
-        &GENCOPY_CONSTRAINTS
+        self.gen.constraints
     }
 
     fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
@@ -53,7 +59,7 @@ impl<VM: VMBinding> Plan for GenCopy<VM> {
                 // The tospace argument doesn't matter, we will rebind before a GC anyway.
                 (CopySelector::CopySpace(0), self.tospace()),
             ],
-            constraints: &GENCOPY_CONSTRAINTS,
+            constraints: self.gen.constraints,
         }
     }
 
@@ -91,6 +97,7 @@ impl<VM: VMBinding> Plan for GenCopy<VM> {
 
     fn prepare(&mut self, tls: VMWorkerThread) {
         let full_heap = !self.is_current_gc_nursery();
+        Gen::record_usage_before_gc(self);
         self.gen.prepare(tls);
         if full_heap {
             self.hi
@@ -116,6 +123,7 @@ impl<VM: VMBinding> Plan for GenCopy<VM> {
             self.fromspace().release();
         }
 
+        Gen::adjust_nursery_size(self);
         self.gen
             .set_next_gc_full_heap(Gen::should_next_gc_be_full_heap(self));
     }
@@ -159,11 +167,15 @@ impl<VM: VMBinding> GenCopy<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let constraints = select_gen_constraints(&GENCOPY_BARRIER_CONSTRAINTS, &options);
         // We have no specific side metadata for copying. So just use the ones from generational.
         let global_metadata_specs =
-            crate::plan::generational::new_generational_global_metadata_specs::<VM>();
+            crate::plan::generational::new_generational_global_metadata_specs::<VM>(
+                constraints.barrier,
+            );
 
         let copyspace0 = CopySpace::new(
             "copyspace0",
@@ -190,10 +202,11 @@ impl<VM: VMBinding> GenCopy<VM> {
             gen: Gen::new(
                 heap,
                 global_metadata_specs,
-                &GENCOPY_CONSTRAINTS,
+                constraints,
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
             ),
             hi: AtomicBool::new(false),
             copyspace0,
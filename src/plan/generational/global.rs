@@ -1,3 +1,4 @@
+use super::nursery_sizing::NurserySizing;
 use crate::plan::global::CommonPlan;
 use crate::plan::Plan;
 use crate::plan::PlanConstraints;
@@ -32,6 +33,10 @@ pub struct Gen<VM: VMBinding> {
     pub gc_full_heap: AtomicBool,
     /// Is next GC full heap?
     pub next_gc_full_heap: AtomicBool,
+    /// Decides the nursery size that triggers a nursery GC.
+    pub nursery_sizing: NurserySizing,
+    /// The constraints of the plan, with the barrier selected by the `gen_barrier` option.
+    pub constraints: &'static PlanConstraints,
 }
 
 impl<VM: VMBinding> Gen<VM> {
@@ -42,28 +47,35 @@ impl<VM: VMBinding> Gen<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
+        let nursery = CopySpace::new(
+            "nursery",
+            false,
+            true,
+            VMRequest::fixed_extent(crate::util::options::NURSERY_SIZE, false),
+            global_metadata_specs.clone(),
+            vm_map,
+            mmapper,
+            &mut heap,
+        );
+        let common = CommonPlan::new(
+            vm_map,
+            mmapper,
+            options,
+            scheduler,
+            heap,
+            constraints,
+            global_metadata_specs,
+        );
+        let nursery_sizing = NurserySizing::new(&common.base.stats);
         Gen {
-            nursery: CopySpace::new(
-                "nursery",
-                false,
-                true,
-                VMRequest::fixed_extent(crate::util::options::NURSERY_SIZE, false),
-                global_metadata_specs.clone(),
-                vm_map,
-                mmapper,
-                &mut heap,
-            ),
-            common: CommonPlan::new(
-                vm_map,
-                mmapper,
-                options,
-                heap,
-                constraints,
-                global_metadata_specs,
-            ),
+            nursery,
+            common,
             gc_full_heap: AtomicBool::default(),
             next_gc_full_heap: AtomicBool::new(false),
+            nursery_sizing,
+            constraints,
         }
     }
 
@@ -77,6 +89,7 @@ impl<VM: VMBinding> Gen<VM> {
     pub fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
         self.common.gc_init(heap_size, vm_map);
         self.nursery.init(vm_map);
+        self.nursery_sizing.init(&self.common.base.options);
     }
 
     /// Prepare Gen. This should be called by a single thread in GC prepare work.
@@ -103,8 +116,7 @@ impl<VM: VMBinding> Gen<VM> {
         space_full: bool,
         space: &dyn Space<VM>,
     ) -> bool {
-        let nursery_full = self.nursery.reserved_pages()
-            >= (conversions::bytes_to_pages_up(*self.common.base.options.max_nursery));
+        let nursery_full = self.nursery.reserved_pages() >= self.nursery_sizing.get_nursery_pages();
         if nursery_full {
             return true;
         }
@@ -209,10 +221,39 @@ impl<VM: VMBinding> Gen<VM> {
         !self.gc_full_heap.load(Ordering::SeqCst)
     }
 
-    /// Check a plan to see if the next GC should be a full heap GC.
+    /// Check a plan to see if the next GC should be a full heap GC. If there is a pause time goal, and
+    /// full heap GCs stay within it, we collect the full heap once the available pages cannot hold the
+    /// current nursery size. Otherwise, we wait until they cannot hold the minimum nursery size.
     pub fn should_next_gc_be_full_heap(plan: &dyn Plan<VM = VM>) -> bool {
-        plan.get_available_pages()
-            < conversions::bytes_to_pages_up(*plan.base().options.min_nursery)
+        let base = plan.base();
+        let nursery_pages =
+            if base.pause_goal.goal().is_some() && !base.pause_goal.is_exceeded_by_full_heap_gc() {
+                plan.generational().nursery_sizing.get_nursery_pages()
+            } else {
+                conversions::bytes_to_pages_up(*base.options.min_nursery)
+            };
+        plan.get_available_pages() < nursery_pages
+    }
+
+    /// Record the heap usage before a GC for adaptive nursery sizing. A generational plan should
+    /// call this in its prepare(), before any object is copied.
+    pub fn record_usage_before_gc(plan: &dyn Plan<VM = VM>) {
+        let gen = plan.generational();
+        gen.nursery_sizing
+            .on_gc_start(gen.nursery.reserved_pages(), plan.get_used_pages());
+    }
+
+    /// Adjust the nursery size from what survived the GC. A generational plan should call this
+    /// in its release(), after all the spaces are released.
+    pub fn adjust_nursery_size(plan: &dyn Plan<VM = VM>) {
+        let gen = plan.generational();
+        let base = plan.base();
+        gen.nursery_sizing.on_gc_end(
+            gen.is_current_gc_nursery(),
+            plan.get_used_pages(),
+            base.stats.current_pause(),
+            &base.pause_goal,
+        );
     }
 
     /// Set next_gc_full_heap to the given value.
@@ -1,6 +1,9 @@
 use super::gc_work::GenImmixMatureGCWorkContext;
 use super::gc_work::GenImmixNurseryGCWorkContext;
 use crate::plan::generational::global::Gen;
+use crate::plan::generational::{
+    gen_constraints_for_barriers, select_gen_constraints, GEN_BARRIERS,
+};
 use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::GcStatus;
@@ -8,6 +11,7 @@ use crate::plan::immix::gc_work::{TRACE_KIND_DEFRAG, TRACE_KIND_FAST};
 use crate::plan::AllocationSemantics;
 use crate::plan::Plan;
 use crate::plan::PlanConstraints;
+use crate::policy::immix::defrag::DefragDecision;
 use crate::policy::immix::ImmixSpace;
 use crate::policy::space::Space;
 use crate::scheduler::GCWorkScheduler;
@@ -54,12 +58,15 @@ pub const GENIMMIX_CONSTRAINTS: PlanConstraints = PlanConstraints {
     ),
     ..crate::plan::generational::GEN_CONSTRAINTS
 };
+/// The constraints of GenImmix with each barrier of generational plans.
+static GENIMMIX_BARRIER_CONSTRAINTS: [PlanConstraints; GEN_BARRIERS] =
+    gen_constraints_for_barriers(GENIMMIX_CONSTRAINTS);
 
 impl<VM: VMBinding> Plan for GenImmix<VM> {
     type VM = VM;
 
     fn constraints(&self) -> &'static PlanConstraints {
-        &GENIMMIX_CONSTRAINTS
+        self.gen.constraints
     }
 
     fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
@@ -71,7 +78,7 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
                 _ => CopySelector::Unused,
             },
             space_mapping: vec![(CopySelector::Immix(0), &self.immix)],
-            constraints: &GENIMMIX_CONSTRAINTS,
+            constraints: self.gen.constraints,
         }
     }
 
@@ -113,13 +120,8 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
         self.base().set_collection_kind::<Self>(self);
         self.base().set_gc_status(GcStatus::GcPrepare);
         let defrag = if is_full_heap {
-            self.immix.decide_whether_to_defrag(
-                self.is_emergency_collection(),
-                true,
-                self.base().cur_collection_attempts.load(Ordering::SeqCst),
-                self.base().is_user_triggered_collection(),
-                *self.base().options.full_heap_system_gc,
-            )
+            self.immix
+                .decide_whether_to_defrag(&DefragDecision::full_heap_gc(self))
         } else {
             false
         };
@@ -144,6 +146,7 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
 
     fn prepare(&mut self, tls: VMWorkerThread) {
         let full_heap = !self.is_current_gc_nursery();
+        Gen::record_usage_before_gc(self);
         self.gen.prepare(tls);
         if full_heap {
             self.immix.prepare(full_heap);
@@ -161,6 +164,7 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
         }
         self.last_gc_was_full_heap
             .store(full_heap, Ordering::Relaxed);
+        Gen::adjust_nursery_size(self);
     }
 
     fn get_collection_reserved_pages(&self) -> usize {
@@ -205,26 +209,31 @@ impl<VM: VMBinding> GenImmix<VM> {
         scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let constraints = select_gen_constraints(&GENIMMIX_BARRIER_CONSTRAINTS, &options);
         // We have no specific side metadata for copying. So just use the ones from generational.
         let global_metadata_specs =
-            crate::plan::generational::new_generational_global_metadata_specs::<VM>();
+            crate::plan::generational::new_generational_global_metadata_specs::<VM>(
+                constraints.barrier,
+            );
         let immix_space = ImmixSpace::new(
             "immix_mature",
             vm_map,
             mmapper,
             &mut heap,
-            scheduler,
+            scheduler.clone(),
             global_metadata_specs.clone(),
+            constraints,
         );
 
         let genimmix = GenImmix {
             gen: Gen::new(
                 heap,
                 global_metadata_specs,
-                &GENIMMIX_CONSTRAINTS,
+                constraints,
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
             ),
             immix: immix_space,
             last_gc_was_defrag: AtomicBool::new(false),
@@ -1,20 +1,20 @@
 pub(super) use super::super::ALLOCATOR_MAPPING;
-use crate::plan::barriers::ObjectRememberingBarrier;
-use crate::plan::generational::create_gen_space_mapping;
-use crate::plan::generational::gc_work::GenNurseryProcessEdges;
 use crate::plan::generational::immix::GenImmix;
+use crate::plan::generational::{create_gen_barrier, create_gen_space_mapping};
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
 use crate::plan::AllocationSemantics;
 use crate::util::alloc::allocators::Allocators;
 use crate::util::alloc::BumpAllocator;
 use crate::util::{VMMutatorThread, VMWorkerThread};
-use crate::vm::{ObjectModel, VMBinding};
+use crate::vm::VMBinding;
 use crate::MMTK;
 
 pub fn genimmix_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {}
 
-pub fn genimmix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+pub fn genimmix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
     // reset nursery allocator
     let bump_allocator = unsafe {
         mutator
@@ -40,10 +40,7 @@ pub fn create_genimmix_mutator<VM: VMBinding>(
 
     Mutator {
         allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
-        barrier: Box::new(ObjectRememberingBarrier::<GenNurseryProcessEdges<VM>>::new(
-            mmtk,
-            *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
-        )),
+        barrier: create_gen_barrier(mmtk, mutator_tls, &genimmix.gen.nursery),
         mutator_tls,
         config,
         plan: genimmix,
@@ -0,0 +1,18 @@
+use super::global::GenMarkSweep;
+use crate::plan::generational::gc_work::GenNurseryProcessEdges;
+use crate::scheduler::gc_work::SFTProcessEdges;
+use crate::vm::*;
+
+pub struct GenMarkSweepNurseryGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for GenMarkSweepNurseryGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = GenMarkSweep<VM>;
+    type ProcessEdgesWorkType = GenNurseryProcessEdges<VM>;
+}
+
+pub struct GenMarkSweepMatureGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for GenMarkSweepMatureGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = GenMarkSweep<VM>;
+    type ProcessEdgesWorkType = SFTProcessEdges<Self::VM>;
+}
@@ -0,0 +1,218 @@
+use super::gc_work::{GenMarkSweepMatureGCWorkContext, GenMarkSweepNurseryGCWorkContext};
+use crate::plan::generational::global::Gen;
+use crate::plan::generational::{
+    gen_constraints_for_barriers, select_gen_constraints, GEN_BARRIERS,
+};
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::GcStatus;
+use crate::plan::marksweep::gc_work::MSSweepChunks;
+use crate::plan::AllocationSemantics;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::mallocspace::metadata::ACTIVE_CHUNK_METADATA_SPEC;
+use crate::policy::mallocspace::MallocSpace;
+use crate::policy::space::Space;
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+#[cfg(not(feature = "global_alloc_bit"))]
+use crate::util::alloc_bit::ALLOC_SIDE_METADATA_SPEC;
+use crate::util::copy::*;
+use crate::util::heap::layout::heap_layout::Mmapper;
+use crate::util::heap::layout::heap_layout::VMMap;
+use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::HeapMeta;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::options::UnsafeOptionsWrapper;
+use crate::util::VMWorkerThread;
+use crate::vm::*;
+
+use enum_map::EnumMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Generational mark-sweep. Objects are allocated into a copying nursery, and the survivors of a nursery GC
+/// are promoted into a non-moving mark-sweep space that allocates with malloc. A full heap GC marks the
+/// mature space and sweeps it, in the same way as the mark-sweep plan.
+pub struct GenMarkSweep<VM: VMBinding> {
+    /// Generational plan, which includes a nursery space and operations related with nursery.
+    pub gen: Gen<VM>,
+    /// A mark-sweep space as the mature space.
+    pub ms: MallocSpace<VM>,
+}
+
+pub const GENMS_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    // Objects in the mature space are marked with non-atomic checks, the same as the mark-sweep plan.
+    may_trace_duplicate_edges: true,
+    ..crate::plan::generational::GEN_CONSTRAINTS
+};
+/// The constraints of GenMarkSweep with each barrier of generational plans.
+static GENMS_BARRIER_CONSTRAINTS: [PlanConstraints; GEN_BARRIERS] =
+    gen_constraints_for_barriers(GENMS_CONSTRAINTS);
+
+impl<VM: VMBinding> Plan for GenMarkSweep<VM> {
+    type VM = VM;
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        self.gen.constraints
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::PromoteToMature => CopySelector::MallocSpace(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::MallocSpace(0), &self.ms)],
+            constraints: self.gen.constraints,
+        }
+    }
+
+    fn force_full_heap_collection(&self) {
+        self.gen.force_full_heap_collection()
+    }
+
+    fn last_collection_full_heap(&self) -> bool {
+        self.gen.last_collection_full_heap()
+    }
+
+    fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool
+    where
+        Self: Sized,
+    {
+        self.gen.collection_required(self, space_full, space)
+    }
+
+    fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
+        self.gen.gc_init(heap_size, vm_map);
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        let is_full_heap = self.request_full_heap_collection();
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+        if is_full_heap {
+            debug!("Full heap GC");
+            scheduler.schedule_common_work::<GenMarkSweepMatureGCWorkContext<VM>>(self);
+            // Nursery objects are promoted into the mature space until the transitive closure is done,
+            // and they may be in new chunks. So we generate the sweep work after the closure.
+            scheduler.work_buckets[WorkBucketStage::Release]
+                .add(MSSweepChunks::<VM>::new(&self.ms));
+        } else {
+            debug!("Nursery GC");
+            scheduler.schedule_common_work::<GenMarkSweepNurseryGCWorkContext<VM>>(self);
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &*super::mutator::ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let full_heap = !self.is_current_gc_nursery();
+        Gen::record_usage_before_gc(self);
+        self.gen.prepare(tls);
+        self.ms.prepare(full_heap);
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        self.gen.release(tls);
+        Gen::adjust_nursery_size(self);
+        self.gen
+            .set_next_gc_full_heap(Gen::should_next_gc_be_full_heap(self));
+    }
+
+    fn get_collection_reserved_pages(&self) -> usize {
+        self.gen.get_collection_reserved_pages()
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.gen.get_used_pages() + self.ms.reserved_pages()
+    }
+
+    /// Return the number of pages avilable for allocation. Assuming all future allocations goes to nursery.
+    fn get_available_pages(&self) -> usize {
+        // super.get_pages_avail() / 2 to reserve pages for copying
+        (self
+            .get_total_pages()
+            .saturating_sub(self.get_reserved_pages()))
+            >> 1
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.gen.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.gen.common
+    }
+
+    fn generational(&self) -> &Gen<VM> {
+        &self.gen
+    }
+
+    fn is_current_gc_nursery(&self) -> bool {
+        !self.gen.gc_full_heap.load(Ordering::SeqCst)
+    }
+}
+
+impl<VM: VMBinding> GenMarkSweep<VM> {
+    pub fn new(
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+    ) -> Self {
+        let heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let constraints = select_gen_constraints(&GENMS_BARRIER_CONSTRAINTS, &options);
+        // if global_alloc_bit is enabled, ALLOC_SIDE_METADATA_SPEC will be added to
+        // SideMetadataContext by default, so we don't need to add it here.
+        #[cfg(feature = "global_alloc_bit")]
+        let global_metadata_specs =
+            crate::plan::generational::new_generational_global_metadata_specs_with::<VM>(
+                constraints.barrier,
+                &[ACTIVE_CHUNK_METADATA_SPEC],
+            );
+        // if global_alloc_bit is NOT enabled,
+        // we need to add ALLOC_SIDE_METADATA_SPEC to SideMetadataContext here.
+        #[cfg(not(feature = "global_alloc_bit"))]
+        let global_metadata_specs =
+            crate::plan::generational::new_generational_global_metadata_specs_with::<VM>(
+                constraints.barrier,
+                &[ALLOC_SIDE_METADATA_SPEC, ACTIVE_CHUNK_METADATA_SPEC],
+            );
+
+        let genms = GenMarkSweep {
+            ms: MallocSpace::new(global_metadata_specs.clone()),
+            gen: Gen::new(
+                heap,
+                global_metadata_specs,
+                constraints,
+                vm_map,
+                mmapper,
+                options,
+                scheduler,
+            ),
+        };
+
+        // Use SideMetadataSanity to check if each spec is valid. This is also needed for check
+        // side metadata in extreme_assertions.
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            genms
+                .gen
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            genms
+                .ms
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        genms
+    }
+
+    fn request_full_heap_collection(&self) -> bool {
+        self.gen
+            .request_full_heap_collection(self.get_total_pages(), self.get_reserved_pages())
+    }
+}
@@ -0,0 +1,9 @@
+//! Plan: generational mark-sweep
+
+pub(in crate::plan) mod gc_work;
+pub(in crate::plan) mod global;
+pub(in crate::plan) mod mutator;
+
+pub use self::global::GenMarkSweep;
+
+pub use self::global::GENMS_CONSTRAINTS;
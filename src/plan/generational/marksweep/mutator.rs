@@ -0,0 +1,48 @@
+pub(super) use super::super::ALLOCATOR_MAPPING;
+use crate::plan::generational::marksweep::GenMarkSweep;
+use crate::plan::generational::{create_gen_barrier, create_gen_space_mapping};
+use crate::plan::mutator_context::common_mutator_release;
+use crate::plan::mutator_context::Mutator;
+use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::AllocationSemantics;
+use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::BumpAllocator;
+use crate::util::{VMMutatorThread, VMWorkerThread};
+use crate::vm::VMBinding;
+use crate::MMTK;
+
+pub fn genms_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {}
+
+pub fn genms_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
+    // reset nursery allocator
+    let bump_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<BumpAllocator<VM>>()
+    .unwrap();
+    bump_allocator.reset();
+}
+
+pub fn create_genms_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let genms = mmtk.plan.downcast_ref::<GenMarkSweep<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &*ALLOCATOR_MAPPING,
+        space_mapping: Box::new(create_gen_space_mapping(&*mmtk.plan, &genms.gen.nursery)),
+        prepare_func: &genms_mutator_prepare,
+        release_func: &genms_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: create_gen_barrier(mmtk, mutator_tls, &genms.gen.nursery),
+        mutator_tls,
+        config,
+        plan: genms,
+    }
+}
@@ -1,7 +1,10 @@
 use enum_map::EnumMap;
 
 ///! Generational plans
-use crate::plan::barriers::BarrierSelector;
+use crate::plan::barriers::{
+    Barrier, BarrierSelector, CardMarkingBarrier, FieldRememberingBarrier, NoBarrier,
+    ObjectRememberingBarrier, CARD_TABLE_SPEC, FIELD_LOG_BIT_SPEC,
+};
 use crate::plan::mutator_context::create_allocator_mapping;
 use crate::plan::AllocationSemantics;
 use crate::plan::PlanConstraints;
@@ -10,10 +13,14 @@ use crate::policy::space::Space;
 use crate::util::alloc::AllocatorSelector;
 use crate::util::metadata::side_metadata::SideMetadataContext;
 use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::options::{GenBarrierSelector, Options};
+use crate::util::VMMutatorThread;
 use crate::vm::ObjectModel;
 use crate::vm::VMBinding;
 use crate::Plan;
+use crate::MMTK;
 
+use self::gc_work::GenNurseryProcessEdges;
 use super::mutator_context::create_space_mapping;
 use super::mutator_context::ReservedAllocators;
 
@@ -23,46 +30,117 @@ use super::mutator_context::ReservedAllocators;
 pub mod copying;
 /// Generational immix (GenImmix)
 pub mod immix;
+/// Generational mark-sweep (GenMarkSweep)
+pub mod marksweep;
 
 // Common generational code
 
 pub(super) mod gc_work;
 pub(super) mod global;
+mod nursery_sizing;
 
+/// Full heap collection as nursery GC.
+///
 /// # Barrier overhead measurement:
 ///  - Set `FULL_NURSERY_GC` to `true`.
-/// ## 1. Baseline: No barrier
-///  - Set `ACTIVE_BARRIER` to `BarrierSelector::NoBarrier`.
-/// ## 2. Object barrier
-///  - Set `ACTIVE_BARRIER` to `BarrierSelector::ObjectBarrier`.
-pub const ACTIVE_BARRIER: BarrierSelector = BarrierSelector::ObjectBarrier;
-/// Full heap collection as nursery GC.
+///  - Select the barrier with the `gen_barrier` option: `NoBarrier` for the baseline, and `ObjectBarrier`,
+///    `FieldBarrier` or `CardBarrier` for the barrier to measure.
 pub const FULL_NURSERY_GC: bool = false;
 
-/// Constraints for generational plans. Each generational plan should overwrite based on this constant.
-pub const GEN_CONSTRAINTS: PlanConstraints = PlanConstraints {
-    moves_objects: true,
-    gc_header_bits: 2,
-    gc_header_words: 0,
-    num_specialized_scans: 1,
-    needs_log_bit: ACTIVE_BARRIER.equals(BarrierSelector::ObjectBarrier),
-    barrier: ACTIVE_BARRIER,
-    max_non_los_default_alloc_bytes: crate::util::rust_util::min_of_usize(
-        crate::plan::plan_constraints::MAX_NON_LOS_ALLOC_BYTES_COPYING_PLAN,
-        crate::util::options::NURSERY_SIZE,
-    ),
-    ..PlanConstraints::default()
-};
+/// Constraints for generational plans with the object barrier. Each generational plan should overwrite based on this constant,
+/// and use [`gen_constraints_for_barriers`] to support the other barriers.
+pub const GEN_CONSTRAINTS: PlanConstraints = with_gen_barrier(
+    PlanConstraints {
+        moves_objects: true,
+        gc_header_bits: 2,
+        gc_header_words: 0,
+        num_specialized_scans: 1,
+        max_non_los_default_alloc_bytes: crate::util::rust_util::min_of_usize(
+            crate::plan::plan_constraints::MAX_NON_LOS_ALLOC_BYTES_COPYING_PLAN,
+            crate::util::options::NURSERY_SIZE,
+        ),
+        ..PlanConstraints::default()
+    },
+    BarrierSelector::ObjectBarrier,
+);
+
+/// The number of barriers that generational plans support, i.e. the number of `GenBarrierSelector` variants.
+pub const GEN_BARRIERS: usize = 4;
+
+/// Use the barrier in the constraints. The object barrier and the card-marking barrier need the log bit.
+const fn with_gen_barrier(
+    constraints: PlanConstraints,
+    barrier: BarrierSelector,
+) -> PlanConstraints {
+    PlanConstraints {
+        needs_log_bit: barrier.equals(BarrierSelector::ObjectBarrier)
+            || barrier.equals(BarrierSelector::CardBarrier),
+        barrier,
+        ..constraints
+    }
+}
+
+/// The constraints of a generational plan with each barrier, in the order of the `GenBarrierSelector` variants.
+/// A plan should keep this in a static, and pick its constraints with [`select_gen_constraints`].
+pub const fn gen_constraints_for_barriers(
+    constraints: PlanConstraints,
+) -> [PlanConstraints; GEN_BARRIERS] {
+    [
+        with_gen_barrier(constraints, BarrierSelector::NoBarrier),
+        with_gen_barrier(constraints, BarrierSelector::ObjectBarrier),
+        with_gen_barrier(constraints, BarrierSelector::FieldBarrier),
+        with_gen_barrier(constraints, BarrierSelector::CardBarrier),
+    ]
+}
 
-/// Create global side metadata specs for generational plans. This will call SideMetadataContext::new_global_specs().
+/// Whether generational plans support the barrier, i.e. whether it has constraints in
+/// [`gen_constraints_for_barriers`]. This is the validator of the `gen_barrier` option, so the generational
+/// plans only see the barriers that [`create_gen_barrier`] creates.
+pub(crate) fn is_gen_barrier_supported(barrier: &GenBarrierSelector) -> bool {
+    (*barrier as usize) < GEN_BARRIERS
+}
+
+/// Pick the constraints for the barrier selected by the `gen_barrier` option.
+pub fn select_gen_constraints(
+    constraints: &'static [PlanConstraints; GEN_BARRIERS],
+    options: &Options,
+) -> &'static PlanConstraints {
+    &constraints[*options.gen_barrier as usize]
+}
+
+/// Create global side metadata specs for generational plans with the barrier. This will call SideMetadataContext::new_global_specs().
 /// So if a plan calls this, it should not call SideMetadataContext::new_global_specs() again.
-pub fn new_generational_global_metadata_specs<VM: VMBinding>() -> Vec<SideMetadataSpec> {
-    let specs = if ACTIVE_BARRIER == BarrierSelector::ObjectBarrier {
-        crate::util::metadata::extract_side_metadata(&[*VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC])
-    } else {
-        vec![]
+pub fn new_generational_global_metadata_specs<VM: VMBinding>(
+    barrier: BarrierSelector,
+) -> Vec<SideMetadataSpec> {
+    new_generational_global_metadata_specs_with::<VM>(barrier, &[])
+}
+
+/// Create global side metadata specs for generational plans with the barrier, with the plan-specific global specs in `specs`.
+/// Similar to [`new_generational_global_metadata_specs`], a plan that calls this should not call
+/// SideMetadataContext::new_global_specs() again.
+pub fn new_generational_global_metadata_specs_with<VM: VMBinding>(
+    barrier: BarrierSelector,
+    specs: &[SideMetadataSpec],
+) -> Vec<SideMetadataSpec> {
+    let mut global_specs = match barrier {
+        BarrierSelector::ObjectBarrier => {
+            crate::util::metadata::extract_side_metadata(&[*VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC])
+        }
+        BarrierSelector::FieldBarrier => vec![FIELD_LOG_BIT_SPEC],
+        BarrierSelector::CardBarrier => {
+            let mut specs = crate::util::metadata::extract_side_metadata(&[
+                *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+            ]);
+            specs.push(CARD_TABLE_SPEC);
+            // For the slots written without their objects.
+            specs.push(FIELD_LOG_BIT_SPEC);
+            specs
+        }
+        _ => vec![],
     };
-    SideMetadataContext::new_global_specs(&specs)
+    global_specs.extend_from_slice(specs);
+    SideMetadataContext::new_global_specs(&global_specs)
 }
 
 const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
@@ -78,6 +156,35 @@ lazy_static! {
     };
 }
 
+/// Create the barrier for a mutator of a generational plan, based on the barrier in the plan constraints.
+fn create_gen_barrier<VM: VMBinding>(
+    mmtk: &'static MMTK<VM>,
+    mutator_tls: VMMutatorThread,
+    nursery: &'static CopySpace<VM>,
+) -> Box<dyn Barrier> {
+    let barrier = mmtk.plan.constraints().barrier;
+    match barrier {
+        BarrierSelector::NoBarrier => Box::new(NoBarrier),
+        BarrierSelector::ObjectBarrier => Box::new(ObjectRememberingBarrier::<
+            GenNurseryProcessEdges<VM>,
+        >::new(
+            mmtk, *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+        )),
+        BarrierSelector::FieldBarrier => Box::new(FieldRememberingBarrier::<
+            GenNurseryProcessEdges<VM>,
+        >::new(mmtk, mutator_tls, nursery)),
+        BarrierSelector::CardBarrier => {
+            Box::new(CardMarkingBarrier::<GenNurseryProcessEdges<VM>>::new(
+                mmtk,
+                *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+                nursery,
+            ))
+        }
+        // The `gen_barrier` option only allows the barriers above.
+        _ => unreachable!(),
+    }
+}
+
 fn create_gen_space_mapping<VM: VMBinding>(
     plan: &'static dyn Plan<VM = VM>,
     nursery: &'static CopySpace<VM>,
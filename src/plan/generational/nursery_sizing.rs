@@ -0,0 +1,289 @@
+//! Adaptive nursery sizing.
+//!
+//! If the options `min_nursery` and `max_nursery` give a range of nursery sizes, the nursery size that
+//! triggers a nursery GC is adjusted after each nursery GC. The nursery grows if too many objects survive
+//! the nursery GC (objects are not given enough time to die), or if the mutators spend too much time in
+//! nursery GCs. It shrinks if few objects survive and nursery GCs are cheap, so the mutators touch less
+//! memory. If there is a pause time goal (see [`PauseGoal`]), the nursery shrinks after a nursery GC that
+//! exceeds the goal, and it only grows if the pause is expected to stay within the goal. Otherwise, the nursery
+//! size stays at `max_nursery`.
+//!
+//! Note that `min_nursery` and `max_nursery` have the same default value, so the nursery size is fixed
+//! by default. Set `min_nursery` to a smaller value to enable adaptive nursery sizing.
+
+use crate::plan::PauseGoal;
+use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::conversions::bytes_to_pages_up;
+use crate::util::options::Options;
+use crate::util::statistics::stats::Stats;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// If more than this fraction of the nursery survives a nursery GC, the nursery grows.
+const TARGET_SURVIVAL_RATE: f64 = 0.1;
+/// If the mutators spend more than this fraction of time in nursery GCs, the nursery grows.
+const TARGET_GC_OVERHEAD: f64 = 0.05;
+/// How much the nursery grows each time
+const GROW_FACTOR: f64 = 1.5;
+/// How much the nursery shrinks each time
+const SHRINK_FACTOR: f64 = 0.8;
+
+/// What we observed about the current GC.
+struct Observations {
+    /// When the last GC ended
+    last_gc_end: Instant,
+    /// The pages in the nursery when the current GC started
+    nursery_pages_before_gc: usize,
+    /// The used pages of the plan when the current GC started
+    used_pages_before_gc: usize,
+}
+
+pub struct NurserySizing {
+    /// Is the nursery size allowed to change?
+    adaptive: bool,
+    /// The minimum nursery size in pages
+    min_pages: usize,
+    /// The maximum nursery size in pages
+    max_pages: usize,
+    /// The current nursery size in pages. A nursery GC is triggered when the nursery reaches this size.
+    current_pages: AtomicUsize,
+    observations: Mutex<Observations>,
+    /// The current nursery size in bytes, reported with the statistics
+    size_gauge: Arc<AtomicUsize>,
+}
+
+impl NurserySizing {
+    pub fn new(stats: &Stats) -> Self {
+        NurserySizing {
+            adaptive: false,
+            min_pages: 0,
+            max_pages: 0,
+            current_pages: AtomicUsize::new(0),
+            observations: Mutex::new(Observations {
+                last_gc_end: Instant::now(),
+                nursery_pages_before_gc: 0,
+                used_pages_before_gc: 0,
+            }),
+            size_gauge: stats.new_gauge("nursery.size"),
+        }
+    }
+
+    /// Decide the range of the nursery size from the options. The nursery starts at `max_nursery`.
+    pub fn init(&mut self, options: &Options) {
+        self.max_pages = bytes_to_pages_up(*options.max_nursery);
+        // Only max_nursery may be set, and it could be smaller than the default min_nursery.
+        self.min_pages = bytes_to_pages_up(*options.min_nursery).min(self.max_pages);
+        self.adaptive = self.min_pages < self.max_pages;
+        self.current_pages.store(self.max_pages, Ordering::Relaxed);
+        self.size_gauge
+            .store(self.max_pages * BYTES_IN_PAGE, Ordering::Relaxed);
+        self.observations.lock().unwrap().last_gc_end = Instant::now();
+    }
+
+    /// The current nursery size in pages.
+    #[inline(always)]
+    pub fn get_nursery_pages(&self) -> usize {
+        self.current_pages.load(Ordering::Relaxed)
+    }
+
+    /// A GC starts. `nursery_pages` is the number of pages in the nursery, and `used_pages` is the number
+    /// of pages used by the plan (including the nursery), both before any object is copied.
+    pub fn on_gc_start(&self, nursery_pages: usize, used_pages: usize) {
+        if !self.adaptive {
+            return;
+        }
+        let mut obs = self.observations.lock().unwrap();
+        obs.nursery_pages_before_gc = nursery_pages;
+        obs.used_pages_before_gc = used_pages;
+    }
+
+    /// A GC ends. `used_pages` is the number of pages used by the plan after all the spaces are released,
+    /// and `pause` is the pause time of the GC so far. The nursery size is only adjusted after a nursery GC.
+    pub fn on_gc_end(
+        &self,
+        nursery_gc: bool,
+        used_pages: usize,
+        pause: Duration,
+        pause_goal: &PauseGoal,
+    ) {
+        if !self.adaptive {
+            return;
+        }
+        let mut obs = self.observations.lock().unwrap();
+        let now = Instant::now();
+        let mutator_time = now
+            .duration_since(obs.last_gc_end)
+            .saturating_sub(pause)
+            .as_secs_f64();
+        obs.last_gc_end = now;
+        if !nursery_gc || obs.nursery_pages_before_gc == 0 {
+            return;
+        }
+
+        // The nursery is empty after a nursery GC, so whatever is used in addition to the old mature
+        // objects has survived the nursery GC.
+        let mature_pages_before_gc = obs
+            .used_pages_before_gc
+            .saturating_sub(obs.nursery_pages_before_gc);
+        let survived_pages = used_pages.saturating_sub(mature_pages_before_gc);
+        let survival_rate = survived_pages as f64 / obs.nursery_pages_before_gc as f64;
+        let gc_time = pause.as_secs_f64();
+        let gc_overhead = if gc_time + mutator_time > 0f64 {
+            gc_time / (gc_time + mutator_time)
+        } else {
+            0f64
+        };
+        // We assume the pause time is proportional to the nursery size.
+        let can_grow = !pause_goal.is_exceeded_by(pause.mul_f64(GROW_FACTOR));
+
+        let old_pages = self.get_nursery_pages();
+        let new_pages = if pause_goal.is_exceeded_by(pause) {
+            (old_pages as f64 * SHRINK_FACTOR) as usize
+        } else if can_grow
+            && (survival_rate > TARGET_SURVIVAL_RATE || gc_overhead > TARGET_GC_OVERHEAD)
+        {
+            (old_pages as f64 * GROW_FACTOR) as usize
+        } else if survival_rate < TARGET_SURVIVAL_RATE / 2f64
+            && gc_overhead < TARGET_GC_OVERHEAD / 2f64
+        {
+            (old_pages as f64 * SHRINK_FACTOR) as usize
+        } else {
+            old_pages
+        };
+        let new_pages = new_pages.max(self.min_pages).min(self.max_pages);
+        if new_pages != old_pages {
+            debug!(
+                "Resize nursery from {} pages to {} pages (survival rate = {:.3}, GC overhead = {:.3}, pause = {:?})",
+                old_pages, new_pages, survival_rate, gc_overhead, pause
+            );
+            self.current_pages.store(new_pages, Ordering::Relaxed);
+            self.size_gauge
+                .store(new_pages * BYTES_IN_PAGE, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_util::serial_test;
+
+    const MB: usize = 1024 * 1024;
+    const PAGES_IN_MB: usize = MB / BYTES_IN_PAGE;
+
+    fn new_sizing(
+        min_nursery: usize,
+        max_nursery: usize,
+        max_pause_ms: usize,
+    ) -> (NurserySizing, PauseGoal) {
+        let mut options = Options::default();
+        options.min_nursery.value = min_nursery;
+        options.max_nursery.value = max_nursery;
+        options.max_pause_ms.value = max_pause_ms;
+        let stats = Stats::new(&options);
+        let mut sizing = NurserySizing::new(&stats);
+        sizing.init(&options);
+        let mut pause_goal = PauseGoal::new();
+        pause_goal.init(&options);
+        (sizing, pause_goal)
+    }
+
+    /// Run a nursery GC that collects a full nursery, where `survived_pages` of the nursery survive.
+    fn nursery_gc(
+        sizing: &NurserySizing,
+        pause_goal: &PauseGoal,
+        survived_pages: usize,
+        pause: Duration,
+    ) {
+        let mature_pages = 1000;
+        let nursery_pages = sizing.get_nursery_pages();
+        sizing.on_gc_start(nursery_pages, mature_pages + nursery_pages);
+        sizing.on_gc_end(true, mature_pages + survived_pages, pause, pause_goal);
+    }
+
+    #[test]
+    fn fixed_by_default() {
+        serial_test(|| {
+            let (sizing, pause_goal) = new_sizing(32 * MB, 32 * MB, 0);
+            assert!(!sizing.adaptive);
+            assert_eq!(sizing.get_nursery_pages(), 32 * PAGES_IN_MB);
+            nursery_gc(&sizing, &pause_goal, 0, Duration::ZERO);
+            assert_eq!(sizing.get_nursery_pages(), 32 * PAGES_IN_MB);
+        })
+    }
+
+    #[test]
+    fn min_nursery_larger_than_max_nursery() {
+        serial_test(|| {
+            let (sizing, _) = new_sizing(64 * MB, 16 * MB, 0);
+            assert!(!sizing.adaptive);
+            assert_eq!(sizing.get_nursery_pages(), 16 * PAGES_IN_MB);
+        })
+    }
+
+    #[test]
+    fn shrink_and_grow_with_survival_rate() {
+        serial_test(|| {
+            let (sizing, pause_goal) = new_sizing(MB, 32 * MB, 0);
+            assert!(sizing.adaptive);
+            let max_pages = 32 * PAGES_IN_MB;
+            // Few objects survive, so the nursery shrinks.
+            nursery_gc(&sizing, &pause_goal, 0, Duration::ZERO);
+            let shrunk_pages = (max_pages as f64 * SHRINK_FACTOR) as usize;
+            assert_eq!(sizing.get_nursery_pages(), shrunk_pages);
+            assert_eq!(
+                sizing.size_gauge.load(Ordering::Relaxed),
+                shrunk_pages * BYTES_IN_PAGE
+            );
+            // Half of the nursery survives, so the nursery grows, but not above max_nursery.
+            nursery_gc(&sizing, &pause_goal, shrunk_pages / 2, Duration::ZERO);
+            assert_eq!(sizing.get_nursery_pages(), max_pages);
+        })
+    }
+
+    #[test]
+    fn never_below_min_nursery() {
+        serial_test(|| {
+            let (sizing, pause_goal) = new_sizing(30 * MB, 32 * MB, 0);
+            nursery_gc(&sizing, &pause_goal, 0, Duration::ZERO);
+            assert_eq!(sizing.get_nursery_pages(), 30 * PAGES_IN_MB);
+        })
+    }
+
+    #[test]
+    fn full_heap_gc_keeps_size() {
+        serial_test(|| {
+            let (sizing, pause_goal) = new_sizing(MB, 32 * MB, 0);
+            let nursery_pages = sizing.get_nursery_pages();
+            sizing.on_gc_start(nursery_pages, 1000 + nursery_pages);
+            sizing.on_gc_end(false, 1000, Duration::ZERO, &pause_goal);
+            assert_eq!(sizing.get_nursery_pages(), nursery_pages);
+        })
+    }
+
+    #[test]
+    fn shrink_when_pause_goal_exceeded() {
+        serial_test(|| {
+            let (sizing, pause_goal) = new_sizing(MB, 32 * MB, 10);
+            let max_pages = 32 * PAGES_IN_MB;
+            // Many objects survive, but the pause exceeds the goal.
+            nursery_gc(
+                &sizing,
+                &pause_goal,
+                max_pages / 2,
+                Duration::from_millis(20),
+            );
+            let shrunk_pages = (max_pages as f64 * SHRINK_FACTOR) as usize;
+            assert_eq!(sizing.get_nursery_pages(), shrunk_pages);
+            // The pause is within the goal, but a larger nursery would exceed it.
+            nursery_gc(
+                &sizing,
+                &pause_goal,
+                shrunk_pages / 2,
+                Duration::from_millis(8),
+            );
+            assert_eq!(sizing.get_nursery_pages(), shrunk_pages);
+        })
+    }
+}
@@ -1,6 +1,9 @@
 //! The global part of a plan implementation.
 
+use super::gc_overhead_limit::GCOverheadLimit;
 use super::gc_requester::GCRequester;
+use super::gc_trigger::{GCTriggerPolicy, PlanGCTrigger};
+use super::pause_goal::PauseGoal;
 use super::PlanConstraints;
 use crate::mmtk::MMTK;
 use crate::plan::generational::global::Gen;
@@ -8,17 +11,21 @@ use crate::plan::transitive_closure::TransitiveClosure;
 use crate::plan::Mutator;
 use crate::policy::immortalspace::ImmortalSpace;
 use crate::policy::largeobjectspace::LargeObjectSpace;
+use crate::policy::marksweepspace::MarkSweepSpace;
 use crate::policy::space::Space;
 use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
 #[cfg(feature = "analysis")]
 use crate::util::analysis::AnalysisManager;
-use crate::util::conversions::bytes_to_pages;
 use crate::util::copy::{CopyConfig, GCWorkerCopyContext};
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::layout::map::Map;
+use crate::util::heap::uncommit::UNCOMMITTER;
+use crate::util::heap::zeroing::ZEROER;
+use crate::util::heap::CgroupMemoryLimit;
 use crate::util::heap::HeapMeta;
+use crate::util::heap::HeapSizing;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::SideMetadataSanity;
 use crate::util::metadata::side_metadata::SideMetadataSpec;
@@ -58,6 +65,18 @@ pub fn create_mutator<VM: VMBinding>(
         PlanSelector::MarkCompact => {
             crate::plan::markcompact::mutator::create_markcompact_mutator(tls, &*mmtk.plan)
         }
+        PlanSelector::ConcurrentImmix => {
+            crate::plan::concurrent::immix::mutator::create_concurrent_immix_mutator(tls, mmtk)
+        }
+        PlanSelector::RefCount => {
+            crate::plan::refcount::mutator::create_refcount_mutator(tls, mmtk)
+        }
+        PlanSelector::StickyImmix => {
+            crate::plan::sticky::immix::mutator::create_sticky_immix_mutator(tls, mmtk)
+        }
+        PlanSelector::GenMarkSweep => {
+            crate::plan::generational::marksweep::mutator::create_genms_mutator(tls, mmtk)
+        }
     })
 }
 
@@ -71,26 +90,42 @@ pub fn create_plan<VM: VMBinding>(
     match plan {
         PlanSelector::NoGC => Box::new(crate::plan::nogc::NoGC::new(vm_map, mmapper, options)),
         PlanSelector::SemiSpace => Box::new(crate::plan::semispace::SemiSpace::new(
-            vm_map, mmapper, options,
+            vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::GenCopy => Box::new(crate::plan::generational::copying::GenCopy::new(
-            vm_map, mmapper, options,
+            vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::GenImmix => Box::new(crate::plan::generational::immix::GenImmix::new(
             vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::MarkSweep => Box::new(crate::plan::marksweep::MarkSweep::new(
-            vm_map, mmapper, options,
+            vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::Immix => Box::new(crate::plan::immix::Immix::new(
             vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::PageProtect => Box::new(crate::plan::pageprotect::PageProtect::new(
-            vm_map, mmapper, options,
+            vm_map, mmapper, options, scheduler,
         )),
         PlanSelector::MarkCompact => Box::new(crate::plan::markcompact::MarkCompact::new(
-            vm_map, mmapper, options,
+            vm_map, mmapper, options, scheduler,
         )),
+        PlanSelector::ConcurrentImmix => {
+            Box::new(crate::plan::concurrent::immix::ConcurrentImmix::new(
+                vm_map, mmapper, options, scheduler,
+            ))
+        }
+        PlanSelector::RefCount => Box::new(crate::plan::refcount::RefCount::new(
+            vm_map, mmapper, options, scheduler,
+        )),
+        PlanSelector::StickyImmix => Box::new(crate::plan::sticky::immix::StickyImmix::new(
+            vm_map, mmapper, options, scheduler,
+        )),
+        PlanSelector::GenMarkSweep => {
+            Box::new(crate::plan::generational::marksweep::GenMarkSweep::new(
+                vm_map, mmapper, options, scheduler,
+            ))
+        }
     }
 }
 
@@ -202,7 +237,14 @@ pub trait Plan: 'static + Sync + Downcast {
     fn release(&mut self, tls: VMWorkerThread);
 
     fn poll(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
-        if self.collection_required(space_full, space) {
+        let plan_requires_gc = self.collection_required(space_full, space);
+        // A full space always needs a GC. The trigger policy only decides the other GCs.
+        if space_full
+            || self.base().gc_trigger.is_gc_required(
+                plan_requires_gc,
+                <Self::VM as VMBinding>::VMActivePlan::global(),
+            )
+        {
             // FIXME
             /*if space == META_DATA_SPACE {
                 /* In general we must not trigger a GC on metadata allocation since
@@ -218,22 +260,27 @@ pub trait Plan: 'static + Sync + Downcast {
             return true;
         }
 
-        // FIXME
-        /*if self.concurrent_collection_required() {
-            // FIXME
-            /*if space == self.common().meta_data_space {
-                self.log_poll(space, "Triggering async concurrent collection");
-                Self::trigger_internal_collection_request();
-                return false;
-            } else {*/
+        if self.concurrent_collection_required() {
+            // The mutator does not need to block for a concurrent collection. It will be stopped
+            // by the collection if the collection needs a pause.
             self.log_poll(space, "Triggering concurrent collection");
-            Self::trigger_internal_collection_request();
-            return true;
-        }*/
+            self.base().trigger_internal_collection_request();
+            return false;
+        }
 
         false
     }
 
+    /// Return true if a plan with a concurrent phase should start a concurrent collection.
+    /// Unlike `collection_required()`, the mutator that polls does not block for the collection.
+    fn concurrent_collection_required(&self) -> bool {
+        false
+    }
+
+    /// This is invoked by GC workers when all the work in the `Concurrent` bucket has been done.
+    /// A plan with a concurrent phase can trigger a collection here to finish the phase.
+    fn concurrent_work_drained(&self) {}
+
     fn log_poll(&self, space: &dyn Space<Self::VM>, message: &'static str) {
         info!("  [POLL] {}: {}", space.get_name(), message);
     }
@@ -362,6 +409,16 @@ pub struct BasePlan<VM: VMBinding> {
     pub vm_map: &'static VMMap,
     pub options: Arc<UnsafeOptionsWrapper>,
     pub heap: HeapMeta,
+    /// Decides the heap size after each GC
+    pub heap_sizing: HeapSizing,
+    /// The memory limit of the cgroup, if the heap is sized from it
+    pub cgroup_memory_limit: CgroupMemoryLimit,
+    /// Decides when to trigger a GC
+    pub gc_trigger: Box<dyn GCTriggerPolicy<VM>>,
+    /// The pause time goal
+    pub pause_goal: PauseGoal,
+    /// Detects GCs that take most of the time but recover little memory
+    pub gc_overhead_limit: GCOverheadLimit,
     #[cfg(feature = "sanity")]
     pub inside_sanity: AtomicBool,
     /// A counter for per-mutator stack scanning
@@ -504,6 +561,11 @@ impl<VM: VMBinding> BasePlan<VM> {
             stats,
             mmapper,
             heap,
+            heap_sizing: HeapSizing::new(),
+            cgroup_memory_limit: CgroupMemoryLimit::new(),
+            gc_trigger: Box::new(PlanGCTrigger),
+            pause_goal: PauseGoal::new(),
+            gc_overhead_limit: GCOverheadLimit::new(),
             vm_map,
             options,
             #[cfg(feature = "sanity")]
@@ -522,9 +584,16 @@ impl<VM: VMBinding> BasePlan<VM> {
             self.heap.get_discontig_start(),
             self.heap.get_discontig_end(),
         );
-        self.heap
-            .total_pages
-            .store(bytes_to_pages(heap_size), Ordering::Relaxed);
+        let heap_size = self.cgroup_memory_limit.init(heap_size, &self.options);
+        let total_pages = self.heap_sizing.init(heap_size, &self.options);
+        self.heap.total_pages.store(total_pages, Ordering::Relaxed);
+        UNCOMMITTER.configure(&self.options);
+        self.pause_goal.init(&self.options);
+        self.gc_overhead_limit.init(&self.options);
+        ZEROER.configure(&self.options);
+        if let Some(gc_trigger) = VM::VMCollection::create_gc_trigger() {
+            self.gc_trigger = gc_trigger;
+        }
 
         #[cfg(feature = "code_space")]
         self.code_space.init(vm_map);
@@ -551,8 +620,6 @@ impl<VM: VMBinding> BasePlan<VM> {
     }
 
     /// MMTK has requested stop-the-world activity (e.g., stw within a concurrent gc).
-    // This is not used, as we do not have a concurrent plan.
-    #[allow(unused)]
     pub fn trigger_internal_collection_request(&self) {
         self.last_internal_triggered_collection
             .store(true, Ordering::Relaxed);
@@ -741,15 +808,8 @@ impl<VM: VMBinding> BasePlan<VM> {
 
     /// Return true if this collection was triggered internally.
     pub fn is_internal_triggered_collection(&self) -> bool {
-        let is_internal_triggered = self
-            .last_internal_triggered_collection
-            .load(Ordering::SeqCst);
-        // Remove this assertion when we have concurrent GC.
-        assert!(
-            !is_internal_triggered,
-            "We have no concurrent GC implemented. We should not have internally triggered GC"
-        );
-        is_internal_triggered
+        self.last_internal_triggered_collection
+            .load(Ordering::SeqCst)
     }
 
     /// Increase the allocation bytes and return the current allocation bytes after increasing
@@ -811,7 +871,12 @@ impl<VM: VMBinding> BasePlan<VM> {
         // than the heap's total pages. In that case, we will have to do a GC.
         let heap_full = plan.get_reserved_pages() > plan.get_total_pages();
 
-        space_full || stress_force_gc || heap_full
+        space_full
+            || stress_force_gc
+            || heap_full
+            || self
+                .cgroup_memory_limit
+                .is_approaching_limit(plan.get_reserved_pages())
     }
 
     #[allow(unused_variables)] // depending on the enabled features, base may not be used.
@@ -837,6 +902,9 @@ CommonPlan is for representing state and features used by _many_ plans, but that
 pub struct CommonPlan<VM: VMBinding> {
     pub immortal: ImmortalSpace<VM>,
     pub los: LargeObjectSpace<VM>,
+    /// A collected space whose objects are never moved, for `AllocationSemantics::NonMoving`.
+    /// It is only collected in full heap GCs.
+    pub nonmoving: MarkSweepSpace<VM>,
     pub base: BasePlan<VM>,
 }
 
@@ -845,6 +913,7 @@ impl<VM: VMBinding> CommonPlan<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
         mut heap: HeapMeta,
         constraints: &'static PlanConstraints,
         global_side_metadata_specs: Vec<SideMetadataSpec>,
@@ -871,6 +940,15 @@ impl<VM: VMBinding> CommonPlan<VM> {
                 constraints,
                 false,
             ),
+            nonmoving: MarkSweepSpace::new(
+                "nonmoving",
+                vm_map,
+                mmapper,
+                &mut heap,
+                scheduler,
+                global_side_metadata_specs.clone(),
+                constraints,
+            ),
             base: BasePlan::new(
                 vm_map,
                 mmapper,
@@ -886,10 +964,14 @@ impl<VM: VMBinding> CommonPlan<VM> {
         self.base.gc_init(heap_size, vm_map);
         self.immortal.init(vm_map);
         self.los.init(vm_map);
+        self.nonmoving.init(vm_map);
     }
 
     pub fn get_used_pages(&self) -> usize {
-        self.immortal.reserved_pages() + self.los.reserved_pages() + self.base.get_used_pages()
+        self.immortal.reserved_pages()
+            + self.los.reserved_pages()
+            + self.nonmoving.reserved_pages()
+            + self.base.get_used_pages()
     }
 
     pub fn trace_object<T: TransitiveClosure>(
@@ -905,18 +987,28 @@ impl<VM: VMBinding> CommonPlan<VM> {
             trace!("trace_object: object in los");
             return self.los.trace_object(trace, object);
         }
+        if self.nonmoving.in_space(object) {
+            trace!("trace_object: object in nonmoving space");
+            return self.nonmoving.trace_object(trace, object);
+        }
         self.base.trace_object::<T>(trace, object)
     }
 
     pub fn prepare(&mut self, tls: VMWorkerThread, full_heap: bool) {
         self.immortal.prepare();
         self.los.prepare(full_heap);
+        if full_heap {
+            self.nonmoving.prepare();
+        }
         self.base.prepare(tls, full_heap)
     }
 
     pub fn release(&mut self, tls: VMWorkerThread, full_heap: bool) {
         self.immortal.release();
         self.los.release(full_heap);
+        if full_heap {
+            self.nonmoving.release();
+        }
         self.base.release(tls, full_heap)
     }
 
@@ -932,6 +1024,10 @@ impl<VM: VMBinding> CommonPlan<VM> {
         &self.los
     }
 
+    pub fn get_nonmoving(&self) -> &MarkSweepSpace<VM> {
+        &self.nonmoving
+    }
+
     pub(crate) fn verify_side_metadata_sanity(
         &self,
         side_metadata_sanity_checker: &mut SideMetadataSanity,
@@ -942,6 +1038,8 @@ impl<VM: VMBinding> CommonPlan<VM> {
             .verify_side_metadata_sanity(side_metadata_sanity_checker);
         self.los
             .verify_side_metadata_sanity(side_metadata_sanity_checker);
+        self.nonmoving
+            .verify_side_metadata_sanity(side_metadata_sanity_checker);
     }
 }
 
@@ -957,4 +1055,8 @@ pub enum AllocationSemantics {
     Code = 3,
     ReadOnly = 4,
     LargeCode = 5,
+    /// Objects that are collected but never moved. Objects larger than the mark-sweep size classes
+    /// (`crate::policy::marksweepspace::block::MAX_OBJECT_SIZE`) are allocated in the large object space,
+    /// which does not move objects either.
+    NonMoving = 6,
 }
@@ -7,6 +7,7 @@ use crate::plan::global::GcStatus;
 use crate::plan::AllocationSemantics;
 use crate::plan::Plan;
 use crate::plan::PlanConstraints;
+use crate::policy::immix::defrag::DefragDecision;
 use crate::policy::space::Space;
 use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
@@ -77,13 +78,9 @@ impl<VM: VMBinding> Plan for Immix<VM> {
     fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
         self.base().set_collection_kind::<Self>(self);
         self.base().set_gc_status(GcStatus::GcPrepare);
-        let in_defrag = self.immix_space.decide_whether_to_defrag(
-            self.is_emergency_collection(),
-            true,
-            self.base().cur_collection_attempts.load(Ordering::SeqCst),
-            self.base().is_user_triggered_collection(),
-            *self.base().options.full_heap_system_gc,
-        );
+        let in_defrag = self
+            .immix_space
+            .decide_whether_to_defrag(&DefragDecision::full_heap_gc(self));
 
         // The blocks are not identical, clippy is wrong. Probably it does not recognize the constant type parameter.
         #[allow(clippy::if_same_then_else)]
@@ -142,13 +139,15 @@ impl<VM: VMBinding> Immix<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
-                scheduler,
+                scheduler.clone(),
                 global_metadata_specs.clone(),
+                &IMMIX_CONSTRAINTS,
             ),
             common: CommonPlan::new(
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
                 heap,
                 &IMMIX_CONSTRAINTS,
                 global_metadata_specs,
@@ -1,4 +1,5 @@
 use super::Immix;
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::create_allocator_mapping;
 use crate::plan::mutator_context::create_space_mapping;
 use crate::plan::mutator_context::Mutator;
@@ -26,7 +27,8 @@ pub fn immix_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMW
     immix_allocator.reset();
 }
 
-pub fn immix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+pub fn immix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
     let immix_allocator = unsafe {
         mutator
             .allocators
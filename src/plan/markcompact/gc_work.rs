@@ -1,5 +1,5 @@
 use super::global::MarkCompact;
-use crate::policy::markcompactspace::MarkCompactSpace;
+use crate::policy::markcompactspace::{CompactionRegion, MarkCompactSpace};
 use crate::policy::space::Space;
 use crate::scheduler::gc_work::*;
 use crate::scheduler::GCWork;
@@ -13,15 +13,23 @@ use crate::MMTK;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
-/// iterate through the heap and calculate the new location of live objects
+/// iterate through the heap and calculate the new location of live objects.
+/// This generates a [`SummarizeRegion`] packet for each region of the space.
 pub struct CalculateForwardingAddress<VM: VMBinding> {
     mc_space: &'static MarkCompactSpace<VM>,
 }
 
 impl<VM: VMBinding> GCWork<VM> for CalculateForwardingAddress<VM> {
     #[inline]
-    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
-        self.mc_space.calculate_forwarding_pointer();
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let packets = self
+            .mc_space
+            .regions()
+            .map(|region| {
+                Box::new(SummarizeRegion::<VM>::new(self.mc_space, region)) as Box<dyn GCWork<VM>>
+            })
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::CalculateForwarding].bulk_add(packets);
     }
 }
 
@@ -31,6 +39,57 @@ impl<VM: VMBinding> CalculateForwardingAddress<VM> {
     }
 }
 
+/// compute the size of the live objects in a region. The last region to finish
+/// assigns the destinations of all the regions, and generates a [`ForwardRegion`]
+/// packet for each region.
+pub struct SummarizeRegion<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    region: CompactionRegion,
+}
+
+impl<VM: VMBinding> GCWork<VM> for SummarizeRegion<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        if !self.mc_space.summarize_region(self.region) {
+            return;
+        }
+        self.mc_space.plan_compaction();
+        let packets = self
+            .mc_space
+            .regions()
+            .map(|region| {
+                Box::new(ForwardRegion::<VM>::new(self.mc_space, region)) as Box<dyn GCWork<VM>>
+            })
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::CalculateForwarding].bulk_add(packets);
+    }
+}
+
+impl<VM: VMBinding> SummarizeRegion<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, region: CompactionRegion) -> Self {
+        Self { mc_space, region }
+    }
+}
+
+/// calculate the new location of live objects in a region
+pub struct ForwardRegion<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    region: CompactionRegion,
+}
+
+impl<VM: VMBinding> GCWork<VM> for ForwardRegion<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        self.mc_space.calculate_forwarding_pointer(self.region);
+    }
+}
+
+impl<VM: VMBinding> ForwardRegion<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, region: CompactionRegion) -> Self {
+        Self { mc_space, region }
+    }
+}
+
 /// create another round of root scanning work packets
 /// to update object references
 pub struct UpdateReferences<VM: VMBinding> {
@@ -65,15 +124,25 @@ impl<VM: VMBinding> UpdateReferences<VM> {
     }
 }
 
-/// compact live objects based on forwarding pointers calculated before
+/// compact live objects based on forwarding pointers calculated before.
+/// This generates a [`CompactRegion`] packet for each region that does not
+/// need to wait for other regions.
 pub struct Compact<VM: VMBinding> {
     mc_space: &'static MarkCompactSpace<VM>,
 }
 
 impl<VM: VMBinding> GCWork<VM> for Compact<VM> {
     #[inline]
-    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
-        self.mc_space.compact();
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let packets = self
+            .mc_space
+            .regions_ready_to_compact()
+            .into_iter()
+            .map(|region| {
+                Box::new(CompactRegion::<VM>::new(self.mc_space, region)) as Box<dyn GCWork<VM>>
+            })
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::Compact].bulk_add(packets);
     }
 }
 
@@ -83,6 +152,31 @@ impl<VM: VMBinding> Compact<VM> {
     }
 }
 
+/// compact the live objects in a region. Regions whose destinations overlap
+/// with this region are scheduled once this region is done.
+pub struct CompactRegion<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    region: CompactionRegion,
+}
+
+impl<VM: VMBinding> GCWork<VM> for CompactRegion<VM> {
+    #[inline]
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        for region in self.mc_space.compact(self.region) {
+            worker.add_work(
+                WorkBucketStage::Compact,
+                CompactRegion::<VM>::new(self.mc_space, region),
+            );
+        }
+    }
+}
+
+impl<VM: VMBinding> CompactRegion<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, region: CompactionRegion) -> Self {
+        Self { mc_space, region }
+    }
+}
+
 // Transitive closure to mark live objects
 pub struct MarkingProcessEdges<VM: VMBinding> {
     plan: &'static MarkCompact<VM>,
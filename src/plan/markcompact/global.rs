@@ -174,6 +174,7 @@ impl<VM: VMBinding> MarkCompact<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
         // if global_alloc_bit is enabled, ALLOC_SIDE_METADATA_SPEC will be added to
@@ -202,6 +203,7 @@ impl<VM: VMBinding> MarkCompact<VM> {
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
                 heap,
                 &MARKCOMPACT_CONSTRAINTS,
                 global_metadata_specs,
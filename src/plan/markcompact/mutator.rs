@@ -1,5 +1,6 @@
 use super::MarkCompact; // Add
 use crate::plan::barriers::NoBarrier;
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::create_allocator_mapping;
 use crate::plan::mutator_context::create_space_mapping;
 use crate::plan::mutator_context::Mutator;
@@ -57,15 +58,13 @@ pub fn markcompact_mutator_prepare<VM: VMBinding>(
 ) {
 }
 
-pub fn markcompact_mutator_release<VM: VMBinding>(
-    _mutator: &mut Mutator<VM>,
-    _tls: VMWorkerThread,
-) {
+pub fn markcompact_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
     // reset the thread-local allocation bump pointer
     let markcompact_allocator = unsafe {
-        _mutator
+        mutator
             .allocators
-            .get_allocator_mut(_mutator.config.allocator_mapping[AllocationSemantics::Default])
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
     }
     .downcast_mut::<MarkCompactAllocator<VM>>()
     .unwrap();
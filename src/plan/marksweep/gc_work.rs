@@ -26,19 +26,19 @@ impl<VM: VMBinding> GCWork<VM> for MSSweepChunk<VM> {
 
 /// Work packet that generates sweep jobs for gc workers. Each chunk is given its own work packet
 pub struct MSSweepChunks<VM: VMBinding> {
-    plan: &'static MarkSweep<VM>,
+    ms: &'static MallocSpace<VM>,
 }
 
 impl<VM: VMBinding> MSSweepChunks<VM> {
-    pub fn new(plan: &'static MarkSweep<VM>) -> Self {
-        Self { plan }
+    pub fn new(ms: &'static MallocSpace<VM>) -> Self {
+        Self { ms }
     }
 }
 
 impl<VM: VMBinding> GCWork<VM> for MSSweepChunks<VM> {
     #[inline]
     fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
-        let ms = self.plan.ms_space();
+        let ms = self.ms;
         let mut work_packets: Vec<Box<dyn GCWork<VM>>> = vec![];
         let mut chunk = unsafe { Address::from_usize(ms.chunk_addr_min.load(Ordering::Relaxed)) }; // XXX: have to use AtomicUsize to represent an Address
         let end = unsafe { Address::from_usize(ms.chunk_addr_max.load(Ordering::Relaxed)) }
@@ -2,12 +2,14 @@ use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::GcStatus;
 use crate::plan::marksweep::gc_work::{MSGCWorkContext, MSSweepChunks};
-use crate::plan::marksweep::mutator::ALLOCATOR_MAPPING;
+use crate::plan::marksweep::mutator::{ALLOCATOR_MAPPING, NATIVE_ALLOCATOR_MAPPING};
 use crate::plan::AllocationSemantics;
 use crate::plan::Plan;
 use crate::plan::PlanConstraints;
 use crate::policy::mallocspace::metadata::ACTIVE_CHUNK_METADATA_SPEC;
 use crate::policy::mallocspace::MallocSpace;
+use crate::policy::marksweepspace::block::MAX_OBJECT_SIZE;
+use crate::policy::marksweepspace::MarkSweepSpace;
 use crate::policy::space::Space;
 use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
@@ -18,16 +20,27 @@ use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
 use crate::util::heap::HeapMeta;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
-use crate::util::options::UnsafeOptionsWrapper;
+use crate::util::options::{MarkSweepSpaceSelector, UnsafeOptionsWrapper};
 use crate::util::VMWorkerThread;
 use crate::vm::VMBinding;
 use std::sync::Arc;
 
 use enum_map::EnumMap;
 
+/// The space that MarkSweep allocates objects in, selected by the `marksweep_space` option.
+enum MarkSweepSpaces<VM: VMBinding> {
+    /// The malloc space.
+    Malloc(MallocSpace<VM>),
+    /// The native mark-sweep space.
+    Native(MarkSweepSpace<VM>),
+}
+
 pub struct MarkSweep<VM: VMBinding> {
     common: CommonPlan<VM>,
-    ms: MallocSpace<VM>,
+    /// The space for objects. This is `None` until it is created in `gc_init()`.
+    space: Option<MarkSweepSpaces<VM>>,
+    mmapper: &'static Mmapper,
+    scheduler: Arc<GCWorkScheduler<VM>>,
 }
 
 pub const MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
@@ -39,32 +52,99 @@ pub const MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
     ..PlanConstraints::default()
 };
 
+/// Constraints for MarkSweep with the native mark-sweep space. The binding should allocate objects larger than the
+/// largest size class with `AllocationSemantics::Los`. The free list allocator of the space panics on them.
+pub const NATIVE_MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    max_non_los_default_alloc_bytes: MAX_OBJECT_SIZE,
+    ..MS_CONSTRAINTS
+};
+
 impl<VM: VMBinding> Plan for MarkSweep<VM> {
     type VM = VM;
 
     fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
+        // The space is selected here rather than in `new()`, so the `marksweep_space` option can be set
+        // like other options before `gc_init()`. The native space is discontiguous, and it has to be created
+        // before the common plan finalizes the space map.
+        // if global_alloc_bit is enabled, ALLOC_SIDE_METADATA_SPEC will be added to
+        // SideMetadataContext by default, so we don't need to add it here.
+        #[cfg(feature = "global_alloc_bit")]
+        let global_metadata_specs =
+            SideMetadataContext::new_global_specs(&[ACTIVE_CHUNK_METADATA_SPEC]);
+        // if global_alloc_bit is NOT enabled,
+        // we need to add ALLOC_SIDE_METADATA_SPEC to SideMetadataContext here.
+        #[cfg(not(feature = "global_alloc_bit"))]
+        let global_metadata_specs = SideMetadataContext::new_global_specs(&[
+            ALLOC_SIDE_METADATA_SPEC,
+            ACTIVE_CHUNK_METADATA_SPEC,
+        ]);
+        self.space = Some(if self.is_native() {
+            MarkSweepSpaces::Native(MarkSweepSpace::new(
+                "ms",
+                vm_map,
+                self.mmapper,
+                &mut self.common.base.heap,
+                self.scheduler.clone(),
+                global_metadata_specs,
+                &NATIVE_MS_CONSTRAINTS,
+            ))
+        } else {
+            MarkSweepSpaces::Malloc(MallocSpace::new(global_metadata_specs))
+        });
+
+        // Use SideMetadataSanity to check if each spec is valid. This is also needed for check
+        // side metadata in extreme_assertions.
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            self.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            match self.space.as_ref().unwrap() {
+                MarkSweepSpaces::Malloc(ms) => {
+                    ms.verify_side_metadata_sanity(&mut side_metadata_sanity_checker)
+                }
+                MarkSweepSpaces::Native(native_ms) => {
+                    native_ms.verify_side_metadata_sanity(&mut side_metadata_sanity_checker)
+                }
+            }
+        }
+
         self.common.gc_init(heap_size, vm_map);
+        if let Some(MarkSweepSpaces::Native(native_ms)) = &mut self.space {
+            native_ms.init(vm_map);
+        }
     }
 
     fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
         self.base().set_collection_kind::<Self>(self);
         self.base().set_gc_status(GcStatus::GcPrepare);
         scheduler.schedule_common_work::<MSGCWorkContext<VM>>(self);
-        scheduler.work_buckets[WorkBucketStage::Prepare].add(MSSweepChunks::<VM>::new(self));
+        if let Some(MarkSweepSpaces::Malloc(ms)) = &self.space {
+            scheduler.work_buckets[WorkBucketStage::Prepare].add(MSSweepChunks::<VM>::new(ms));
+        }
     }
 
     fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
-        &*ALLOCATOR_MAPPING
+        if self.is_native() {
+            &*NATIVE_ALLOCATOR_MAPPING
+        } else {
+            &*ALLOCATOR_MAPPING
+        }
     }
 
     fn prepare(&mut self, tls: VMWorkerThread) {
         self.common.prepare(tls, true);
         // Dont need to prepare for MallocSpace
+        if let Some(MarkSweepSpaces::Native(native_ms)) = &mut self.space {
+            native_ms.prepare();
+        }
     }
 
     fn release(&mut self, tls: VMWorkerThread) {
         trace!("Marksweep: Release");
         self.common.release(tls, true);
+        if let Some(MarkSweepSpaces::Native(native_ms)) = &mut self.space {
+            native_ms.release();
+        }
     }
 
     fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
@@ -72,7 +152,12 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
     }
 
     fn get_used_pages(&self) -> usize {
-        self.common.get_used_pages() + self.ms.reserved_pages()
+        self.common.get_used_pages()
+            + match &self.space {
+                Some(MarkSweepSpaces::Malloc(ms)) => ms.reserved_pages(),
+                Some(MarkSweepSpaces::Native(native_ms)) => native_ms.reserved_pages(),
+                None => 0,
+            }
     }
 
     fn base(&self) -> &BasePlan<VM> {
@@ -84,7 +169,11 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
     }
 
     fn constraints(&self) -> &'static PlanConstraints {
-        &MS_CONSTRAINTS
+        if self.is_native() {
+            &NATIVE_MS_CONSTRAINTS
+        } else {
+            &MS_CONSTRAINTS
+        }
     }
 }
 
@@ -93,6 +182,7 @@ impl<VM: VMBinding> MarkSweep<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let heap = HeapMeta::new(HEAP_START, HEAP_END);
         // if global_alloc_bit is enabled, ALLOC_SIDE_METADATA_SPEC will be added to
@@ -108,32 +198,39 @@ impl<VM: VMBinding> MarkSweep<VM> {
             ACTIVE_CHUNK_METADATA_SPEC,
         ]);
 
-        let res = MarkSweep {
-            ms: MallocSpace::new(global_metadata_specs.clone()),
+        MarkSweep {
+            space: None,
+            mmapper,
             common: CommonPlan::new(
                 vm_map,
                 mmapper,
                 options,
+                scheduler.clone(),
                 heap,
                 &MS_CONSTRAINTS,
                 global_metadata_specs,
             ),
-        };
+            scheduler,
+        }
+    }
 
-        // Use SideMetadataSanity to check if each spec is valid. This is also needed for check
-        // side metadata in extreme_assertions.
-        {
-            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
-            res.common
-                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
-            res.ms
-                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+    pub fn ms_space(&self) -> &MallocSpace<VM> {
+        match &self.space {
+            Some(MarkSweepSpaces::Malloc(ms)) => ms,
+            _ => panic!("MarkSweep does not use the malloc space"),
         }
+    }
 
-        res
+    pub fn native_ms_space(&self) -> &MarkSweepSpace<VM> {
+        match &self.space {
+            Some(MarkSweepSpaces::Native(native_ms)) => native_ms,
+            _ => panic!("MarkSweep does not use the native mark-sweep space"),
+        }
     }
 
-    pub fn ms_space(&self) -> &MallocSpace<VM> {
-        &self.ms
+    /// Whether objects are allocated in the native mark-sweep space rather than the malloc space. This follows
+    /// the `marksweep_space` option, which also decides the space created in `gc_init()`.
+    pub fn is_native(&self) -> bool {
+        *self.common.base.options.marksweep_space == MarkSweepSpaceSelector::Native
     }
 }
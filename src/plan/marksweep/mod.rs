@@ -1,8 +1,9 @@
-//! Plan: marksweep (currently using malloc as its freelist allocator)
+//! Plan: marksweep (using malloc or the native marksweep space as its freelist allocator)
 
-mod gc_work;
+pub(in crate::plan) mod gc_work;
 mod global;
 pub mod mutator;
 
 pub use self::global::MarkSweep;
 pub use self::global::MS_CONSTRAINTS;
+pub use self::global::NATIVE_MS_CONSTRAINTS;
@@ -1,5 +1,6 @@
 use super::MarkSweep;
 use crate::plan::barriers::NoBarrier;
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::create_allocator_mapping;
 use crate::plan::mutator_context::create_space_mapping;
 use crate::plan::mutator_context::Mutator;
@@ -8,6 +9,7 @@ use crate::plan::mutator_context::ReservedAllocators;
 use crate::plan::AllocationSemantics;
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::FreeListAllocator;
 use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::VMBinding;
 use crate::Plan;
@@ -17,8 +19,21 @@ pub fn ms_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWor
     // Do nothing
 }
 
-pub fn ms_mutator_release<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
-    // Do nothing
+pub fn ms_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
+}
+
+pub fn native_ms_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
+    // The blocks are swept, so the free lists are no longer valid.
+    let free_list_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<FreeListAllocator<VM>>()
+    .unwrap();
+    free_list_allocator.reset();
 }
 
 const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
@@ -26,12 +41,22 @@ const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
     ..ReservedAllocators::DEFAULT
 };
 
+const NATIVE_RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
+    n_free_list: 1,
+    ..ReservedAllocators::DEFAULT
+};
+
 lazy_static! {
     pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationSemantics, AllocatorSelector> = {
         let mut map = create_allocator_mapping(RESERVED_ALLOCATORS, true);
         map[AllocationSemantics::Default] = AllocatorSelector::Malloc(0);
         map
     };
+    pub static ref NATIVE_ALLOCATOR_MAPPING: EnumMap<AllocationSemantics, AllocatorSelector> = {
+        let mut map = create_allocator_mapping(NATIVE_RESERVED_ALLOCATORS, true);
+        map[AllocationSemantics::Default] = AllocatorSelector::FreeList(0);
+        map
+    };
 }
 
 pub fn create_ms_mutator<VM: VMBinding>(
@@ -39,15 +64,28 @@ pub fn create_ms_mutator<VM: VMBinding>(
     plan: &'static dyn Plan<VM = VM>,
 ) -> Mutator<VM> {
     let ms = plan.downcast_ref::<MarkSweep<VM>>().unwrap();
-    let config = MutatorConfig {
-        allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: Box::new({
-            let mut vec = create_space_mapping(RESERVED_ALLOCATORS, true, plan);
-            vec.push((AllocatorSelector::Malloc(0), ms.ms_space()));
-            vec
-        }),
-        prepare_func: &ms_mutator_prepare,
-        release_func: &ms_mutator_release,
+    let config = if ms.is_native() {
+        MutatorConfig {
+            allocator_mapping: &*NATIVE_ALLOCATOR_MAPPING,
+            space_mapping: Box::new({
+                let mut vec = create_space_mapping(NATIVE_RESERVED_ALLOCATORS, true, plan);
+                vec.push((AllocatorSelector::FreeList(0), ms.native_ms_space()));
+                vec
+            }),
+            prepare_func: &ms_mutator_prepare,
+            release_func: &native_ms_mutator_release,
+        }
+    } else {
+        MutatorConfig {
+            allocator_mapping: &*ALLOCATOR_MAPPING,
+            space_mapping: Box::new({
+                let mut vec = create_space_mapping(RESERVED_ALLOCATORS, true, plan);
+                vec.push((AllocatorSelector::Malloc(0), ms.ms_space()));
+                vec
+            }),
+            prepare_func: &ms_mutator_prepare,
+            release_func: &ms_mutator_release,
+        }
     };
 
     Mutator {
@@ -14,10 +14,16 @@
 //! For more about implementing a plan, it is recommended to read the [MMTk tutorial](/docs/tutorial/Tutorial.md).
 
 mod barriers;
-pub use barriers::BarrierSelector;
+pub use barriers::{
+    ArrayCopy, BarrierFastPath, BarrierFastPathKind, BarrierSelector, BarrierSlowPath,
+    LogBitLocation,
+};
 
 pub(crate) mod gc_requester;
 
+mod gc_trigger;
+pub use gc_trigger::{GCTriggerPolicy, PlanGCTrigger};
+
 mod global;
 pub(crate) use global::create_gc_worker_context;
 pub(crate) use global::create_mutator;
@@ -30,6 +36,12 @@ mod mutator_context;
 pub use mutator_context::Mutator;
 pub use mutator_context::MutatorContext;
 
+mod gc_overhead_limit;
+pub(crate) use gc_overhead_limit::GCOverheadLimit;
+
+mod pause_goal;
+pub(crate) use pause_goal::PauseGoal;
+
 mod plan_constraints;
 pub use plan_constraints::PlanConstraints;
 pub use plan_constraints::DEFAULT_PLAN_CONSTRAINTS;
@@ -40,21 +52,30 @@ pub use tracelocal::TraceLocal;
 mod transitive_closure;
 pub use transitive_closure::{ObjectsClosure, TransitiveClosure};
 
+mod concurrent;
 mod generational;
+pub(crate) use generational::is_gen_barrier_supported;
 mod immix;
 mod markcompact;
 mod marksweep;
 mod nogc;
 mod pageprotect;
+mod refcount;
 mod semispace;
+mod sticky;
 
 // Expose plan constraints as public. Though a binding can get them from plan.constraints(),
 // it is possible for performance reasons that they want the constraints as constants.
 
+pub use concurrent::immix::CONCURRENT_IMMIX_CONSTRAINTS;
 pub use generational::copying::GENCOPY_CONSTRAINTS;
+pub use generational::marksweep::GENMS_CONSTRAINTS;
 pub use immix::IMMIX_CONSTRAINTS;
 pub use markcompact::MARKCOMPACT_CONSTRAINTS;
 pub use marksweep::MS_CONSTRAINTS;
+pub use marksweep::NATIVE_MS_CONSTRAINTS;
 pub use nogc::NOGC_CONSTRAINTS;
 pub use pageprotect::PP_CONSTRAINTS;
+pub use refcount::REFCOUNT_CONSTRAINTS;
 pub use semispace::SS_CONSTRAINTS;
+pub use sticky::immix::STICKY_IMMIX_CONSTRAINTS;
@@ -1,10 +1,12 @@
 //! Mutator context for each application thread.
 
-use crate::plan::barriers::{Barrier, WriteTarget};
+use crate::plan::barriers::{ArrayCopy, Barrier, WriteTarget};
 use crate::plan::global::Plan;
 use crate::plan::AllocationSemantics;
+use crate::policy::marksweepspace::block::MAX_OBJECT_SIZE;
 use crate::policy::space::Space;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::FreeListAllocator;
 use crate::util::{Address, ObjectReference};
 use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::VMBinding;
@@ -80,6 +82,7 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
         offset: isize,
         allocator: AllocationSemantics,
     ) -> Address {
+        let allocator = self.large_object_semantics(size, allocator);
         unsafe {
             self.allocators
                 .get_allocator_mut(self.config.allocator_mapping[allocator])
@@ -88,12 +91,8 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
     }
 
     // Note that this method is slow, and we expect VM bindings that care about performance to implement allocation fastpath sequence in their bindings.
-    fn post_alloc(
-        &mut self,
-        refer: ObjectReference,
-        _bytes: usize,
-        allocator: AllocationSemantics,
-    ) {
+    fn post_alloc(&mut self, refer: ObjectReference, bytes: usize, allocator: AllocationSemantics) {
+        let allocator = self.large_object_semantics(bytes, allocator);
         unsafe {
             self.allocators
                 .get_allocator_mut(self.config.allocator_mapping[allocator])
@@ -111,6 +110,24 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
     }
 }
 
+impl<VM: VMBinding> Mutator<VM> {
+    /// The semantics to allocate an object of `size` bytes with. Non-moving objects that are too large
+    /// for the size classes of the non-moving space are allocated in the large object space. Large
+    /// default objects are not rerouted here: the binding should allocate objects larger than
+    /// `max_non_los_default_alloc_bytes` in the plan constraints with [`AllocationSemantics::Los`].
+    #[inline(always)]
+    fn large_object_semantics(
+        &self,
+        size: usize,
+        semantics: AllocationSemantics,
+    ) -> AllocationSemantics {
+        match semantics {
+            AllocationSemantics::NonMoving if size > MAX_OBJECT_SIZE => AllocationSemantics::Los,
+            _ => semantics,
+        }
+    }
+}
+
 /// Each GC plan should provide their implementation of a MutatorContext. *Note that this trait is no longer needed as we removed
 /// per-plan mutator implementation and we will remove this trait as well in the future.*
 
@@ -139,6 +156,46 @@ pub trait MutatorContext<VM: VMBinding>: Send + 'static {
     fn record_modified_node(&mut self, obj: ObjectReference) {
         self.barrier().post_write_barrier(WriteTarget::Object(obj));
     }
+
+    /// The binding should call this after the reference in the slot is modified,
+    /// if the plan uses a post-write barrier and the binding only knows the address of the field.
+    /// This needs `BarrierSelector::FieldBarrier`, `BarrierSelector::CardBarrier` or
+    /// `BarrierSelector::CoalescingObjectBarrier`. The object barrier panics on it, as it can only log objects.
+    fn record_modified_edge(&mut self, slot: Address) {
+        self.barrier().post_write_barrier(WriteTarget::Slot(slot));
+    }
+
+    /// The binding should call this after the reference in the slot of the object is modified,
+    /// if the plan uses a post-write barrier and the binding knows both the object and the address of the field.
+    /// Some barriers need both (e.g. `BarrierSelector::CardBarrier`).
+    fn record_modified_field(&mut self, obj: ObjectReference, slot: Address) {
+        self.barrier()
+            .post_write_barrier(WriteTarget::Field(obj, slot));
+    }
+
+    /// The binding should call this before a reference field of the object is modified,
+    /// if the plan uses a pre-write barrier (e.g. `BarrierSelector::SATBBarrier`).
+    fn record_modifying_node(&mut self, obj: ObjectReference) {
+        self.barrier().pre_write_barrier(WriteTarget::Object(obj));
+    }
+
+    /// The binding should call this before the destination slots of an array copy are overwritten,
+    /// if the plan uses a pre-write barrier. See [`crate::memory_manager::object_reference_array_copy`].
+    fn record_modifying_array(&mut self, copy: ArrayCopy) {
+        self.barrier().pre_array_copy_barrier(copy);
+    }
+
+    /// The binding should call this after the destination slots of an array copy are overwritten,
+    /// if the plan uses a post-write barrier. See [`crate::memory_manager::object_reference_array_copy`].
+    fn record_modified_array(&mut self, copy: ArrayCopy) {
+        self.barrier().post_array_copy_barrier(copy);
+    }
+
+    /// The binding should call this before the reference in the slot is overwritten,
+    /// if the plan uses a pre-write barrier (e.g. `BarrierSelector::SATBBarrier`).
+    fn record_modifying_edge(&mut self, slot: Address) {
+        self.barrier().pre_write_barrier(WriteTarget::Slot(slot));
+    }
 }
 
 /// This is used for plans to indicate the number of allocators reserved for the plan.
@@ -155,6 +212,7 @@ pub(crate) struct ReservedAllocators {
     pub n_malloc: u8,
     pub n_immix: u8,
     pub n_mark_compact: u8,
+    pub n_free_list: u8,
 }
 
 impl ReservedAllocators {
@@ -164,6 +222,7 @@ impl ReservedAllocators {
         n_malloc: 0,
         n_immix: 0,
         n_mark_compact: 0,
+        n_free_list: 0,
     };
     /// check if the number of each allocator is okay. Panics if any allocator exceeds the max number.
     fn validate(&self) {
@@ -188,6 +247,29 @@ impl ReservedAllocators {
             self.n_mark_compact as usize <= MAX_MARK_COMPACT_ALLOCATORS,
             "Allocator mapping declared more mark compact allocators than the max allowed."
         );
+        assert!(
+            self.n_free_list as usize <= MAX_FREE_LIST_ALLOCATORS,
+            "Allocator mapping declared more free list allocators than the max allowed."
+        );
+    }
+}
+
+/// Mutator release for the allocators of the spaces in CommonPlan. The release function of each plan that
+/// uses CommonPlan should call this.
+pub(crate) fn common_mutator_release<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    _tls: VMWorkerThread,
+) {
+    // The non-moving space is swept in full heap GCs, so the free lists are no longer valid.
+    if mutator.plan.last_collection_full_heap() {
+        let free_list_allocator = unsafe {
+            mutator
+                .allocators
+                .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::NonMoving])
+        }
+        .downcast_mut::<FreeListAllocator<VM>>()
+        .unwrap();
+        free_list_allocator.reset();
     }
 }
 
@@ -234,6 +316,9 @@ pub(crate) fn create_allocator_mapping(
 
         map[AllocationSemantics::Los] = AllocatorSelector::LargeObject(reserved.n_large_object);
         reserved.n_large_object += 1;
+
+        map[AllocationSemantics::NonMoving] = AllocatorSelector::FreeList(reserved.n_free_list);
+        reserved.n_free_list += 1;
     }
 
     reserved.validate();
@@ -295,6 +380,11 @@ pub(crate) fn create_space_mapping<VM: VMBinding>(
             plan.common().get_los(),
         ));
         reserved.n_large_object += 1;
+        vec.push((
+            AllocatorSelector::FreeList(reserved.n_free_list),
+            plan.common().get_nonmoving(),
+        ));
+        reserved.n_free_list += 1;
     }
 
     reserved.validate();
@@ -29,6 +29,8 @@ lazy_static! {
             map[AllocationSemantics::Default] = AllocatorSelector::BumpPointer(0);
             map[AllocationSemantics::Immortal] = AllocatorSelector::BumpPointer(1);
             map[AllocationSemantics::Los] = AllocatorSelector::BumpPointer(2);
+            // Nothing is moved or collected in NoGC, so non-moving objects can go to the default space.
+            map[AllocationSemantics::NonMoving] = AllocatorSelector::BumpPointer(0);
             map
         } else {
             *ALLOCATOR_MAPPING_SINGLE_SPACE
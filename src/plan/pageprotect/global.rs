@@ -97,6 +97,7 @@ impl<VM: VMBinding> PageProtect<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
@@ -117,6 +118,7 @@ impl<VM: VMBinding> PageProtect<VM> {
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
                 heap,
                 &CONSTRAINTS,
                 global_metadata_specs,
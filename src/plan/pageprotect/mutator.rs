@@ -1,4 +1,5 @@
 use super::PageProtect;
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
 use crate::plan::mutator_context::{
@@ -18,7 +19,9 @@ use enum_map::EnumMap;
 fn pp_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {}
 
 /// Release mutator. Do nothing.
-fn pp_mutator_release<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {}
+fn pp_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
+}
 
 const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
     n_large_object: 1,
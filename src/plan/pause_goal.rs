@@ -0,0 +1,123 @@
+//! The pause time goal.
+//!
+//! If the option `max_pause_ms` is set, MMTk tries to keep GC pauses under it on a best-effort basis.
+//! The pause times recorded in [`Stats`](crate::util::statistics::stats::Stats) are fed back into the policies:
+//! * Generational plans shrink the nursery after a nursery GC that exceeds the goal, and only grow it if the
+//!   larger nursery is expected to stay within the goal.
+//! * Immix spaces skip optional defragmentation after a full heap GC that exceeds the goal.
+//! * Generational plans may collect the full heap early while full heap GCs stay within the goal, so a nursery
+//!   GC always has a full nursery. Otherwise, full heap GCs are postponed as long as possible.
+
+use crate::util::options::Options;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub struct PauseGoal {
+    /// The longest pause we aim for. None if there is no goal.
+    goal: Option<Duration>,
+    /// Did the last full heap GC exceed the goal?
+    full_heap_exceeded: AtomicBool,
+}
+
+impl PauseGoal {
+    pub fn new() -> Self {
+        PauseGoal {
+            goal: None,
+            full_heap_exceeded: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the goal from the options.
+    pub fn init(&mut self, options: &Options) {
+        self.goal = match *options.max_pause_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms as u64)),
+        };
+    }
+
+    /// The longest pause we aim for, if there is a goal.
+    pub fn goal(&self) -> Option<Duration> {
+        self.goal
+    }
+
+    /// Does the pause exceed the goal? This is false if there is no goal.
+    pub fn is_exceeded_by(&self, pause: Duration) -> bool {
+        self.goal.map_or(false, |goal| pause > goal)
+    }
+
+    /// Did the last full heap GC exceed the goal? This is false if there is no goal.
+    pub fn is_exceeded_by_full_heap_gc(&self) -> bool {
+        self.full_heap_exceeded.load(Ordering::Relaxed)
+    }
+
+    /// A GC ends with the given pause time.
+    pub fn on_gc_end(&self, pause: Duration, full_heap: bool) {
+        if full_heap {
+            self.full_heap_exceeded
+                .store(self.is_exceeded_by(pause), Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for PauseGoal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_util::serial_test;
+
+    fn new_goal(max_pause_ms: usize) -> PauseGoal {
+        let mut options = Options::default();
+        options.max_pause_ms.value = max_pause_ms;
+        let mut pause_goal = PauseGoal::new();
+        pause_goal.init(&options);
+        pause_goal
+    }
+
+    #[test]
+    fn no_goal_by_default() {
+        serial_test(|| {
+            let pause_goal = new_goal(0);
+            assert_eq!(pause_goal.goal(), None);
+            assert!(!pause_goal.is_exceeded_by(Duration::from_secs(3600)));
+            pause_goal.on_gc_end(Duration::from_secs(3600), true);
+            assert!(!pause_goal.is_exceeded_by_full_heap_gc());
+        })
+    }
+
+    #[test]
+    fn exceeded_by_longer_pause() {
+        serial_test(|| {
+            let pause_goal = new_goal(10);
+            assert_eq!(pause_goal.goal(), Some(Duration::from_millis(10)));
+            assert!(!pause_goal.is_exceeded_by(Duration::from_millis(5)));
+            assert!(!pause_goal.is_exceeded_by(Duration::from_millis(10)));
+            assert!(pause_goal.is_exceeded_by(Duration::from_millis(11)));
+        })
+    }
+
+    #[test]
+    fn remember_last_full_heap_gc() {
+        serial_test(|| {
+            let pause_goal = new_goal(10);
+            assert!(!pause_goal.is_exceeded_by_full_heap_gc());
+
+            pause_goal.on_gc_end(Duration::from_millis(20), true);
+            assert!(pause_goal.is_exceeded_by_full_heap_gc());
+
+            // Nursery GCs do not change it.
+            pause_goal.on_gc_end(Duration::from_millis(1), false);
+            assert!(pause_goal.is_exceeded_by_full_heap_gc());
+
+            pause_goal.on_gc_end(Duration::from_millis(5), true);
+            assert!(!pause_goal.is_exceeded_by_full_heap_gc());
+
+            pause_goal.on_gc_end(Duration::from_millis(20), false);
+            assert!(!pause_goal.is_exceeded_by_full_heap_gc());
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use super::global::{Pause, RefCount};
+use super::rc;
+use crate::plan::barriers::FieldValuesVisitor;
+use crate::policy::immix::chunk::Chunk;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::{GCWork, GCWorker, WorkBucketStage};
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use crate::MMTK;
+use std::ops::{Deref, DerefMut};
+
+/// The max number of objects in a [`ProcessDecs`] work packet.
+const DECS_CAPACITY: usize = 4096;
+
+/// Apply the increments in a reference counting pause. Each traced edge increments the count of its referent.
+/// The first increment of a new object starts counting the new object: its fields are scanned,
+/// and the edges are traced by this type as well.
+pub(super) struct RCProcessEdges<VM: VMBinding> {
+    // Use a static ref to the specific plan to avoid overhead from dynamic dispatch or
+    // downcast for each traced object.
+    plan: &'static RefCount<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for RCProcessEdges<VM> {
+    type VM = VM;
+
+    // Objects are never moved.
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<Address>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<RefCount<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[inline(always)]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if self.plan.immix_space.in_space(object) {
+            if rc::inc(object) == 0 {
+                // Writes to new objects are not logged, so we need to count their fields now.
+                self.plan.start_counting(object);
+                self.process_node(object);
+            }
+        } else if self.plan.common.get_los().in_space(object) {
+            // New large objects are in the nursery of the large object space. They are traced
+            // (and scanned) at the first time they are reached. Other large objects are ignored.
+            self.plan.common.get_los().trace_object(self, object);
+        }
+        object
+    }
+
+    #[inline]
+    fn process_edges(&mut self) {
+        if self.roots {
+            self.plan.record_roots(&self.edges);
+        }
+        for i in 0..self.edges.len() {
+            self.process_edge(self.edges[i])
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for RCProcessEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for RCProcessEdges<VM> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Object tracing for a backup trace. This is a full heap trace with the immix space, and it recomputes the
+/// reference counts of all the reachable objects, so garbage cycles and objects with stuck counts are reclaimed.
+pub(super) struct BackupProcessEdges<VM: VMBinding> {
+    plan: &'static RefCount<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for BackupProcessEdges<VM> {
+    type VM = VM;
+
+    // Objects are never moved.
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<Address>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<RefCount<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[inline(always)]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if self.plan.immix_space.in_space(object) {
+            // The counts were cleared before the trace.
+            if rc::inc(object) == 0 {
+                self.plan.start_counting(object);
+            }
+            self.plan.immix_space.fast_trace_object(self, object)
+        } else {
+            self.plan.common.trace_object::<Self>(self, object)
+        }
+    }
+
+    #[inline]
+    fn process_edges(&mut self) {
+        if self.roots {
+            self.plan.record_roots(&self.edges);
+        }
+        for i in 0..self.edges.len() {
+            self.process_edge(self.edges[i])
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for BackupProcessEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for BackupProcessEdges<VM> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Decrement the reference counts of the given objects. An object whose count drops to zero is dead:
+/// the counts of its referents are decremented in turn, and the lines it occupies are released.
+pub struct ProcessDecs<VM: VMBinding> {
+    decs: Vec<ObjectReference>,
+    phantom: std::marker::PhantomData<VM>,
+}
+
+impl<VM: VMBinding> ProcessDecs<VM> {
+    pub fn new(decs: Vec<ObjectReference>) -> Self {
+        Self {
+            decs,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessDecs<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        trace!("ProcessDecs");
+        let plan = mmtk.plan.downcast_ref::<RefCount<VM>>().unwrap();
+        if plan.current_pause() == Pause::Backup {
+            // The backup trace recomputes all the counts.
+            return;
+        }
+        let mut referents = FieldValuesVisitor { fields: vec![] };
+        for object in &self.decs {
+            if object.is_null() || !plan.immix_space.in_space(*object) {
+                continue;
+            }
+            if rc::dec(*object) == 1 {
+                VM::VMScanning::scan_object(worker.tls, *object, &mut referents);
+                plan.release_object(*object);
+                if referents.fields.len() >= DECS_CAPACITY {
+                    let decs = std::mem::take(&mut referents.fields);
+                    worker.add_work(WorkBucketStage::RCDecrements, ProcessDecs::<VM>::new(decs));
+                }
+            }
+        }
+        if !referents.fields.is_empty() {
+            worker.add_work(
+                WorkBucketStage::RCDecrements,
+                ProcessDecs::<VM>::new(referents.fields),
+            );
+        }
+    }
+}
+
+/// Add the referents recorded by the coalescing barrier to the decrements of the next pause.
+pub(super) fn flush_decbuf<VM: VMBinding>(mmtk: &'static MMTK<VM>, decs: Vec<ObjectReference>) {
+    mmtk.scheduler.work_buckets[WorkBucketStage::RCDecrements].add(ProcessDecs::<VM>::new(decs));
+}
+
+/// Clear the reference counts and the line counts of a chunk before a backup trace.
+pub(super) struct ClearCounts {
+    pub chunk: Chunk,
+}
+
+impl<VM: VMBinding> GCWork<VM> for ClearCounts {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        side_metadata::bzero_metadata(&rc::RC_TABLE, self.chunk.start(), Chunk::BYTES);
+        side_metadata::bzero_metadata(&rc::LINE_COUNT_TABLE, self.chunk.start(), Chunk::BYTES);
+    }
+}
+
+pub(super) struct RCGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for RCGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = RefCount<VM>;
+    type ProcessEdgesWorkType = RCProcessEdges<VM>;
+}
+
+pub(super) struct BackupGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for BackupGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = RefCount<VM>;
+    type ProcessEdgesWorkType = BackupProcessEdges<VM>;
+}
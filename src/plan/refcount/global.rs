@@ -0,0 +1,302 @@
+use super::gc_work::{
+    BackupGCWorkContext, ClearCounts, ProcessDecs, RCGCWorkContext, RCProcessEdges,
+};
+use super::mutator::ALLOCATOR_MAPPING;
+use super::rc;
+use crate::plan::barriers::{BarrierSelector, FIELD_LOG_BIT_SPEC};
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::GcStatus;
+use crate::plan::AllocationSemantics;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::immix::ImmixSpace;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::{Prepare, Release, StopMutators};
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::heap::layout::heap_layout::Mmapper;
+use crate::util::heap::layout::heap_layout::VMMap;
+use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::HeapMeta;
+use crate::util::metadata::side_metadata::SideMetadataContext;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::options::UnsafeOptionsWrapper;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex};
+
+use atomic::{Atomic, Ordering};
+use enum_map::EnumMap;
+
+/// Do a backup trace after this many reference counting pauses.
+const BACKUP_TRACE_INTERVAL: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Pause {
+    /// Apply the increments and decrements since the last pause, and reclaim the objects whose counts drop to zero.
+    RefCount,
+    /// A full heap trace that recomputes the counts. This reclaims cyclic garbage and the objects with stuck counts.
+    Backup,
+}
+
+/// A reference counting plan with deferred and coalescing reference counting.
+///
+/// Objects are allocated into an immix space. The counts do not include the references from the roots,
+/// and the writes to the heap are recorded by a coalescing object barrier with the log bit: when an unlogged
+/// object is modified for the first time, the barrier logs it and records its referents. In each pause,
+/// the counts of the current roots and the current referents of the logged objects are incremented, and the
+/// counts of the recorded referents and the roots of the last pause are decremented. An object whose count
+/// drops to zero is dead, and a line is reclaimed when all of the objects on it are dead.
+///
+/// New objects are not counted until they are reached from a counted object or a root in a pause,
+/// so short-lived objects are reclaimed without being counted. Counts are small and sticky,
+/// and a periodic backup trace with the immix space reclaims garbage cycles and the objects with stuck counts.
+///
+/// Writes that only give the slot (`WriteTarget::Slot`) log the slot instead of the object. The current referent
+/// of a logged slot is incremented in the next pause, but its old referent is not decremented, so the old
+/// referent is only reclaimed by a backup trace.
+///
+/// Only the objects in the immix space are counted. Large objects are traced when they are new, so their
+/// referents are counted, but a dead large object is only reclaimed by a backup trace.
+///
+/// Note that reference types and finalizers are only processed in backup traces. The binding should
+/// treat the references from reference objects as strong references (e.g. with the option `no_reference_types`).
+pub struct RefCount<VM: VMBinding> {
+    pub immix_space: ImmixSpace<VM>,
+    pub common: CommonPlan<VM>,
+    /// The kind of the current pause (or the last pause if we are not in a GC).
+    pause: Atomic<Pause>,
+    /// Force the next GC to do a backup trace.
+    next_gc_backup: AtomicBool,
+    /// The number of reference counting pauses since the last backup trace.
+    pauses_since_backup: AtomicUsize,
+    /// The roots in the immix space found in the current (or the last) pause. Their counts are decremented in the next pause.
+    roots: Mutex<Vec<ObjectReference>>,
+}
+
+pub const REFCOUNT_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: false,
+    gc_header_bits: 2,
+    gc_header_words: 0,
+    num_specialized_scans: 1,
+    /// Max immix object size is half of a block.
+    max_non_los_default_alloc_bytes: crate::policy::immix::MAX_IMMIX_OBJECT_SIZE,
+    needs_log_bit: true,
+    barrier: BarrierSelector::CoalescingObjectBarrier,
+    ..PlanConstraints::default()
+};
+
+impl<VM: VMBinding> Plan for RefCount<VM> {
+    type VM = VM;
+
+    fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
+        self.base().collection_required(self, space_full, space)
+    }
+
+    fn last_collection_full_heap(&self) -> bool {
+        self.current_pause() == Pause::Backup
+    }
+
+    fn force_full_heap_collection(&self) {
+        self.next_gc_backup.store(true, Ordering::SeqCst);
+    }
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &REFCOUNT_CONSTRAINTS
+    }
+
+    fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
+        self.common.gc_init(heap_size, vm_map);
+        self.immix_space.init(vm_map);
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+        let pause = self.select_pause();
+        self.pause.store(pause, Ordering::SeqCst);
+        // The roots of the last pause.
+        let roots = std::mem::take(&mut *self.roots.lock().unwrap());
+        match pause {
+            Pause::RefCount => {
+                debug!("Reference counting pause");
+                // The roots of this pause are counted before any decrement.
+                for chunk in roots.chunks(RCProcessEdges::<VM>::CAPACITY) {
+                    scheduler.work_buckets[WorkBucketStage::RCDecrements]
+                        .add(ProcessDecs::<VM>::new(chunk.to_vec()));
+                }
+                scheduler.work_buckets[WorkBucketStage::Unconstrained]
+                    .add(StopMutators::<RCProcessEdges<VM>>::new());
+                scheduler.work_buckets[WorkBucketStage::Prepare]
+                    .add(Prepare::<RCGCWorkContext<VM>>::new(self));
+                scheduler.work_buckets[WorkBucketStage::Release]
+                    .add(Release::<RCGCWorkContext<VM>>::new(self));
+            }
+            Pause::Backup => {
+                debug!("Backup trace");
+                scheduler.work_buckets[WorkBucketStage::Prepare].bulk_add(
+                    self.immix_space
+                        .chunk_map
+                        .generate_tasks(|chunk| Box::new(ClearCounts { chunk })),
+                );
+                scheduler.schedule_common_work::<BackupGCWorkContext<VM>>(self);
+            }
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &*ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let backup = self.current_pause() == Pause::Backup;
+        // In a reference counting pause, only the nursery of the large object space is traced.
+        self.common.prepare(tls, backup);
+        if backup {
+            self.immix_space.prepare(true);
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        let backup = self.current_pause() == Pause::Backup;
+        self.common.release(tls, backup);
+        // The lines of the dead objects have been unmarked. Sweep the immix space with the current line mark state.
+        self.immix_space.release(backup);
+        if backup {
+            self.pauses_since_backup.store(0, Ordering::SeqCst);
+        } else {
+            self.pauses_since_backup.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.immix_space.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+
+    fn is_current_gc_nursery(&self) -> bool {
+        self.current_pause() == Pause::RefCount
+    }
+}
+
+impl<VM: VMBinding> RefCount<VM> {
+    pub fn new(
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+    ) -> Self {
+        if !*options.no_reference_types || !*options.no_finalizer {
+            warn!("RefCount only processes reference types and finalizers in backup traces");
+        }
+        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut specs = crate::util::metadata::extract_side_metadata(&[
+            *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+        ]);
+        // The coalescing barrier logs the slots of `WriteTarget::Slot` writes.
+        specs.push(FIELD_LOG_BIT_SPEC);
+        specs.push(rc::RC_TABLE);
+        specs.push(rc::LINE_COUNT_TABLE);
+        let global_metadata_specs = SideMetadataContext::new_global_specs(&specs);
+        let plan = RefCount {
+            immix_space: ImmixSpace::new(
+                "immix",
+                vm_map,
+                mmapper,
+                &mut heap,
+                scheduler.clone(),
+                global_metadata_specs.clone(),
+                &REFCOUNT_CONSTRAINTS,
+            ),
+            common: CommonPlan::new(
+                vm_map,
+                mmapper,
+                options,
+                scheduler,
+                heap,
+                &REFCOUNT_CONSTRAINTS,
+                global_metadata_specs,
+            ),
+            pause: Atomic::new(Pause::Backup),
+            next_gc_backup: AtomicBool::new(false),
+            pauses_since_backup: AtomicUsize::new(0),
+            roots: Mutex::new(vec![]),
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            plan.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            plan.immix_space
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        plan
+    }
+
+    pub(super) fn current_pause(&self) -> Pause {
+        self.pause.load(Ordering::SeqCst)
+    }
+
+    /// Decide which pause to do for the current GC.
+    fn select_pause(&self) -> Pause {
+        // Allow the same 'true' block for if-else.
+        #[allow(clippy::if_same_then_else)]
+        let backup = if self.base().is_user_triggered_collection()
+            && *self.base().options.full_heap_system_gc
+        {
+            true
+        } else if self.next_gc_backup.swap(false, Ordering::SeqCst)
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1
+        {
+            // A reference counting pause did not free enough memory.
+            true
+        } else {
+            self.pauses_since_backup.load(Ordering::SeqCst) >= BACKUP_TRACE_INTERVAL
+        };
+        if backup {
+            Pause::Backup
+        } else {
+            Pause::RefCount
+        }
+    }
+
+    /// Start counting a new object (or an object found in a backup trace): it has got its first count.
+    /// Its lines are in use from now on, and the writes to it need to be logged.
+    #[inline]
+    pub(super) fn start_counting(&self, object: ObjectReference) {
+        self.immix_space.mark_lines(object);
+        rc::inc_lines::<VM>(object);
+        VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+    }
+
+    /// Release a dead object in the immix space, whose count has dropped to zero.
+    #[inline]
+    pub(super) fn release_object(&self, object: ObjectReference) {
+        rc::dec_lines::<VM>(object);
+        // The memory may be reused for new objects, which should not be logged.
+        VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_logged::<VM>(object, Ordering::SeqCst);
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::unset_alloc_bit(object);
+    }
+
+    /// Remember the root objects in the immix space. Roots are counted in each pause, and the counts are
+    /// decremented in the next pause.
+    pub(super) fn record_roots(&self, edges: &[Address]) {
+        let roots = edges
+            .iter()
+            .map(|edge| unsafe { edge.load::<ObjectReference>() })
+            .filter(|object| !object.is_null() && self.immix_space.in_space(*object));
+        self.roots.lock().unwrap().extend(roots);
+    }
+}
@@ -0,0 +1,10 @@
+//! Plan: reference counting
+
+pub(in crate::plan) mod gc_work;
+pub(in crate::plan) mod global;
+pub(in crate::plan) mod mutator;
+pub(in crate::plan) mod rc;
+
+pub use self::global::RefCount;
+
+pub use self::global::REFCOUNT_CONSTRAINTS;
@@ -0,0 +1,84 @@
+use super::gc_work::{flush_decbuf, RCProcessEdges};
+use super::RefCount;
+use crate::plan::barriers::ObjectRememberingBarrier;
+use crate::plan::mutator_context::common_mutator_release;
+use crate::plan::mutator_context::create_allocator_mapping;
+use crate::plan::mutator_context::create_space_mapping;
+use crate::plan::mutator_context::Mutator;
+use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::ReservedAllocators;
+use crate::plan::AllocationSemantics;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::ImmixAllocator;
+use crate::util::opaque_pointer::{VMMutatorThread, VMWorkerThread};
+use crate::vm::{ObjectModel, VMBinding};
+use crate::MMTK;
+use enum_map::EnumMap;
+
+pub fn refcount_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    let immix_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<ImmixAllocator<VM>>()
+    .unwrap();
+    immix_allocator.reset();
+}
+
+pub fn refcount_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
+    let immix_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<ImmixAllocator<VM>>()
+    .unwrap();
+    immix_allocator.reset();
+}
+
+const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
+    n_immix: 1,
+    ..ReservedAllocators::DEFAULT
+};
+
+lazy_static! {
+    pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationSemantics, AllocatorSelector> = {
+        let mut map = create_allocator_mapping(RESERVED_ALLOCATORS, true);
+        map[AllocationSemantics::Default] = AllocatorSelector::Immix(0);
+        map
+    };
+}
+
+pub fn create_refcount_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let plan = mmtk.plan.downcast_ref::<RefCount<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &*ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec = create_space_mapping(RESERVED_ALLOCATORS, true, &*mmtk.plan);
+            vec.push((AllocatorSelector::Immix(0), &plan.immix_space));
+            vec
+        }),
+        prepare_func: &refcount_mutator_prepare,
+        release_func: &refcount_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: Box::new(
+            ObjectRememberingBarrier::<RCProcessEdges<VM>>::new_coalescing(
+                mmtk,
+                mutator_tls,
+                *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+                flush_decbuf::<VM>,
+            ),
+        ),
+        mutator_tls,
+        config,
+        plan,
+    }
+}
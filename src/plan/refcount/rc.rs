@@ -0,0 +1,98 @@
+//! Reference counts and line counts for the reference counting plan.
+//!
+//! Only the objects in the immix space are counted. Both counts are kept in global side metadata.
+
+use crate::policy::immix::line::Line;
+use crate::util::linear_scan::{Region, RegionIterator};
+use crate::util::metadata::side_metadata::{self, SideMetadataSpec};
+use crate::util::ObjectReference;
+use crate::vm::*;
+use std::sync::atomic::Ordering;
+
+/// The reference count of each object.
+pub const RC_TABLE: SideMetadataSpec = crate::util::metadata::side_metadata::spec_defs::RC_COUNT;
+
+/// The number of objects with a non-zero count that span each line. A line can be reused once its count drops to zero.
+pub const LINE_COUNT_TABLE: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::RC_LINE_COUNT;
+
+/// Counts stick at this value. Objects with a stuck count can only be reclaimed by a backup trace.
+pub const MAX_REF_COUNT: usize = (1 << (1 << RC_TABLE.log_num_of_bits)) - 1;
+
+/// Increment the reference count of an object. Return the count before the increment.
+#[inline(always)]
+pub fn inc(object: ObjectReference) -> usize {
+    loop {
+        let old = side_metadata::load_atomic(&RC_TABLE, object.to_address(), Ordering::SeqCst);
+        if old == MAX_REF_COUNT {
+            return old;
+        }
+        if side_metadata::compare_exchange_atomic(
+            &RC_TABLE,
+            object.to_address(),
+            old,
+            old + 1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            return old;
+        }
+    }
+}
+
+/// Decrement the reference count of an object. Return the count before the decrement.
+/// Stuck counts and zero counts are not changed.
+#[inline(always)]
+pub fn dec(object: ObjectReference) -> usize {
+    loop {
+        let old = side_metadata::load_atomic(&RC_TABLE, object.to_address(), Ordering::SeqCst);
+        if old == 0 || old == MAX_REF_COUNT {
+            return old;
+        }
+        if side_metadata::compare_exchange_atomic(
+            &RC_TABLE,
+            object.to_address(),
+            old,
+            old - 1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            return old;
+        }
+    }
+}
+
+/// The lines that an object spans.
+#[inline(always)]
+fn lines_of<VM: VMBinding>(object: ObjectReference) -> RegionIterator<Line> {
+    let start = VM::VMObjectModel::object_start_ref(object);
+    let end = start + VM::VMObjectModel::get_current_size(object);
+    let start_line = Line::from(Line::align(start));
+    let mut end_line = Line::from(Line::align(end));
+    if !Line::is_aligned(end) {
+        end_line = end_line.next();
+    }
+    RegionIterator::<Line>::new(start_line, end_line)
+}
+
+/// Increment the line counts for all the lines that an object spans.
+#[inline]
+pub fn inc_lines<VM: VMBinding>(object: ObjectReference) {
+    for line in lines_of::<VM>(object) {
+        side_metadata::fetch_add_atomic(&LINE_COUNT_TABLE, line.start(), 1, Ordering::SeqCst);
+    }
+}
+
+/// Decrement the line counts for all the lines that an object spans. The lines whose counts drop
+/// to zero are unmarked, so the immix space can reclaim them when it sweeps.
+#[inline]
+pub fn dec_lines<VM: VMBinding>(object: ObjectReference) {
+    for line in lines_of::<VM>(object) {
+        let old =
+            side_metadata::fetch_sub_atomic(&LINE_COUNT_TABLE, line.start(), 1, Ordering::SeqCst);
+        debug_assert_ne!(old, 0);
+        if old == 1 {
+            side_metadata::store_atomic(&Line::MARK_TABLE, line.start(), 0, Ordering::SeqCst);
+        }
+    }
+}
@@ -130,6 +130,7 @@ impl<VM: VMBinding> SemiSpace<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
         let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
@@ -160,6 +161,7 @@ impl<VM: VMBinding> SemiSpace<VM> {
                 vm_map,
                 mmapper,
                 options,
+                scheduler,
                 heap,
                 &SS_CONSTRAINTS,
                 global_metadata_specs,
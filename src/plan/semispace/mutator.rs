@@ -1,5 +1,6 @@
 use super::SemiSpace;
 use crate::plan::barriers::NoBarrier;
+use crate::plan::mutator_context::common_mutator_release;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
 use crate::plan::mutator_context::{
@@ -17,7 +18,8 @@ pub fn ss_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWor
     // Do nothing
 }
 
-pub fn ss_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+pub fn ss_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    common_mutator_release(mutator, tls);
     // rebind the allocation bump pointer to the appropriate semispace
     let bump_allocator = unsafe {
         mutator
@@ -0,0 +1,178 @@
+use super::global::{StickyImmix, STICKY_IMMIX_COPY};
+use crate::plan::immix::gc_work::{TraceKind, TRACE_KIND_DEFRAG, TRACE_KIND_FAST};
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::{GCWork, GCWorker};
+use crate::util::copy::CopySemantics;
+use crate::util::{Address, ObjectReference};
+use crate::vm::VMBinding;
+use crate::MMTK;
+use std::ops::{Deref, DerefMut};
+
+/// ProcessEdges for a nursery GC of sticky immix. Only new objects (the objects allocated since the last GC)
+/// are traced: the objects in the immix space that are marked, and the large objects that are not in the
+/// nursery of the large object space, have survived an earlier GC and are not traced again.
+pub struct StickyImmixNurseryProcessEdges<VM: VMBinding> {
+    plan: &'static StickyImmix<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for StickyImmixNurseryProcessEdges<VM> {
+    type VM = VM;
+
+    const OVERWRITE_REFERENCE: bool = STICKY_IMMIX_COPY;
+
+    fn new(edges: Vec<Address>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<StickyImmix<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[cold]
+    fn flush(&mut self) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let nodes = self.pop_nodes();
+        self.plan.promote(&nodes);
+        let scan_objects_work = crate::policy::immix::ScanObjectsAndMarkLines::<Self>::new(
+            nodes,
+            false,
+            &self.plan.immix,
+        );
+        self.new_scan_work(scan_objects_work);
+    }
+
+    #[inline(always)]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if self.plan.immix.in_space(object) {
+            return self.plan.immix.trace_object_nursery(
+                self,
+                object,
+                CopySemantics::PromoteToMature,
+                self.worker(),
+                STICKY_IMMIX_COPY,
+            );
+        }
+        // We may alloc large object into LOS as nursery objects. Trace them here.
+        if self.plan.common.get_los().in_space(object) {
+            return self.plan.common.get_los().trace_object(self, object);
+        }
+        object
+    }
+}
+
+impl<VM: VMBinding> Deref for StickyImmixNurseryProcessEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for StickyImmixNurseryProcessEdges<VM> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// ProcessEdges for a full heap GC of sticky immix. The const type parameter
+/// defines whether there is defragmentation in the GC.
+pub(super) struct StickyImmixMatureProcessEdges<VM: VMBinding, const KIND: TraceKind> {
+    plan: &'static StickyImmix<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> ProcessEdgesWork
+    for StickyImmixMatureProcessEdges<VM, KIND>
+{
+    type VM = VM;
+
+    const OVERWRITE_REFERENCE: bool = KIND == TRACE_KIND_DEFRAG;
+
+    fn new(edges: Vec<Address>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<StickyImmix<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[cold]
+    fn flush(&mut self) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let nodes = self.pop_nodes();
+        self.plan.promote(&nodes);
+        let scan_objects_work = crate::policy::immix::ScanObjectsAndMarkLines::<Self>::new(
+            nodes,
+            false,
+            &self.plan.immix,
+        );
+        self.new_scan_work(scan_objects_work);
+    }
+
+    #[inline(always)]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if self.plan.immix.in_space(object) {
+            if KIND == TRACE_KIND_FAST {
+                self.plan.immix.fast_trace_object(self, object)
+            } else {
+                self.plan
+                    .immix
+                    .trace_object(self, object, CopySemantics::Mature, self.worker())
+            }
+        } else {
+            self.plan.common.trace_object::<Self>(self, object)
+        }
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> Deref for StickyImmixMatureProcessEdges<VM, KIND> {
+    type Target = ProcessEdgesBase<VM>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> DerefMut for StickyImmixMatureProcessEdges<VM, KIND> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Set the trigger for the next nursery GC. This is done at the end of a GC, after the spaces are swept.
+pub(super) struct UpdateNurseryTrigger;
+
+impl<VM: VMBinding> GCWork<VM> for UpdateNurseryTrigger {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let plan = mmtk.plan.downcast_ref::<StickyImmix<VM>>().unwrap();
+        plan.update_nursery_trigger();
+    }
+}
+
+pub(super) struct StickyImmixNurseryGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for StickyImmixNurseryGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = StickyImmix<VM>;
+    type ProcessEdgesWorkType = StickyImmixNurseryProcessEdges<VM>;
+}
+
+pub(super) struct StickyImmixMatureGCWorkContext<VM: VMBinding, const KIND: TraceKind>(
+    std::marker::PhantomData<VM>,
+);
+impl<VM: VMBinding, const KIND: TraceKind> crate::scheduler::GCWorkContext
+    for StickyImmixMatureGCWorkContext<VM, KIND>
+{
+    type VM = VM;
+    type PlanType = StickyImmix<VM>;
+    type ProcessEdgesWorkType = StickyImmixMatureProcessEdges<VM, KIND>;
+}
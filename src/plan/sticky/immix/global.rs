@@ -0,0 +1,279 @@
+use super::gc_work::{
+    StickyImmixMatureGCWorkContext, StickyImmixNurseryGCWorkContext, UpdateNurseryTrigger,
+};
+use super::mutator::ALLOCATOR_MAPPING;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::GcStatus;
+use crate::plan::immix::gc_work::{TRACE_KIND_DEFRAG, TRACE_KIND_FAST};
+use crate::plan::AllocationSemantics;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::immix::defrag::DefragDecision;
+use crate::policy::immix::ImmixSpace;
+use crate::policy::space::Space;
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::conversions;
+use crate::util::copy::*;
+use crate::util::heap::layout::heap_layout::Mmapper;
+use crate::util::heap::layout::heap_layout::VMMap;
+use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::HeapMeta;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::options::UnsafeOptionsWrapper;
+use crate::util::{ObjectReference, VMWorkerThread};
+use crate::vm::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::Arc;
+
+use atomic::Ordering;
+use enum_map::EnumMap;
+
+/// Whether sticky immix moves objects. If this is true, new objects are opportunistically copied in
+/// nursery GCs, and the immix space is defragmented in full heap GCs as the immix plan does.
+/// Otherwise, objects are never moved. This is enabled with the feature `sticky_immix_copy`.
+pub const STICKY_IMMIX_COPY: bool = cfg!(feature = "sticky_immix_copy");
+
+/// Sticky immix. This is a generational plan with a single immix space, using sticky mark bits
+/// (Demers et al., POPL'90). Mark bits and line marks are not cleared in a nursery GC, so the objects
+/// that survived an earlier GC are considered mature, and a nursery GC only traces the objects that are
+/// new since the last GC, plus the fields of the mature objects that were modified since then. The
+/// modified objects are remembered by the object barrier with the log bit, the same as the generational plans.
+/// The new objects that survive are promoted in place (or opportunistically copied if [`STICKY_IMMIX_COPY`] is true),
+/// so objects do not need to move to get promoted.
+pub struct StickyImmix<VM: VMBinding> {
+    pub immix: ImmixSpace<VM>,
+    pub common: CommonPlan<VM>,
+    /// Is the current GC (or the last GC if we are not in a GC) a full heap GC?
+    gc_full_heap: AtomicBool,
+    /// Force the next GC to be a full heap GC.
+    next_gc_full_heap: AtomicBool,
+    /// Whether the last GC was a defrag GC for the immix space.
+    last_gc_was_defrag: AtomicBool,
+    /// A nursery GC is triggered when the reserved pages reach this number.
+    nursery_trigger_pages: AtomicUsize,
+}
+
+pub const STICKY_IMMIX_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: STICKY_IMMIX_COPY,
+    /// Max immix object size is half of a block.
+    max_non_los_default_alloc_bytes: crate::policy::immix::MAX_IMMIX_OBJECT_SIZE,
+    ..crate::plan::generational::GEN_CONSTRAINTS
+};
+
+impl<VM: VMBinding> Plan for StickyImmix<VM> {
+    type VM = VM;
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &STICKY_IMMIX_CONSTRAINTS
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::PromoteToMature => CopySelector::Immix(0),
+                CopySemantics::Mature => CopySelector::Immix(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::Immix(0), &self.immix)],
+            constraints: &STICKY_IMMIX_CONSTRAINTS,
+        }
+    }
+
+    fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
+        let nursery_full =
+            self.get_reserved_pages() >= self.nursery_trigger_pages.load(Ordering::Relaxed);
+        if nursery_full {
+            return true;
+        }
+
+        if space_full {
+            // A space cannot get more pages. A nursery GC may not be able to free enough memory.
+            self.next_gc_full_heap.store(true, Ordering::SeqCst);
+        }
+
+        self.base().collection_required(self, space_full, space)
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        self.last_collection_full_heap()
+            && ImmixSpace::<VM>::is_last_gc_exhaustive(
+                self.last_gc_was_defrag.load(Ordering::Relaxed),
+            )
+    }
+
+    fn last_collection_full_heap(&self) -> bool {
+        self.gc_full_heap.load(Ordering::Relaxed)
+    }
+
+    fn force_full_heap_collection(&self) {
+        self.next_gc_full_heap.store(true, Ordering::SeqCst);
+    }
+
+    fn gc_init(&mut self, heap_size: usize, vm_map: &'static VMMap) {
+        self.common.gc_init(heap_size, vm_map);
+        self.immix.init(vm_map);
+    }
+
+    // StickyImmixMatureGCWorkContext<VM, TRACE_KIND_DEFRAG> and StickyImmixMatureGCWorkContext<VM, TRACE_KIND_FAST>
+    // are different types. However, it seems clippy does not recognize the constant type parameter and thinks we have identical blocks
+    // in different if branches.
+    #[allow(clippy::if_same_then_else)]
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        let is_full_heap = self.request_full_heap_collection();
+
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+        let defrag = STICKY_IMMIX_COPY
+            && is_full_heap
+            && self
+                .immix
+                .decide_whether_to_defrag(&DefragDecision::full_heap_gc(self));
+
+        if !is_full_heap {
+            debug!("Nursery GC");
+            scheduler.schedule_common_work::<StickyImmixNurseryGCWorkContext<VM>>(self);
+        } else if defrag {
+            debug!("Full heap GC Defrag");
+            scheduler
+                .schedule_common_work::<StickyImmixMatureGCWorkContext<VM, TRACE_KIND_DEFRAG>>(
+                    self,
+                );
+        } else {
+            debug!("Full heap GC Fast");
+            scheduler
+                .schedule_common_work::<StickyImmixMatureGCWorkContext<VM, TRACE_KIND_FAST>>(self);
+        }
+        scheduler.work_buckets[WorkBucketStage::Final].add(UpdateNurseryTrigger);
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &*ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let full_heap = !self.is_current_gc_nursery();
+        // In a nursery GC, only the nursery of the large object space is traced, and the
+        // immix space keeps the marks of the mature objects.
+        self.common.prepare(tls, full_heap);
+        self.immix.prepare(full_heap);
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        let full_heap = !self.is_current_gc_nursery();
+        self.common.release(tls, full_heap);
+        let did_defrag = self.immix.release(full_heap);
+        self.last_gc_was_defrag.store(did_defrag, Ordering::Relaxed);
+    }
+
+    fn get_collection_reserved_pages(&self) -> usize {
+        if STICKY_IMMIX_COPY {
+            self.immix.defrag_headroom_pages()
+        } else {
+            0
+        }
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.immix.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+
+    fn is_current_gc_nursery(&self) -> bool {
+        !self.gc_full_heap.load(Ordering::SeqCst)
+    }
+}
+
+impl<VM: VMBinding> StickyImmix<VM> {
+    pub fn new(
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        options: Arc<UnsafeOptionsWrapper>,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+    ) -> Self {
+        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        // Use the log bit from generational plans for the object barrier.
+        let global_metadata_specs =
+            crate::plan::generational::new_generational_global_metadata_specs::<VM>(
+                STICKY_IMMIX_CONSTRAINTS.barrier,
+            );
+        let nursery_pages = conversions::bytes_to_pages_up(*options.max_nursery);
+        let plan = StickyImmix {
+            immix: ImmixSpace::new(
+                "immix",
+                vm_map,
+                mmapper,
+                &mut heap,
+                scheduler.clone(),
+                global_metadata_specs.clone(),
+                &STICKY_IMMIX_CONSTRAINTS,
+            ),
+            common: CommonPlan::new(
+                vm_map,
+                mmapper,
+                options,
+                scheduler,
+                heap,
+                &STICKY_IMMIX_CONSTRAINTS,
+                global_metadata_specs,
+            ),
+            gc_full_heap: AtomicBool::new(false),
+            next_gc_full_heap: AtomicBool::new(false),
+            last_gc_was_defrag: AtomicBool::new(false),
+            nursery_trigger_pages: AtomicUsize::new(nursery_pages),
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            plan.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            plan.immix
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        plan
+    }
+
+    /// Decide whether the current GC should be a full heap GC.
+    fn request_full_heap_collection(&self) -> bool {
+        let is_full_heap = self.next_gc_full_heap.swap(false, Ordering::SeqCst)
+            || (self.base().is_user_triggered_collection()
+                && *self.base().options.full_heap_system_gc)
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1;
+        self.gc_full_heap.store(is_full_heap, Ordering::SeqCst);
+        is_full_heap
+    }
+
+    /// The objects that are traced in a GC are mature from now on, and the writes to them need to be logged.
+    #[inline]
+    pub(super) fn promote(&self, objects: &[ObjectReference]) {
+        for object in objects {
+            if self.immix.in_space(*object) {
+                VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                    .mark_as_unlogged::<VM>(*object, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Allow the mutators to allocate `max_nursery` bytes of new pages before the next nursery GC.
+    /// If there is not room for `min_nursery` bytes after this GC, the next GC will be a full heap GC.
+    pub(super) fn update_nursery_trigger(&self) {
+        let reserved_pages = self.get_reserved_pages();
+        let max_nursery_pages = conversions::bytes_to_pages_up(*self.base().options.max_nursery);
+        let min_nursery_pages = conversions::bytes_to_pages_up(*self.base().options.min_nursery);
+        self.nursery_trigger_pages
+            .store(reserved_pages + max_nursery_pages, Ordering::Relaxed);
+        if self.get_total_pages().saturating_sub(reserved_pages) < min_nursery_pages {
+            self.next_gc_full_heap.store(true, Ordering::SeqCst);
+        }
+    }
+}
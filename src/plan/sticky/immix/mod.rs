@@ -0,0 +1,9 @@
+//! Plan: sticky immix
+
+pub(in crate::plan) mod gc_work;
+pub(in crate::plan) mod global;
+pub(in crate::plan) mod mutator;
+
+pub use self::global::StickyImmix;
+
+pub use self::global::STICKY_IMMIX_CONSTRAINTS;
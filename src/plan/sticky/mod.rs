@@ -0,0 +1,6 @@
+//! Plans with sticky mark bits
+
+// Sticky mark bits plans:
+
+/// Sticky immix (StickyImmix)
+pub mod immix;
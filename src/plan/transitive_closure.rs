@@ -27,23 +27,30 @@ impl<T: ProcessEdgesWork> TransitiveClosure for T {
 pub struct ObjectsClosure<'a, E: ProcessEdgesWork> {
     buffer: Vec<Address>,
     worker: &'a mut GCWorker<E::VM>,
+    /// The bucket that the edge processing work packets are added to.
+    bucket: WorkBucketStage,
 }
 
 impl<'a, E: ProcessEdgesWork> ObjectsClosure<'a, E> {
     pub fn new(worker: &'a mut GCWorker<E::VM>) -> Self {
+        Self::new_in_bucket(worker, WorkBucketStage::Closure)
+    }
+
+    /// Create a closure that adds the edge processing work packets to the given bucket,
+    /// e.g. `WorkBucketStage::Concurrent` for concurrent tracing.
+    pub fn new_in_bucket(worker: &'a mut GCWorker<E::VM>, bucket: WorkBucketStage) -> Self {
         Self {
             buffer: vec![],
             worker,
+            bucket,
         }
     }
 
     fn flush(&mut self) {
         let mut new_edges = Vec::new();
         mem::swap(&mut new_edges, &mut self.buffer);
-        self.worker.add_work(
-            WorkBucketStage::Closure,
-            E::new(new_edges, false, self.worker.mmtk),
-        );
+        self.worker
+            .add_work(self.bucket, E::new(new_edges, false, self.worker.mmtk));
     }
 }
 
@@ -57,10 +64,8 @@ impl<'a, E: ProcessEdgesWork> EdgeVisitor for ObjectsClosure<'a, E> {
         if self.buffer.len() >= E::CAPACITY {
             let mut new_edges = Vec::new();
             mem::swap(&mut new_edges, &mut self.buffer);
-            self.worker.add_work(
-                WorkBucketStage::Closure,
-                E::new(new_edges, false, self.worker.mmtk),
-            );
+            self.worker
+                .add_work(self.bucket, E::new(new_edges, false, self.worker.mmtk));
         }
     }
 }
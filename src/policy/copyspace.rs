@@ -4,6 +4,7 @@ use crate::policy::space::SpaceOptions;
 use crate::policy::space::*;
 use crate::policy::space::{CommonSpace, Space, SFT};
 use crate::scheduler::GCWorker;
+use crate::util::alloc::PostAllocAction;
 use crate::util::constants::CARD_META_PAGES_PER_REGION;
 use crate::util::copy::*;
 use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
@@ -52,6 +53,14 @@ impl<VM: VMBinding> SFT for CopySpace<VM> {
         crate::util::alloc_bit::set_alloc_bit(_object);
     }
 
+    fn post_alloc_action(&self) -> PostAllocAction {
+        if cfg!(feature = "global_alloc_bit") {
+            PostAllocAction::SetAllocBit
+        } else {
+            PostAllocAction::None
+        }
+    }
+
     #[inline(always)]
     fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
         if !self.is_from_space() {
@@ -3,6 +3,7 @@ use super::{
     line::Line,
     ImmixSpace,
 };
+use crate::plan::Plan;
 use crate::policy::space::Space;
 use crate::util::linear_scan::Region;
 use crate::{util::constants::LOG_BYTES_IN_PAGE, vm::*};
@@ -11,6 +12,33 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 pub type Histogram = [usize; Defrag::NUM_BINS];
 
+/// The facts about the current GC that decide whether it should do defragmentation.
+#[derive(Debug, Clone, Copy)]
+pub struct DefragDecision {
+    pub emergency_collection: bool,
+    pub collect_whole_heap: bool,
+    pub collection_attempts: usize,
+    pub user_triggered: bool,
+    pub full_heap_system_gc: bool,
+    /// Did the last full heap GC exceed the pause time goal?
+    pub exceeded_pause_goal: bool,
+}
+
+impl DefragDecision {
+    /// Gather the facts for a full heap GC of the given plan.
+    pub fn full_heap_gc<P: Plan>(plan: &P) -> Self {
+        let base = plan.base();
+        DefragDecision {
+            emergency_collection: plan.is_emergency_collection(),
+            collect_whole_heap: true,
+            collection_attempts: base.cur_collection_attempts.load(Ordering::SeqCst),
+            user_triggered: base.is_user_triggered_collection(),
+            full_heap_system_gc: *base.options.full_heap_system_gc,
+            exceeded_pause_goal: base.pause_goal.is_exceeded_by_full_heap_gc(),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Defrag {
     /// Is current GC a defrag GC?
@@ -49,22 +77,15 @@ impl Defrag {
         self.in_defrag_collection.load(Ordering::Acquire)
     }
 
-    /// Determine whether the current GC should do defragmentation.
-    pub fn decide_whether_to_defrag(
-        &self,
-        emergency_collection: bool,
-        collect_whole_heap: bool,
-        collection_attempts: usize,
-        user_triggered: bool,
-        exhausted_reusable_space: bool,
-        full_heap_system_gc: bool,
-    ) {
+    /// Determine whether the current GC should do defragmentation. If the last full heap GC exceeded
+    /// the pause time goal, we only defrag when we have to.
+    pub fn decide_whether_to_defrag(&self, gc: &DefragDecision, exhausted_reusable_space: bool) {
         let in_defrag = super::DEFRAG
-            && (emergency_collection
-                || (collection_attempts > 1)
-                || !exhausted_reusable_space
+            && (gc.emergency_collection
+                || (gc.collection_attempts > 1)
+                || (!exhausted_reusable_space && !gc.exceeded_pause_goal)
                 || Self::DEFRAG_STRESS
-                || (collect_whole_heap && user_triggered && full_heap_system_gc));
+                || (gc.collect_whole_heap && gc.user_triggered && gc.full_heap_system_gc));
         // println!("Defrag: {}", in_defrag);
         self.in_defrag_collection
             .store(in_defrag, Ordering::Release)
@@ -2,12 +2,14 @@ use super::line::*;
 use super::{
     block::*,
     chunk::{Chunk, ChunkMap, ChunkState},
-    defrag::Defrag,
+    defrag::{Defrag, DefragDecision},
 };
 use crate::plan::ObjectsClosure;
+use crate::plan::PlanConstraints;
 use crate::policy::space::SpaceOptions;
 use crate::policy::space::*;
 use crate::policy::space::{CommonSpace, Space, SFT};
+use crate::util::alloc::PostAllocAction;
 use crate::util::copy::*;
 use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
 use crate::util::heap::HeapMeta;
@@ -31,7 +33,10 @@ use crate::{
     MMTK,
 };
 use atomic::Ordering;
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU8},
+    Arc,
+};
 
 pub struct ImmixSpace<VM: VMBinding> {
     common: CommonSpace<VM>,
@@ -48,6 +53,12 @@ pub struct ImmixSpace<VM: VMBinding> {
     pub(super) defrag: Defrag,
     /// Object mark state
     mark_state: u8,
+    /// True if the space is used by a concurrent plan. Only then `mark_on_alloc` may be set, so other plans
+    /// do not need to check it for each allocation.
+    concurrent: bool,
+    /// If true, objects are marked as live when they are allocated. A concurrent plan sets this while
+    /// marking is in progress, as objects allocated during marking are not part of the snapshot.
+    mark_on_alloc: AtomicBool,
     /// Work packet scheduler
     scheduler: Arc<GCWorkScheduler<VM>>,
 }
@@ -69,13 +80,59 @@ impl<VM: VMBinding> SFT for ImmixSpace<VM> {
     fn is_movable(&self) -> bool {
         super::DEFRAG
     }
+    #[cfg(feature = "object_pinning")]
+    fn pin_object(&self, object: ObjectReference) -> bool {
+        side_metadata::store_atomic(
+            &Self::PINNING_BIT_TABLE,
+            object.to_address(),
+            1,
+            Ordering::SeqCst,
+        );
+        true
+    }
+    #[cfg(feature = "object_pinning")]
+    fn unpin_object(&self, object: ObjectReference) -> bool {
+        side_metadata::compare_exchange_atomic(
+            &Self::PINNING_BIT_TABLE,
+            object.to_address(),
+            1,
+            0,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+    }
+    #[cfg(feature = "object_pinning")]
+    fn is_object_pinned(&self, object: ObjectReference) -> bool {
+        !super::DEFRAG || Self::is_pinned(object)
+    }
     #[cfg(feature = "sanity")]
     fn is_sane(&self) -> bool {
         true
     }
-    fn initialize_object_metadata(&self, _object: ObjectReference, _alloc: bool) {
+    fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
         #[cfg(feature = "global_alloc_bit")]
-        crate::util::alloc_bit::set_alloc_bit(_object);
+        crate::util::alloc_bit::set_alloc_bit(object);
+        // The pin bit may be left by a dead object at the same address.
+        #[cfg(feature = "object_pinning")]
+        Self::clear_pin_bit(object);
+        if self.concurrent && self.mark_on_alloc.load(Ordering::Acquire) {
+            self.attempt_mark(object, self.mark_state);
+            if !super::BLOCK_ONLY {
+                self.mark_lines(object);
+            } else {
+                Block::containing::<VM>(object).set_state(BlockState::Marked);
+            }
+        }
+    }
+    fn post_alloc_action(&self) -> PostAllocAction {
+        // Marking on allocation is only used by concurrent plans, which always call post_alloc().
+        if cfg!(feature = "object_pinning") {
+            PostAllocAction::CallPostAlloc
+        } else if cfg!(feature = "global_alloc_bit") {
+            PostAllocAction::SetAllocBit
+        } else {
+            PostAllocAction::None
+        }
     }
     #[inline(always)]
     fn sft_trace_object(
@@ -116,10 +173,13 @@ impl<VM: VMBinding> Space<VM> for ImmixSpace<VM> {
 impl<VM: VMBinding> ImmixSpace<VM> {
     const UNMARKED_STATE: u8 = 0;
     const MARKED_STATE: u8 = 1;
+    /// The pin bit of each object. Defrag does not move objects with the bit set.
+    const PINNING_BIT_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::IX_PINNING_BIT;
 
     /// Get side metadata specs
     fn side_metadata_specs() -> Vec<SideMetadataSpec> {
-        metadata::extract_side_metadata(&if super::BLOCK_ONLY {
+        let mut specs = if super::BLOCK_ONLY {
             vec![
                 MetadataSpec::OnSide(Block::DEFRAG_STATE_TABLE),
                 MetadataSpec::OnSide(Block::MARK_TABLE),
@@ -134,7 +194,11 @@ impl<VM: VMBinding> ImmixSpace<VM> {
                 MetadataSpec::OnSide(ChunkMap::ALLOC_TABLE),
                 *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
             ]
-        })
+        };
+        if cfg!(feature = "object_pinning") {
+            specs.push(MetadataSpec::OnSide(Self::PINNING_BIT_TABLE));
+        }
+        metadata::extract_side_metadata(&specs)
     }
 
     pub fn new(
@@ -144,6 +208,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         heap: &mut HeapMeta,
         scheduler: Arc<GCWorkScheduler<VM>>,
         global_side_metadata_specs: Vec<SideMetadataSpec>,
+        constraints: &'static PlanConstraints,
     ) -> Self {
         let common = CommonSpace::new(
             SpaceOptions {
@@ -175,6 +240,8 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             reusable_blocks: BlockList::default(),
             defrag: Defrag::default(),
             mark_state: Self::UNMARKED_STATE,
+            concurrent: constraints.needs_concurrent_workers,
+            mark_on_alloc: AtomicBool::new(false),
             scheduler,
         }
     }
@@ -191,30 +258,27 @@ impl<VM: VMBinding> ImmixSpace<VM> {
     }
 
     /// check if the current GC should do defragmentation.
-    pub fn decide_whether_to_defrag(
-        &self,
-        emergency_collection: bool,
-        collect_whole_heap: bool,
-        collection_attempts: usize,
-        user_triggered_collection: bool,
-        full_heap_system_gc: bool,
-    ) -> bool {
-        self.defrag.decide_whether_to_defrag(
-            emergency_collection,
-            collect_whole_heap,
-            collection_attempts,
-            user_triggered_collection,
-            self.reusable_blocks.len() == 0,
-            full_heap_system_gc,
-        );
+    pub fn decide_whether_to_defrag(&self, gc: &DefragDecision) -> bool {
+        self.defrag
+            .decide_whether_to_defrag(gc, self.reusable_blocks.len() == 0);
         self.defrag.in_defrag()
     }
 
+    /// Set whether newly allocated objects should be marked as live. This is only allowed for a concurrent plan.
+    pub fn set_mark_on_alloc(&self, mark_on_alloc: bool) {
+        debug_assert!(self.concurrent);
+        self.mark_on_alloc.store(mark_on_alloc, Ordering::Release);
+    }
+
     /// Get work packet scheduler
     fn scheduler(&self) -> &GCWorkScheduler<VM> {
         &self.scheduler
     }
 
+    /// Prepare for the immix space. This is called when a GC starts.
+    /// A plan with sticky mark bits calls this with `major_gc == false` for a nursery GC, in which case the
+    /// object marks, the line marks and the block states are kept, so the objects that survived earlier GCs
+    /// stay live and only new objects are traced.
     pub fn prepare(&mut self, major_gc: bool) {
         if major_gc {
             // Update mark_state
@@ -230,6 +294,10 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         if super::DEFRAG {
             self.defrag.prepare(self);
         }
+        if !major_gc {
+            // Nursery GC with sticky mark bits.
+            return;
+        }
         // Prepare each block for GC
         let threshold = self.defrag.defrag_spill_threshold.load(Ordering::Acquire);
         // # Safety: ImmixSpace reference is always valid within this collection cycle.
@@ -377,6 +445,28 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         object
     }
 
+    /// Trace objects in a nursery GC for a plan with sticky mark bits. Mark bits are not cleared in
+    /// nursery GCs, so a marked object has survived an earlier GC and is not traced again. A new object is
+    /// opportunistically copied if `copy` is true, and marked in place otherwise.
+    #[inline(always)]
+    pub fn trace_object_nursery(
+        &self,
+        trace: &mut impl TransitiveClosure,
+        object: ObjectReference,
+        semantics: CopySemantics,
+        worker: &mut GCWorker<VM>,
+        copy: bool,
+    ) -> ObjectReference {
+        if self.is_marked(object, self.mark_state) {
+            return object;
+        }
+        if copy {
+            self.trace_object_with_opportunistic_copy(trace, object, semantics, worker)
+        } else {
+            self.trace_object_without_moving(trace, object)
+        }
+    }
+
     /// Trace object and do evacuation if required.
     #[allow(clippy::assertions_on_constants)]
     #[inline(always)]
@@ -500,9 +590,25 @@ impl<VM: VMBinding> ImmixSpace<VM> {
 
     /// Check if an object is pinned.
     #[inline(always)]
-    fn is_pinned(_object: ObjectReference) -> bool {
-        // TODO(wenyuzhao): Object pinning not supported yet.
-        false
+    fn is_pinned(object: ObjectReference) -> bool {
+        cfg!(feature = "object_pinning")
+            && side_metadata::load_atomic(
+                &Self::PINNING_BIT_TABLE,
+                object.to_address(),
+                Ordering::SeqCst,
+            ) == 1
+    }
+
+    /// Clear the pin bit of a new object.
+    #[cfg(feature = "object_pinning")]
+    #[inline(always)]
+    fn clear_pin_bit(object: ObjectReference) {
+        side_metadata::store_atomic(
+            &Self::PINNING_BIT_TABLE,
+            object.to_address(),
+            0,
+            Ordering::SeqCst,
+        );
     }
 
     /// Hole searching.
@@ -605,7 +711,7 @@ impl<VM: VMBinding> GCWork<VM> for PrepareBlockState<VM> {
 /// A work packet to scan the fields of each objects and mark lines.
 pub struct ScanObjectsAndMarkLines<Edges: ProcessEdgesWork> {
     buffer: Vec<ObjectReference>,
-    #[allow(unused)]
+    /// If true, the edges are processed in the `Concurrent` bucket rather than the `Closure` bucket.
     concurrent: bool,
     immix_space: &'static ImmixSpace<Edges::VM>,
 }
@@ -625,10 +731,19 @@ impl<Edges: ProcessEdgesWork> ScanObjectsAndMarkLines<Edges> {
 }
 
 impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanObjectsAndMarkLines<E> {
-    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, _mmtk: &'static MMTK<E::VM>) {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
         trace!("ScanObjectsAndMarkLines");
+        mmtk.plan
+            .base()
+            .heap_sizing
+            .on_objects_traced::<E::VM>(&self.buffer);
         let tls = worker.tls;
-        let mut closure = ObjectsClosure::<E>::new(worker);
+        let bucket = if self.concurrent {
+            WorkBucketStage::Concurrent
+        } else {
+            WorkBucketStage::Closure
+        };
+        let mut closure = ObjectsClosure::<E>::new_in_bucket(worker, bucket);
         for object in &self.buffer {
             <E::VM as VMBinding>::VMScanning::scan_object(tls, *object, &mut closure);
             if super::MARK_LINE_AT_SCAN_TIME
@@ -691,6 +806,8 @@ impl<VM: VMBinding> PolicyCopyContext for ImmixCopyContext<VM> {
         if !super::MARK_LINE_AT_SCAN_TIME {
             self.get_space().mark_lines(obj);
         }
+        #[cfg(feature = "object_pinning")]
+        ImmixSpace::<VM>::clear_pin_bit(obj);
     }
 }
 
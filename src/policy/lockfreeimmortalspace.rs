@@ -1,6 +1,7 @@
 use crate::mmtk::SFT_MAP;
 use crate::policy::space::{CommonSpace, Space, SFT};
 use crate::util::address::Address;
+use crate::util::alloc::PostAllocAction;
 use crate::util::heap::PageResource;
 use crate::util::ObjectReference;
 
@@ -58,6 +59,13 @@ impl<VM: VMBinding> SFT for LockFreeImmortalSpace<VM> {
         #[cfg(feature = "global_alloc_bit")]
         crate::util::alloc_bit::set_alloc_bit(_object);
     }
+    fn post_alloc_action(&self) -> PostAllocAction {
+        if cfg!(feature = "global_alloc_bit") {
+            PostAllocAction::SetAllocBit
+        } else {
+            PostAllocAction::None
+        }
+    }
     fn sft_trace_object(
         &self,
         _trace: SFTProcessEdgesMutRef,
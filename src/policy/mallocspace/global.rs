@@ -1,7 +1,9 @@
 use super::metadata::*;
 use crate::plan::TransitiveClosure;
+use crate::policy::copy_context::PolicyCopyContext;
 use crate::policy::space::CommonSpace;
 use crate::policy::space::SFT;
+use crate::util::alloc::AllocationError;
 use crate::util::constants::BYTES_IN_PAGE;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::PageResource;
@@ -39,6 +41,9 @@ pub struct MallocSpace<VM: VMBinding> {
     pub chunk_addr_min: AtomicUsize, // XXX: have to use AtomicUsize to represent an Address
     pub chunk_addr_max: AtomicUsize,
     metadata: SideMetadataContext,
+    /// Whether the current GC traces this space. Objects copied into the space in such a GC are
+    /// marked, as the space will be swept before the next GC.
+    traced: bool,
     // Mapping between allocated address and its size - this is used to check correctness.
     // Size will be set to zero when the memory is freed.
     #[cfg(debug_assertions)]
@@ -204,6 +209,7 @@ impl<VM: VMBinding> MallocSpace<VM> {
                     *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
                 ]),
             },
+            traced: true,
             #[cfg(debug_assertions)]
             active_mem: Mutex::new(HashMap::new()),
             #[cfg(debug_assertions)]
@@ -223,6 +229,29 @@ impl<VM: VMBinding> MallocSpace<VM> {
             return unsafe { Address::zero() };
         }
 
+        self.alloc_internal(size, align, offset)
+    }
+
+    /// Allocate memory for an object that is copied into this space during a GC. This does not poll for a GC.
+    /// A GC cannot continue if the copy fails, so a malloc failure is reported to the binding as a critical OOM.
+    pub fn alloc_copy(
+        &self,
+        tls: VMWorkerThread,
+        size: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        let address = self.alloc_internal(size, align, offset);
+        if address.is_zero() {
+            // Signal `MmapOutOfMemory`. Expect the VM to abort immediately.
+            trace!("Signal MmapOutOfMemory!");
+            VM::VMCollection::out_of_memory(tls.0, AllocationError::MmapOutOfMemory);
+            unreachable!()
+        }
+        address
+    }
+
+    fn alloc_internal(&self, size: usize, align: usize, offset: isize) -> Address {
         let (address, is_offset_malloc) = alloc::<VM>(size, align, offset);
         if !address.is_zero() {
             let actual_size = get_malloc_usable_size(address, is_offset_malloc);
@@ -250,6 +279,21 @@ impl<VM: VMBinding> MallocSpace<VM> {
         address
     }
 
+    /// Prepare the space for a GC. `traced` is whether this GC traces and sweeps the space.
+    /// A generational plan only traces its mature space in a full heap GC.
+    pub fn prepare(&mut self, traced: bool) {
+        self.traced = traced;
+    }
+
+    /// Initialize the metadata for an object that is copied into this space.
+    pub fn post_copy(&self, object: ObjectReference) {
+        self.initialize_object_metadata(object, false);
+        if self.traced {
+            set_mark_bit::<VM>(object, Some(Ordering::SeqCst));
+            set_chunk_mark(conversions::chunk_align_down(object.to_address()));
+        }
+    }
+
     pub fn free(&self, addr: Address) {
         let offset_malloc_bit = is_offset_malloc(addr);
         let bytes = get_malloc_usable_size(addr, offset_malloc_bit);
@@ -620,3 +664,39 @@ impl<VM: VMBinding> crate::util::linear_scan::LinearScanObjectSize for MallocObj
         bytes
     }
 }
+
+/// Copy allocator for MallocSpace. Objects copied into the space are allocated with malloc.
+pub struct MallocSpaceCopyContext<VM: VMBinding> {
+    tls: VMWorkerThread,
+    space: &'static MallocSpace<VM>,
+}
+
+impl<VM: VMBinding> PolicyCopyContext for MallocSpaceCopyContext<VM> {
+    type VM = VM;
+
+    fn prepare(&mut self) {}
+
+    fn release(&mut self) {}
+
+    #[inline(always)]
+    fn alloc_copy(
+        &mut self,
+        _original: ObjectReference,
+        bytes: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        self.space.alloc_copy(self.tls, bytes, align, offset)
+    }
+
+    #[inline(always)]
+    fn post_copy(&mut self, obj: ObjectReference, _bytes: usize) {
+        self.space.post_copy(obj)
+    }
+}
+
+impl<VM: VMBinding> MallocSpaceCopyContext<VM> {
+    pub fn new(tls: VMWorkerThread, space: &'static MallocSpace<VM>) -> Self {
+        MallocSpaceCopyContext { tls, space }
+    }
+}
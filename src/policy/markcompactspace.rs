@@ -1,19 +1,32 @@
 use super::space::{CommonSpace, Space, SpaceOptions, SFT};
 use crate::policy::space::*;
 use crate::util::alloc::allocator::align_allocation_no_fill;
+use crate::util::alloc::PostAllocAction;
+#[cfg(not(feature = "markcompact_side_forwarding"))]
 use crate::util::constants::LOG_BYTES_IN_WORD;
+use crate::util::constants::{LOG_BITS_IN_WORD, LOG_MIN_OBJECT_SIZE};
 use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
 use crate::util::heap::{HeapMeta, MonotonePageResource, PageResource, VMRequest};
+use crate::util::linear_scan::{LinearScanObjectSize, Region, RegionIterator};
 use crate::util::metadata::load_metadata;
+#[cfg(feature = "markcompact_side_forwarding")]
+use crate::util::metadata::side_metadata;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSpec};
 use crate::util::metadata::{compare_exchange_metadata, extract_side_metadata};
 use crate::util::{alloc_bit, Address, ObjectReference};
 use crate::{vm::*, TransitiveClosure};
-use atomic::Ordering;
+use atomic::{Atomic, Ordering};
+use std::sync::atomic::AtomicUsize;
 
 pub struct MarkCompactSpace<VM: VMBinding> {
     common: CommonSpace<VM>,
     pr: MonotonePageResource<VM>,
+    /// The compaction state of each region in the current GC.
+    regions: Vec<RegionState>,
+    /// The number of regions whose live bytes are not yet computed in the current GC.
+    regions_to_summarize: AtomicUsize,
+    /// The end of the compacted objects. The bump pointer is reset to it after compaction.
+    compaction_end: Atomic<Address>,
 }
 
 const GC_MARK_BIT_MASK: usize = 1;
@@ -21,9 +34,196 @@ const GC_MARK_BIT_MASK: usize = 1;
 /// For each MarkCompact object, we need one extra word for storing forwarding pointer (Lisp-2 implementation).
 /// Note that considering the object alignment, we may end up allocating/reserving more than one word per object.
 /// See [`MarkCompactSpace::HEADER_RESERVED_IN_BYTES`].
+/// With the `markcompact_side_forwarding` feature, forwarding pointers are computed from side metadata instead,
+/// and no extra word is needed.
+#[cfg(not(feature = "markcompact_side_forwarding"))]
 pub const GC_EXTRA_HEADER_WORD: usize = 1;
+#[cfg(feature = "markcompact_side_forwarding")]
+pub const GC_EXTRA_HEADER_WORD: usize = 0;
+#[cfg(not(feature = "markcompact_side_forwarding"))]
 const GC_EXTRA_HEADER_BYTES: usize = GC_EXTRA_HEADER_WORD << LOG_BYTES_IN_WORD;
 
+/// How the forwarding pointers of the live objects are kept in a GC. `HeaderForwarding` is used by default,
+/// and `SideForwarding` is used with the `markcompact_side_forwarding` feature.
+trait ForwardingScheme<VM: VMBinding> {
+    /// The bytes reserved before each object for the forwarding pointer.
+    const HEADER_RESERVED_IN_BYTES: usize;
+    /// The local side metadata used by the scheme.
+    fn side_metadata_specs() -> Vec<SideMetadataSpec>;
+    /// Remember that an object is live. This is called when the object is marked.
+    fn set_live(object: ObjectReference);
+    /// Store the forwarding pointer for an object. `to` is where the previous live object in the region ends after compaction.
+    fn store_forwarding_pointer(
+        object: ObjectReference,
+        to: Address,
+        forwarding_pointer: ObjectReference,
+    );
+    /// Get the forwarding pointer for an object, or null if the object is not live.
+    fn get_forwarding_pointer(object: ObjectReference) -> ObjectReference;
+    /// Get the new location of an object during compaction, and clear its forwarding state.
+    /// `to` is where the previous live object in the region ends after compaction.
+    fn take_forwarding_pointer(object: ObjectReference, to: &mut Address) -> ObjectReference;
+}
+
+#[cfg(not(feature = "markcompact_side_forwarding"))]
+type ActiveForwarding = HeaderForwarding;
+#[cfg(feature = "markcompact_side_forwarding")]
+type ActiveForwarding = SideForwarding;
+
+// Basically for each allocation request, we allocate extra bytes of [`HEADER_RESERVED_IN_BYTES`].
+// From the allocation result we get (e.g. `alloc_res`), `alloc_res + HEADER_RESERVED_IN_BYTES` is the cell
+// address we return to the binding. It ensures we have at least one word (`GC_EXTRA_HEADER_WORD`) before
+// the cell address, and ensures the cell address is properly aligned.
+// From the cell address, `cell - GC_EXTRA_HEADER_WORD` is where we store the header forwarding pointer.
+
+/// Forwarding pointers are stored in an extra header word before each object.
+#[cfg(not(feature = "markcompact_side_forwarding"))]
+struct HeaderForwarding;
+
+#[cfg(not(feature = "markcompact_side_forwarding"))]
+impl HeaderForwarding {
+    /// Get the address for header forwarding pointer
+    #[inline(always)]
+    fn header_forwarding_pointer_address<VM: VMBinding>(object: ObjectReference) -> Address {
+        VM::VMObjectModel::object_start_ref(object) - GC_EXTRA_HEADER_BYTES
+    }
+}
+
+#[cfg(not(feature = "markcompact_side_forwarding"))]
+impl<VM: VMBinding> ForwardingScheme<VM> for HeaderForwarding {
+    const HEADER_RESERVED_IN_BYTES: usize = if VM::MAX_ALIGNMENT > GC_EXTRA_HEADER_BYTES {
+        VM::MAX_ALIGNMENT
+    } else {
+        GC_EXTRA_HEADER_BYTES
+    }
+    .next_power_of_two();
+
+    fn side_metadata_specs() -> Vec<SideMetadataSpec> {
+        vec![]
+    }
+
+    #[inline(always)]
+    fn set_live(_object: ObjectReference) {}
+
+    #[inline(always)]
+    fn store_forwarding_pointer(
+        object: ObjectReference,
+        _to: Address,
+        forwarding_pointer: ObjectReference,
+    ) {
+        unsafe {
+            Self::header_forwarding_pointer_address::<VM>(object)
+                .store::<ObjectReference>(forwarding_pointer);
+        }
+    }
+
+    #[inline(always)]
+    fn get_forwarding_pointer(object: ObjectReference) -> ObjectReference {
+        unsafe { Self::header_forwarding_pointer_address::<VM>(object).load::<ObjectReference>() }
+    }
+
+    #[inline(always)]
+    fn take_forwarding_pointer(object: ObjectReference, _to: &mut Address) -> ObjectReference {
+        let forwarding_pointer = <Self as ForwardingScheme<VM>>::get_forwarding_pointer(object);
+        if !forwarding_pointer.is_null() {
+            // Clear the header forwarding pointer at the new location.
+            crate::util::memory::zero(
+                Self::header_forwarding_pointer_address::<VM>(forwarding_pointer),
+                GC_EXTRA_HEADER_BYTES,
+            );
+        }
+        forwarding_pointer
+    }
+}
+
+/// Forwarding pointers are not stored (Compressor style). A live object has its bit set in [`LIVE_BIT_SPEC`], and each
+/// block of [`BYTES_IN_FORWARDING_BLOCK`] records in [`FORWARDING_OFFSET_SPEC`] where the first live object in the
+/// block is compacted to. The forwarding pointer of an object is computed by sliding the live objects before it
+/// in its block. The live bits of a block fit in a word, so only the live objects are visited.
+#[cfg(feature = "markcompact_side_forwarding")]
+struct SideForwarding;
+
+#[cfg(feature = "markcompact_side_forwarding")]
+impl SideForwarding {
+    /// Return the start of the forwarding block of an object, and the live bits of the objects before it in the block.
+    /// Bit `i` is the live bit of the `i`-th word in the block.
+    #[inline(always)]
+    fn live_bits_before(object: ObjectReference) -> (Address, usize) {
+        let block = object.to_address().align_down(BYTES_IN_FORWARDING_BLOCK);
+        let meta = side_metadata::address_to_meta_address(&LIVE_BIT_SPEC, block);
+        // The live bits do not change while forwarding pointers are calculated and looked up.
+        let live_bits = unsafe { meta.load::<usize>() };
+        let index = (object.to_address() - block) >> LOG_MIN_OBJECT_SIZE;
+        (block, live_bits & ((1 << index) - 1))
+    }
+}
+
+#[cfg(feature = "markcompact_side_forwarding")]
+impl<VM: VMBinding> ForwardingScheme<VM> for SideForwarding {
+    const HEADER_RESERVED_IN_BYTES: usize = 0;
+
+    fn side_metadata_specs() -> Vec<SideMetadataSpec> {
+        vec![LIVE_BIT_SPEC, FORWARDING_OFFSET_SPEC]
+    }
+
+    #[inline(always)]
+    fn set_live(object: ObjectReference) {
+        side_metadata::store_atomic(&LIVE_BIT_SPEC, object.to_address(), 1, Ordering::SeqCst);
+    }
+
+    #[inline(always)]
+    fn store_forwarding_pointer(
+        object: ObjectReference,
+        to: Address,
+        _forwarding_pointer: ObjectReference,
+    ) {
+        let (block, live_bits_before) = Self::live_bits_before(object);
+        if live_bits_before == 0 {
+            side_metadata::store_atomic(
+                &FORWARDING_OFFSET_SPEC,
+                block,
+                to.as_usize(),
+                Ordering::SeqCst,
+            );
+        }
+    }
+
+    fn get_forwarding_pointer(object: ObjectReference) -> ObjectReference {
+        if side_metadata::load_atomic(&LIVE_BIT_SPEC, object.to_address(), Ordering::SeqCst) == 0 {
+            return ObjectReference::NULL;
+        }
+        let (block, mut live_bits_before) = Self::live_bits_before(object);
+        let mut to = unsafe {
+            Address::from_usize(side_metadata::load_atomic(
+                &FORWARDING_OFFSET_SPEC,
+                block,
+                Ordering::SeqCst,
+            ))
+        };
+        while live_bits_before != 0 {
+            let index = live_bits_before.trailing_zeros() as usize;
+            let obj = unsafe { (block + (index << LOG_MIN_OBJECT_SIZE)).to_object_reference() };
+            to = MarkCompactSpace::<VM>::slide_object(obj, to).1;
+            live_bits_before &= live_bits_before - 1;
+        }
+        let (new_start, _) = MarkCompactSpace::<VM>::slide_object(object, to);
+        VM::VMObjectModel::get_reference_when_copied_to(object, new_start)
+    }
+
+    #[inline(always)]
+    fn take_forwarding_pointer(object: ObjectReference, to: &mut Address) -> ObjectReference {
+        if side_metadata::load_atomic(&LIVE_BIT_SPEC, object.to_address(), Ordering::SeqCst) == 0 {
+            return ObjectReference::NULL;
+        }
+        side_metadata::store_atomic(&LIVE_BIT_SPEC, object.to_address(), 0, Ordering::SeqCst);
+        // Objects before this one in the block may have been overwritten, so we cannot
+        // look up the forwarding pointer. Slide from the previous object instead.
+        let (new_start, end) = MarkCompactSpace::<VM>::slide_object(object, *to);
+        *to = end;
+        VM::VMObjectModel::get_reference_when_copied_to(object, new_start)
+    }
+}
+
 impl<VM: VMBinding> SFT for MarkCompactSpace<VM> {
     fn name(&self) -> &str {
         self.get_name()
@@ -31,7 +231,7 @@ impl<VM: VMBinding> SFT for MarkCompactSpace<VM> {
 
     #[inline(always)]
     fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
-        let forwarding_pointer = Self::get_header_forwarding_pointer(object);
+        let forwarding_pointer = Self::get_forwarding_pointer(object);
         if forwarding_pointer.is_null() {
             None
         } else {
@@ -53,6 +253,10 @@ impl<VM: VMBinding> SFT for MarkCompactSpace<VM> {
         crate::util::alloc_bit::set_alloc_bit(object);
     }
 
+    fn post_alloc_action(&self) -> PostAllocAction {
+        PostAllocAction::SetAllocBit
+    }
+
     #[cfg(feature = "sanity")]
     fn is_sane(&self) -> bool {
         true
@@ -99,52 +303,27 @@ impl<VM: VMBinding> Space<VM> for MarkCompactSpace<VM> {
 
 impl<VM: VMBinding> MarkCompactSpace<VM> {
     /// We need one extra header word for each object. Considering the alignment requirement, this is
-    /// the actual bytes we need to reserve for each allocation.
-    pub const HEADER_RESERVED_IN_BYTES: usize = if VM::MAX_ALIGNMENT > GC_EXTRA_HEADER_BYTES {
-        VM::MAX_ALIGNMENT
-    } else {
-        GC_EXTRA_HEADER_BYTES
-    }
-    .next_power_of_two();
-
-    // The following are a few functions for manipulating header forwarding poiner.
-    // Basically for each allocation request, we allocate extra bytes of [`HEADER_RESERVED_IN_BYTES`].
-    // From the allocation result we get (e.g. `alloc_res`), `alloc_res + HEADER_RESERVED_IN_BYTES` is the cell
-    // address we return to the binding. It ensures we have at least one word (`GC_EXTRA_HEADER_WORD`) before
-    // the cell address, and ensures the cell address is properly aligned.
-    // From the cell address, `cell - GC_EXTRA_HEADER_WORD` is where we store the header forwarding pointer.
+    /// the actual bytes we need to reserve for each allocation. No header is reserved when forwarding
+    /// pointers are computed from side metadata.
+    pub const HEADER_RESERVED_IN_BYTES: usize =
+        <ActiveForwarding as ForwardingScheme<VM>>::HEADER_RESERVED_IN_BYTES;
 
-    /// Get the address for header forwarding pointer
+    /// Get the forwarding pointer for an object, or null if the object is not live.
     #[inline(always)]
-    fn header_forwarding_pointer_address(object: ObjectReference) -> Address {
-        VM::VMObjectModel::object_start_ref(object) - GC_EXTRA_HEADER_BYTES
+    fn get_forwarding_pointer(object: ObjectReference) -> ObjectReference {
+        <ActiveForwarding as ForwardingScheme<VM>>::get_forwarding_pointer(object)
     }
 
-    /// Get header forwarding pointer for an object
+    /// Slide an object to `to`. Return the aligned allocation address for the object, and
+    /// the end of the allocation.
     #[inline(always)]
-    fn get_header_forwarding_pointer(object: ObjectReference) -> ObjectReference {
-        unsafe { Self::header_forwarding_pointer_address(object).load::<ObjectReference>() }
-    }
-
-    /// Store header forwarding pointer for an object
-    #[inline(always)]
-    fn store_header_forwarding_pointer(
-        object: ObjectReference,
-        forwarding_pointer: ObjectReference,
-    ) {
-        unsafe {
-            Self::header_forwarding_pointer_address(object)
-                .store::<ObjectReference>(forwarding_pointer);
-        }
-    }
-
-    // Clear header forwarding pointer for an object
-    #[inline(always)]
-    fn clear_header_forwarding_pointer(object: ObjectReference) {
-        crate::util::memory::zero(
-            Self::header_forwarding_pointer_address(object),
-            GC_EXTRA_HEADER_BYTES,
-        );
+    fn slide_object(object: ObjectReference, to: Address) -> (Address, Address) {
+        let copied_size =
+            VM::VMObjectModel::get_size_when_copied(object) + Self::HEADER_RESERVED_IN_BYTES;
+        let align = VM::VMObjectModel::get_align_when_copied(object);
+        let offset = VM::VMObjectModel::get_align_offset_when_copied(object);
+        let start = align_allocation_no_fill::<VM>(to, align, offset);
+        (start, start + copied_size)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -157,7 +336,8 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
         mmapper: &'static Mmapper,
         heap: &mut HeapMeta,
     ) -> Self {
-        let local_specs = extract_side_metadata(&[*VM::VMObjectModel::LOCAL_MARK_BIT_SPEC]);
+        let mut local_specs = extract_side_metadata(&[*VM::VMObjectModel::LOCAL_MARK_BIT_SPEC]);
+        local_specs.extend(<ActiveForwarding as ForwardingScheme<VM>>::side_metadata_specs());
         let common = CommonSpace::new(
             SpaceOptions {
                 name,
@@ -182,12 +362,28 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
                 MonotonePageResource::new_contiguous(common.start, common.extent, 0, vm_map)
             },
             common,
+            regions: vec![],
+            regions_to_summarize: AtomicUsize::new(0),
+            compaction_end: Atomic::new(Address::ZERO),
         }
     }
 
-    pub fn prepare(&self) {}
+    pub fn prepare(&mut self) {
+        let start = self.common.start;
+        let end = self.pr.cursor();
+        let num_regions =
+            (end - start + CompactionRegion::BYTES - 1) >> CompactionRegion::LOG_BYTES;
+        self.regions = (0..num_regions).map(|_| RegionState::new()).collect();
+        self.regions_to_summarize
+            .store(num_regions, Ordering::SeqCst);
+        self.compaction_end.store(start, Ordering::SeqCst);
+    }
 
-    pub fn release(&self) {}
+    pub fn release(&self) {
+        // reset the bump pointer
+        self.pr
+            .reset_cursor(self.compaction_end.load(Ordering::SeqCst));
+    }
 
     pub fn trace_mark_object<T: TransitiveClosure>(
         &self,
@@ -200,6 +396,7 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
             object
         );
         if MarkCompactSpace::<VM>::test_and_mark(object) {
+            <ActiveForwarding as ForwardingScheme<VM>>::set_live(object);
             trace.process_node(object);
         }
         object
@@ -221,7 +418,7 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
             trace.process_node(object);
         }
 
-        Self::get_header_forwarding_pointer(object)
+        Self::get_forwarding_pointer(object)
     }
 
     pub fn test_and_mark(object: ObjectReference) -> bool {
@@ -294,81 +491,262 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
         Self::is_marked(object)
     }
 
-    pub fn calculate_forwarding_pointer(&self) {
-        let start = self.common.start;
-        let end = self.pr.cursor();
-        let mut to = start;
+    /// All the regions that are compacted in this GC.
+    pub fn regions(&self) -> RegionIterator<CompactionRegion> {
+        let first = CompactionRegion::from(self.common.start);
+        RegionIterator::new(first, first.next_nth(self.regions.len()))
+    }
 
-        let linear_scan =
-            crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
-                start, end,
-            );
-        for obj in linear_scan.filter(|obj| Self::to_be_compacted(*obj)) {
-            let copied_size =
-                VM::VMObjectModel::get_size_when_copied(obj) + Self::HEADER_RESERVED_IN_BYTES;
-            let align = VM::VMObjectModel::get_align_when_copied(obj);
-            let offset = VM::VMObjectModel::get_align_offset_when_copied(obj);
-            to = align_allocation_no_fill::<VM>(to, align, offset);
-            let new_obj = VM::VMObjectModel::get_reference_when_copied_to(
-                obj,
-                to + Self::HEADER_RESERVED_IN_BYTES,
-            );
+    #[inline(always)]
+    fn region_index(&self, region: CompactionRegion) -> usize {
+        (region.start() - self.common.start) >> CompactionRegion::LOG_BYTES
+    }
 
-            Self::store_header_forwarding_pointer(obj, new_obj);
+    #[inline(always)]
+    fn region_at(&self, index: usize) -> CompactionRegion {
+        CompactionRegion::from(self.common.start + (index << CompactionRegion::LOG_BYTES))
+    }
 
-            trace!(
-                "Calculate forward: {} (size when copied = {}) ~> {} (size = {})",
-                obj,
-                VM::VMObjectModel::get_size_when_copied(obj),
-                to,
-                copied_size
-            );
+    /// Iterate through the objects that start in the region.
+    fn objects_in_region(
+        &self,
+        region: CompactionRegion,
+    ) -> crate::util::linear_scan::ObjectIterator<VM, MarkCompactObjectSize<VM>, true> {
+        let end = std::cmp::min(region.end(), self.pr.cursor());
+        crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
+            region.start(),
+            end,
+        )
+    }
+
+    /// Slide the live objects in the region to `to` and return the end of the last object.
+    /// If `forward` is true, store the forwarding pointers, otherwise only compute the size.
+    fn slide_region(&self, region: CompactionRegion, mut to: Address, forward: bool) -> Address {
+        for obj in self
+            .objects_in_region(region)
+            .filter(|obj| Self::to_be_compacted(*obj))
+        {
+            let (new_start, end) = Self::slide_object(obj, to);
+            if forward {
+                let new_obj = VM::VMObjectModel::get_reference_when_copied_to(
+                    obj,
+                    new_start + Self::HEADER_RESERVED_IN_BYTES,
+                );
+                <ActiveForwarding as ForwardingScheme<VM>>::store_forwarding_pointer(
+                    obj, to, new_obj,
+                );
+
+                trace!(
+                    "Calculate forward: {} (size when copied = {}) ~> {} (size = {})",
+                    obj,
+                    VM::VMObjectModel::get_size_when_copied(obj),
+                    new_start,
+                    end - new_start
+                );
+            }
 
-            to += copied_size;
+            to = end;
+        }
+        to
+    }
+
+    /// Compute the size of the live objects in the region after compaction, and the extent of the
+    /// objects in the region. Return true if this is the last region to be summarized.
+    pub fn summarize_region(&self, region: CompactionRegion) -> bool {
+        let state = &self.regions[self.region_index(region)];
+        // Regions are aligned to MAX_ALIGNMENT, so sliding from the region start gives the same
+        // size as sliding from any MAX_ALIGNMENT-aligned destination.
+        let live_bytes = self.slide_region(region, region.start(), false) - region.start();
+        let first_object = self
+            .objects_in_region(region)
+            .find(|obj| Self::to_be_compacted(*obj))
+            .unwrap_or(ObjectReference::NULL);
+        let extent_end = self
+            .objects_in_region(region)
+            .map(|obj| {
+                obj.to_address() + <MarkCompactObjectSize<VM> as LinearScanObjectSize>::size(obj)
+            })
+            .fold(region.end(), std::cmp::max);
+        state.live_bytes.store(live_bytes, Ordering::SeqCst);
+        state.first_object.store(first_object, Ordering::SeqCst);
+        state.extent_end.store(extent_end, Ordering::SeqCst);
+        self.regions_to_summarize.fetch_sub(1, Ordering::SeqCst) == 1
+    }
+
+    /// Assign each region its destination, and work out which regions have to be compacted
+    /// before each region. This is done once all the regions are summarized.
+    pub fn plan_compaction(&self) {
+        let mut to = self.common.start;
+        for (index, state) in self.regions.iter().enumerate() {
+            let first_object = state.first_object.load(Ordering::SeqCst);
+            if first_object.is_null() {
+                state.to.store(to, Ordering::SeqCst);
+                continue;
+            }
+            // The live bytes were computed from an aligned destination. Pad the destination to keep them exact,
+            // unless the padding would move the first object above its current start: objects are compacted in
+            // place in address order, so that would overwrite the objects that are not compacted yet.
+            let source_start =
+                VM::VMObjectModel::object_start_ref(first_object) - Self::HEADER_RESERVED_IN_BYTES;
+            let aligned = to.align_up(VM::MAX_ALIGNMENT);
+            if Self::slide_object(first_object, aligned).0 <= source_start {
+                to = aligned;
+            } else {
+                let region = self.region_at(index);
+                let live_bytes = self.slide_region(region, to, false) - to;
+                state.live_bytes.store(live_bytes, Ordering::SeqCst);
+            }
+            debug_assert!(Self::slide_object(first_object, to).0 <= source_start);
+            state.to.store(to, Ordering::SeqCst);
+            to += state.live_bytes.load(Ordering::SeqCst);
+        }
+        for index in 0..self.regions.len() {
+            for dependent in self.dependents(index) {
+                self.regions[dependent]
+                    .blockers
+                    .fetch_add(1, Ordering::SeqCst);
+            }
         }
         debug!("Calculate forward end: to = {}", to);
+        self.compaction_end.store(to, Ordering::SeqCst);
     }
 
-    pub fn compact(&self) {
-        let start = self.common.start;
-        let end = self.pr.cursor();
-        let mut to = end;
+    /// The regions that cannot be compacted until the given region is compacted, as their
+    /// destinations overlap with the objects in the given region. The destinations are
+    /// monotonic, so the dependents are contiguous.
+    fn dependents(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let state = &self.regions[index];
+        let extent_start = self.region_at(index).start() - Self::HEADER_RESERVED_IN_BYTES;
+        let extent_end = state.extent_end.load(Ordering::SeqCst);
+        let first = index
+            + 1
+            + self.regions[index + 1..].partition_point(|s| {
+                s.to.load(Ordering::SeqCst) + s.live_bytes.load(Ordering::SeqCst) <= extent_start
+            });
+        (first..self.regions.len())
+            .take_while(move |&i| self.regions[i].to.load(Ordering::SeqCst) < extent_end)
+            .filter(move |&i| self.regions[i].live_bytes.load(Ordering::SeqCst) != 0)
+    }
 
-        let linear_scan =
-            crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
-                start, end,
-            );
-        for obj in linear_scan {
+    /// Store the forwarding pointers for the live objects in the region.
+    pub fn calculate_forwarding_pointer(&self, region: CompactionRegion) {
+        let state = &self.regions[self.region_index(region)];
+        let to = state.to.load(Ordering::SeqCst);
+        let end = self.slide_region(region, to, true);
+        debug_assert_eq!(end, to + state.live_bytes.load(Ordering::SeqCst));
+    }
+
+    /// The regions that can be compacted before any other region is compacted.
+    pub fn regions_ready_to_compact(&self) -> Vec<CompactionRegion> {
+        (0..self.regions.len())
+            .filter(|&i| self.regions[i].blockers.load(Ordering::SeqCst) == 0)
+            .map(|i| self.region_at(i))
+            .collect()
+    }
+
+    /// Compact the objects in the region. Return the regions that become ready to compact.
+    pub fn compact(&self, region: CompactionRegion) -> Vec<CompactionRegion> {
+        let index = self.region_index(region);
+        debug_assert_eq!(self.regions[index].blockers.load(Ordering::SeqCst), 0);
+
+        let mut to = self.regions[index].to.load(Ordering::SeqCst);
+        for obj in self.objects_in_region(region) {
             // clear the alloc bit
             alloc_bit::unset_addr_alloc_bit(obj.to_address());
 
-            let forwarding_pointer = Self::get_header_forwarding_pointer(obj);
+            let forwarding_pointer =
+                <ActiveForwarding as ForwardingScheme<VM>>::take_forwarding_pointer(obj, &mut to);
 
             trace!("Compact {} to {}", obj, forwarding_pointer);
             if !forwarding_pointer.is_null() {
                 let copied_size = VM::VMObjectModel::get_size_when_copied(obj);
                 let new_object = forwarding_pointer;
-                Self::clear_header_forwarding_pointer(new_object);
 
                 // copy object
                 trace!(" copy from {} to {}", obj, new_object);
                 let end_of_new_object = VM::VMObjectModel::copy_to(obj, new_object, Address::ZERO);
                 // update alloc_bit,
                 alloc_bit::set_alloc_bit(new_object);
-                to = new_object.to_address() + copied_size;
-                debug_assert_eq!(end_of_new_object, to);
+                debug_assert_eq!(
+                    end_of_new_object,
+                    VM::VMObjectModel::object_start_ref(new_object) + copied_size
+                );
             }
         }
 
-        debug!("Compact end: to = {}", to);
+        self.dependents(index)
+            .filter(|&i| self.regions[i].blockers.fetch_sub(1, Ordering::SeqCst) == 1)
+            .map(|i| self.region_at(i))
+            .collect()
+    }
+}
 
-        // reset the bump pointer
-        self.pr.reset_cursor(to);
+/// The unit of parallel compaction. The forwarding pointers of the objects that start in a region
+/// are calculated by one work packet, and those objects are compacted by one work packet.
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+pub struct CompactionRegion(Address);
+
+impl From<Address> for CompactionRegion {
+    #[inline(always)]
+    fn from(address: Address) -> CompactionRegion {
+        debug_assert!(address.is_aligned_to(Self::BYTES));
+        Self(address)
+    }
+}
+
+impl From<CompactionRegion> for Address {
+    #[inline(always)]
+    fn from(region: CompactionRegion) -> Address {
+        region.0
+    }
+}
+
+/// Marks the live objects, from which forwarding pointers are computed.
+#[cfg(feature = "markcompact_side_forwarding")]
+pub const LIVE_BIT_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::MC_LIVE_BIT;
+/// Records where the first live object in each forwarding block is compacted to.
+#[cfg(feature = "markcompact_side_forwarding")]
+pub const FORWARDING_OFFSET_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::MC_FORWARDING_OFFSET;
+/// Forwarding pointers are computed by sliding the live objects in a block of this size.
+/// The live bits of a block fit in a word.
+pub const LOG_BYTES_IN_FORWARDING_BLOCK: usize = LOG_BITS_IN_WORD + LOG_MIN_OBJECT_SIZE as usize;
+#[cfg(feature = "markcompact_side_forwarding")]
+const BYTES_IN_FORWARDING_BLOCK: usize = 1 << LOG_BYTES_IN_FORWARDING_BLOCK;
+
+impl Region for CompactionRegion {
+    const LOG_BYTES: usize = 20;
+}
+
+/// The per-GC compaction state of a region.
+struct RegionState {
+    /// Bytes of the live objects in the region after compaction, including alignment.
+    live_bytes: AtomicUsize,
+    /// The first live object in the region, or null if there is none.
+    first_object: Atomic<ObjectReference>,
+    /// The end of the objects that start in the region, or the region end if it is larger.
+    extent_end: Atomic<Address>,
+    /// Where the live objects in the region are compacted to.
+    to: Atomic<Address>,
+    /// The number of regions that need to be compacted before this region.
+    blockers: AtomicUsize,
+}
+
+impl RegionState {
+    fn new() -> Self {
+        Self {
+            live_bytes: AtomicUsize::new(0),
+            first_object: Atomic::new(ObjectReference::NULL),
+            extent_end: Atomic::new(Address::ZERO),
+            to: Atomic::new(Address::ZERO),
+            blockers: AtomicUsize::new(0),
+        }
     }
 }
 
 struct MarkCompactObjectSize<VM>(std::marker::PhantomData<VM>);
-impl<VM: VMBinding> crate::util::linear_scan::LinearScanObjectSize for MarkCompactObjectSize<VM> {
+impl<VM: VMBinding> LinearScanObjectSize for MarkCompactObjectSize<VM> {
     #[inline(always)]
     fn size(object: ObjectReference) -> usize {
         VM::VMObjectModel::get_current_size(object)
@@ -0,0 +1,249 @@
+use crate::policy::immix::chunk::Chunk;
+use crate::util::constants::*;
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::{self, SideMetadataSpec};
+use crate::util::metadata::{load_metadata, store_metadata};
+use crate::util::{alloc_bit, Address};
+use crate::vm::*;
+use spin::Mutex;
+use std::sync::atomic::Ordering;
+
+/// The cell sizes of all the size classes. Each block is divided into cells of one size class.
+pub const SIZE_CLASSES: [usize; NUM_SIZE_CLASSES] = [
+    8, 16, 24, 32, 40, 48, 56, 64, 72, 80, 88, 96, 104, 112, 120, 128, // 8 bytes apart
+    160, 192, 224, 256, 320, 384, 448, 512, 640, 768, 896, 1024, // 4 classes per power of two
+    1280, 1536, 1792, 2048, 2560, 3072, 3584, 4096, 5120, 6144, 7168, 8192,
+];
+
+/// Number of size classes.
+pub const NUM_SIZE_CLASSES: usize = 40;
+
+/// The largest object that can be allocated in a cell.
+pub const MAX_OBJECT_SIZE: usize = SIZE_CLASSES[NUM_SIZE_CLASSES - 1];
+
+/// Get the smallest size class whose cells can hold the given bytes.
+#[inline(always)]
+pub fn size_class(bytes: usize) -> usize {
+    debug_assert!(bytes <= MAX_OBJECT_SIZE);
+    SIZE_CLASSES.partition_point(|&cell_size| cell_size < bytes)
+}
+
+/// Data structure to reference a mark-sweep block.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+pub struct Block(Address);
+
+impl From<Address> for Block {
+    #[inline(always)]
+    fn from(address: Address) -> Block {
+        debug_assert!(address.is_aligned_to(Self::BYTES));
+        Self(address)
+    }
+}
+
+impl From<Block> for Address {
+    #[inline(always)]
+    fn from(block: Block) -> Address {
+        block.0
+    }
+}
+
+impl Region for Block {
+    const LOG_BYTES: usize = 16;
+}
+
+impl Block {
+    /// Log pages in block
+    pub const LOG_PAGES: usize = Self::LOG_BYTES - LOG_BYTES_IN_PAGE as usize;
+    /// Pages in block
+    pub const PAGES: usize = 1 << Self::LOG_PAGES;
+
+    /// Block allocation state table (side)
+    pub const STATE_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::MS_BLOCK_STATE;
+
+    /// Block size class table (side)
+    pub const SIZE_CLASS_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::MS_BLOCK_SIZE_CLASS;
+
+    /// Block free list table (side). Stores the first free cell of a block that is not used by any allocator.
+    pub const FREE_LIST_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::MS_BLOCK_FREE_LIST;
+
+    const UNALLOCATED: usize = 0;
+    const ALLOCATED: usize = 1;
+
+    /// Get the chunk containing the block.
+    #[inline(always)]
+    pub fn chunk(&self) -> Chunk {
+        Chunk::from(Chunk::align(self.0))
+    }
+
+    /// Test if the block is allocated.
+    #[inline(always)]
+    pub fn is_allocated(&self) -> bool {
+        side_metadata::load_atomic(&Self::STATE_TABLE, self.start(), Ordering::SeqCst)
+            == Self::ALLOCATED
+    }
+
+    /// Get the size class of the block.
+    #[inline(always)]
+    pub fn size_class(&self) -> usize {
+        side_metadata::load_atomic(&Self::SIZE_CLASS_TABLE, self.start(), Ordering::SeqCst)
+    }
+
+    /// Get the cell size of the block.
+    #[inline(always)]
+    pub fn cell_size(&self) -> usize {
+        SIZE_CLASSES[self.size_class()]
+    }
+
+    /// Get all the cells in the block.
+    #[inline(always)]
+    pub fn cells(&self) -> impl DoubleEndedIterator<Item = Address> {
+        let start = self.start();
+        let cell_size = self.cell_size();
+        (0..Self::BYTES / cell_size).map(move |i| start + i * cell_size)
+    }
+
+    /// Take the free list of the block. The block is then used by an allocator until the next GC.
+    #[inline]
+    pub fn take_free_list(&self) -> Address {
+        let head = unsafe {
+            Address::from_usize(side_metadata::load_atomic(
+                &Self::FREE_LIST_TABLE,
+                self.start(),
+                Ordering::SeqCst,
+            ))
+        };
+        self.set_free_list(Address::ZERO);
+        head
+    }
+
+    #[inline]
+    fn set_free_list(&self, head: Address) {
+        side_metadata::store_atomic(
+            &Self::FREE_LIST_TABLE,
+            self.start(),
+            head.as_usize(),
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Link the cells into a free list in address order, and return the number of cells in the list.
+    fn build_free_list(&self, free_cells: impl DoubleEndedIterator<Item = Address>) -> usize {
+        let mut head = Address::ZERO;
+        let mut count = 0;
+        for cell in free_cells.rev() {
+            unsafe { cell.store::<Address>(head) };
+            head = cell;
+            count += 1;
+        }
+        self.set_free_list(head);
+        count
+    }
+
+    /// Initialize a clean block after acquired from page-resource. All the cells are free.
+    #[inline]
+    pub fn init(&self, size_class: usize) {
+        side_metadata::store_atomic(
+            &Self::SIZE_CLASS_TABLE,
+            self.start(),
+            size_class,
+            Ordering::SeqCst,
+        );
+        side_metadata::store_atomic(
+            &Self::STATE_TABLE,
+            self.start(),
+            Self::ALLOCATED,
+            Ordering::SeqCst,
+        );
+        self.build_free_list(self.cells());
+    }
+
+    /// Deinitalize a block before releasing.
+    #[inline]
+    pub fn deinit(&self) {
+        self.set_free_list(Address::ZERO);
+        side_metadata::store_atomic(
+            &Self::STATE_TABLE,
+            self.start(),
+            Self::UNALLOCATED,
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Find the object allocated in the cell, if any.
+    #[inline]
+    fn cell_object<VM: VMBinding>(cell: Address, cell_size: usize) -> Option<Address> {
+        let mut cursor = cell;
+        while cursor < cell + cell_size {
+            if alloc_bit::is_alloced_object(cursor) {
+                return Some(cursor);
+            }
+            cursor += VM::MIN_ALIGNMENT;
+        }
+        None
+    }
+
+    /// Sweep this block. Dead objects are freed, and the mark bits of live objects are cleared.
+    /// The free cells are linked into the free list of the block.
+    /// Return the number of free cells.
+    pub fn sweep<VM: VMBinding>(&self) -> usize {
+        let cell_size = self.cell_size();
+        let mut free_cells = vec![];
+        for cell in self.cells() {
+            match Self::cell_object::<VM>(cell, cell_size) {
+                Some(address) => {
+                    let object = unsafe { address.to_object_reference() };
+                    let marked = load_metadata::<VM>(
+                        &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                        object,
+                        None,
+                        Some(Ordering::SeqCst),
+                    ) == 1;
+                    if marked {
+                        store_metadata::<VM>(
+                            &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                            object,
+                            0,
+                            None,
+                            Some(Ordering::SeqCst),
+                        );
+                    } else {
+                        alloc_bit::unset_addr_alloc_bit(address);
+                        free_cells.push(cell);
+                    }
+                }
+                None => free_cells.push(cell),
+            }
+        }
+        self.build_free_list(free_cells.into_iter())
+    }
+}
+
+/// A list of blocks that have free cells and are not used by any allocator.
+#[derive(Default)]
+pub struct BlockList {
+    queue: Mutex<Vec<Block>>,
+}
+
+impl BlockList {
+    /// Add a block to the list.
+    #[inline]
+    pub fn push(&self, block: Block) {
+        self.queue.lock().push(block)
+    }
+
+    /// Pop a block out of the list.
+    #[inline]
+    pub fn pop(&self) -> Option<Block> {
+        self.queue.lock().pop()
+    }
+
+    /// Clear the list.
+    #[inline]
+    pub fn reset(&self) {
+        *self.queue.lock() = Vec::new()
+    }
+}
@@ -0,0 +1,319 @@
+use super::block::*;
+use crate::policy::immix::chunk::{Chunk, ChunkMap, ChunkState};
+use crate::policy::space::SpaceOptions;
+use crate::policy::space::*;
+use crate::policy::space::{CommonSpace, Space, SFT};
+use crate::util::alloc_bit::ALLOC_SIDE_METADATA_SPEC;
+use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
+use crate::util::heap::FreeListPageResource;
+use crate::util::heap::HeapMeta;
+use crate::util::heap::PageResource;
+use crate::util::heap::VMRequest;
+use crate::util::linear_scan::{Region, RegionIterator};
+use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSpec};
+use crate::util::metadata::{self, compare_exchange_metadata, load_metadata, MetadataSpec};
+use crate::util::opaque_pointer::*;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use crate::{
+    plan::{PlanConstraints, TransitiveClosure},
+    scheduler::{GCWork, GCWorkScheduler, GCWorker, WorkBucketStage},
+    MMTK,
+};
+use atomic::Ordering;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// A mark-sweep space that allocates objects in segregated free lists, without using malloc.
+/// Each block holds cells of one size class. Blocks are swept in the release phase, and a block
+/// is released if none of its cells is live.
+pub struct MarkSweepSpace<VM: VMBinding> {
+    common: CommonSpace<VM>,
+    pr: FreeListPageResource<VM>,
+    /// Allocation status for all chunks in the space
+    pub chunk_map: ChunkMap,
+    /// Blocks with free cells that are not used by any allocator, for each size class.
+    available_blocks: Vec<BlockList>,
+    /// True if the space is used by a concurrent plan. Only then `mark_on_alloc` may be set, so other plans
+    /// do not need to check it for each allocation.
+    concurrent: bool,
+    /// If true, objects are marked as live when they are allocated. A concurrent plan sets this while
+    /// marking is in progress, as objects allocated during marking are not part of the snapshot.
+    mark_on_alloc: AtomicBool,
+    /// Work packet scheduler
+    scheduler: Arc<GCWorkScheduler<VM>>,
+}
+
+unsafe impl<VM: VMBinding> Sync for MarkSweepSpace<VM> {}
+
+impl<VM: VMBinding> SFT for MarkSweepSpace<VM> {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+
+    fn is_live(&self, object: ObjectReference) -> bool {
+        Self::is_marked(object)
+    }
+
+    fn is_movable(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+
+    fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
+        crate::util::alloc_bit::set_alloc_bit(object);
+        if self.concurrent && self.mark_on_alloc.load(Ordering::Acquire) {
+            Self::attempt_mark(object);
+        }
+        if self.common.needs_log_bit {
+            VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+        }
+    }
+
+    #[inline(always)]
+    fn sft_trace_object(
+        &self,
+        trace: SFTProcessEdgesMutRef,
+        object: ObjectReference,
+        _worker: GCWorkerMutRef,
+    ) -> ObjectReference {
+        let trace = trace.into_mut::<VM>();
+        self.trace_object(trace, object)
+    }
+}
+
+impl<VM: VMBinding> Space<VM> for MarkSweepSpace<VM> {
+    fn as_space(&self) -> &dyn Space<VM> {
+        self
+    }
+
+    fn as_sft(&self) -> &(dyn SFT + Sync + 'static) {
+        self
+    }
+
+    fn get_page_resource(&self) -> &dyn PageResource<VM> {
+        &self.pr
+    }
+
+    fn common(&self) -> &CommonSpace<VM> {
+        &self.common
+    }
+
+    fn init(&mut self, _vm_map: &'static VMMap) {
+        self.common().init(self.as_space());
+    }
+
+    fn release_multiple_pages(&mut self, _start: Address) {
+        panic!("marksweepspace only releases pages by blocks")
+    }
+}
+
+impl<VM: VMBinding> MarkSweepSpace<VM> {
+    /// Get side metadata specs. The space finds its objects with the alloc bit, so the alloc bit is
+    /// a local spec unless the plan already has it in the global specs.
+    fn side_metadata_specs(
+        global_side_metadata_specs: &[SideMetadataSpec],
+    ) -> Vec<SideMetadataSpec> {
+        let mut specs = metadata::extract_side_metadata(&[
+            MetadataSpec::OnSide(Block::STATE_TABLE),
+            MetadataSpec::OnSide(Block::SIZE_CLASS_TABLE),
+            MetadataSpec::OnSide(Block::FREE_LIST_TABLE),
+            MetadataSpec::OnSide(ChunkMap::ALLOC_TABLE),
+            *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+        ]);
+        if !global_side_metadata_specs.contains(&ALLOC_SIDE_METADATA_SPEC) {
+            specs.push(ALLOC_SIDE_METADATA_SPEC);
+        }
+        specs
+    }
+
+    pub fn new(
+        name: &'static str,
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        heap: &mut HeapMeta,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+        global_side_metadata_specs: Vec<SideMetadataSpec>,
+        constraints: &'static PlanConstraints,
+    ) -> Self {
+        let common = CommonSpace::new(
+            SpaceOptions {
+                name,
+                movable: false,
+                immortal: false,
+                zeroed: true,
+                vmrequest: VMRequest::discontiguous(),
+                side_metadata_specs: SideMetadataContext {
+                    local: Self::side_metadata_specs(&global_side_metadata_specs),
+                    global: global_side_metadata_specs,
+                },
+                needs_log_bit: constraints.needs_log_bit,
+            },
+            vm_map,
+            mmapper,
+            heap,
+        );
+        MarkSweepSpace {
+            pr: if common.vmrequest.is_discontiguous() {
+                FreeListPageResource::new_discontiguous(0, vm_map)
+            } else {
+                FreeListPageResource::new_contiguous(common.start, common.extent, 0, vm_map)
+            },
+            common,
+            chunk_map: ChunkMap::new(),
+            available_blocks: (0..NUM_SIZE_CLASSES)
+                .map(|_| BlockList::default())
+                .collect(),
+            concurrent: constraints.needs_concurrent_workers,
+            mark_on_alloc: AtomicBool::new(false),
+            scheduler,
+        }
+    }
+
+    /// Set whether newly allocated objects should be marked as live.
+    pub fn set_mark_on_alloc(&self, mark_on_alloc: bool) {
+        debug_assert!(self.concurrent);
+        self.mark_on_alloc.store(mark_on_alloc, Ordering::Release);
+    }
+
+    pub fn prepare(&mut self) {
+        // All the blocks are swept in this GC, and the blocks with free cells will be added back.
+        for list in &self.available_blocks {
+            list.reset();
+        }
+    }
+
+    pub fn release(&mut self) {
+        // # Safety: MarkSweepSpace reference is always valid within this collection cycle.
+        let space = unsafe { &*(self as *const Self) };
+        let work_packets = self
+            .chunk_map
+            .generate_tasks(|chunk| Box::new(SweepChunk { space, chunk }));
+        self.scheduler.work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
+    }
+
+    /// Sweep a chunk, and release the blocks that have no live objects.
+    fn sweep_chunk(&self, chunk: Chunk) {
+        let mut allocated_blocks = 0;
+        let blocks =
+            RegionIterator::<Block>::new(Block::from(chunk.start()), Block::from(chunk.end()));
+        for block in blocks.filter(|block| block.is_allocated()) {
+            let free_cells = block.sweep::<VM>();
+            if free_cells == Block::BYTES / block.cell_size() {
+                self.release_block(block);
+            } else {
+                allocated_blocks += 1;
+                if free_cells != 0 {
+                    self.available_blocks[block.size_class()].push(block);
+                }
+            }
+        }
+        // Set this chunk as free if there is not live blocks.
+        if allocated_blocks == 0 {
+            self.chunk_map.set(chunk, ChunkState::Free)
+        }
+    }
+
+    /// Release a block.
+    pub fn release_block(&self, block: Block) {
+        block.deinit();
+        self.pr.release_pages(block.start());
+    }
+
+    /// Get a block with free cells of the size class for an allocator. Blocks swept in the last GC
+    /// are used first, then a clean block is acquired from the page resource.
+    pub fn acquire_block(&self, tls: VMThread, size_class: usize) -> Option<Block> {
+        if let Some(block) = self.available_blocks[size_class].pop() {
+            return Some(block);
+        }
+        self.get_clean_block(tls, size_class)
+    }
+
+    /// Allocate a clean block. This may trigger a GC, in which case `None` is returned.
+    pub fn get_clean_block(&self, tls: VMThread, size_class: usize) -> Option<Block> {
+        let block_address = self.acquire(tls, Block::PAGES);
+        if block_address.is_zero() {
+            return None;
+        }
+        let block = Block::from(block_address);
+        block.init(size_class);
+        self.chunk_map.set(block.chunk(), ChunkState::Allocated);
+        Some(block)
+    }
+
+    #[inline]
+    pub fn trace_object<T: TransitiveClosure>(
+        &self,
+        trace: &mut T,
+        object: ObjectReference,
+    ) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        debug_assert!(
+            crate::util::alloc_bit::is_alloced(object),
+            "{:x}: alloc bit not set",
+            object
+        );
+        if Self::attempt_mark(object) {
+            trace.process_node(object);
+        }
+        object
+    }
+
+    /// Atomically mark an object. Return false if the object is already marked.
+    #[inline]
+    fn attempt_mark(object: ObjectReference) -> bool {
+        loop {
+            let old_value = load_metadata::<VM>(
+                &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                object,
+                None,
+                Some(Ordering::SeqCst),
+            );
+            if old_value == 1 {
+                return false;
+            }
+            if compare_exchange_metadata::<VM>(
+                &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                object,
+                old_value,
+                1,
+                None,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                return true;
+            }
+        }
+    }
+
+    #[inline]
+    fn is_marked(object: ObjectReference) -> bool {
+        load_metadata::<VM>(
+            &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+            object,
+            None,
+            Some(Ordering::SeqCst),
+        ) == 1
+    }
+}
+
+/// Chunk sweeping work packet.
+struct SweepChunk<VM: VMBinding> {
+    space: &'static MarkSweepSpace<VM>,
+    chunk: Chunk,
+}
+
+impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        if self.space.chunk_map.get(self.chunk) == ChunkState::Allocated {
+            self.space.sweep_chunk(self.chunk);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+///! A marksweep space that allocates from segregated free lists in its own blocks.
+pub mod block;
+mod global;
+
+pub use global::*;
@@ -1,3 +1,4 @@
+use crate::util::alloc::PostAllocAction;
 use crate::util::conversions::*;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
 use crate::util::Address;
@@ -23,6 +24,8 @@ use crate::util::heap::layout::vm_layout_constants::BYTES_IN_CHUNK;
 use crate::util::heap::layout::vm_layout_constants::MAX_CHUNKS;
 use crate::util::heap::layout::Mmapper as IMmapper;
 use crate::util::heap::space_descriptor::SpaceDescriptor;
+use crate::util::heap::uncommit::UNCOMMITTER;
+use crate::util::heap::zeroing::ZEROER;
 use crate::util::heap::HeapMeta;
 use crate::util::memory;
 
@@ -72,6 +75,23 @@ pub trait SFT {
     /// Is the object movable, determined by the policy? E.g. the policy is non-moving,
     /// or the object is pinned.
     fn is_movable(&self) -> bool;
+    /// Pin the object, so the policy will not move it until it is unpinned. Return true if the object
+    /// is pinned (or the policy never moves objects), and false if the policy cannot pin objects.
+    #[inline(always)]
+    fn pin_object(&self, _object: ObjectReference) -> bool {
+        !self.is_movable()
+    }
+    /// Unpin the object. Return true if the object was pinned by `pin_object()`.
+    #[inline(always)]
+    fn unpin_object(&self, _object: ObjectReference) -> bool {
+        false
+    }
+    /// Is the object pinned, i.e. guaranteed not to move? This is true for any object in a
+    /// policy that never moves objects.
+    #[inline(always)]
+    fn is_object_pinned(&self, _object: ObjectReference) -> bool {
+        !self.is_movable()
+    }
     /// Is the object sane? A policy should return false if there is any abnormality about
     /// object - the sanity checker will fail if an object is not sane.
     #[cfg(feature = "sanity")]
@@ -103,6 +123,12 @@ pub trait SFT {
     }
     /// Initialize object metadata (in the header, or in the side metadata).
     fn initialize_object_metadata(&self, object: ObjectReference, alloc: bool);
+    /// What compiled code needs to do for a newly allocated object, instead of calling
+    /// `initialize_object_metadata()`. Policies whose metadata changes between GCs (e.g. a mark state)
+    /// keep the default, which asks compiled code to call `post_alloc()`.
+    fn post_alloc_action(&self) -> PostAllocAction {
+        PostAllocAction::CallPostAlloc
+    }
     /// Trace objects through SFT. This along with [`SFTProcessEdges`](mmtk/scheduler/gc_work/SFTProcessEdges)
     /// provides an easy way for most plans to trace objects without the need to implement any plan-specific
     /// code. However, tracing objects for some policies are more complicated, and they do not provide an
@@ -157,6 +183,12 @@ impl SFT for EmptySpaceSFT {
          */
         false
     }
+    fn pin_object(&self, _object: ObjectReference) -> bool {
+        false
+    }
+    fn is_object_pinned(&self, _object: ObjectReference) -> bool {
+        false
+    }
     #[inline(always)]
     fn is_in_space(&self, _object: ObjectReference) -> bool {
         false
@@ -426,6 +458,8 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
                         res.new_chunk
                     );
                     let bytes = conversions::pages_to_bytes(res.pages);
+                    // The pages may have been released before. They must not be uncommitted from now on.
+                    UNCOMMITTER.reuse(res.start, bytes);
                     self.grow_space(res.start, bytes, res.new_chunk);
                     // Mmap the pages and the side metadata, and handle error. In case of any error,
                     // we will either call back to the VM for OOM, or simply panic.
@@ -442,10 +476,8 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
                         memory::handle_mmap_error::<VM>(mmap_error, tls);
                     }
 
-                    // TODO: Concurrent zeroing
-                    if self.common().zeroed {
-                        memory::zero(res.start, bytes);
-                    }
+                    // The pages may be being zeroed in the background. Stop that, and zero them if we need to.
+                    ZEROER.reuse(res.start, bytes, self.common().zeroed);
 
                     // Some assertions
                     {
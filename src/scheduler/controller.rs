@@ -86,7 +86,10 @@ impl<VM: VMBinding> GCController<VM> {
                 }
             }
             let _guard = self.scheduler.worker_monitor.0.lock().unwrap();
-            if self.scheduler.all_workers_parked() && self.scheduler.all_buckets_empty() {
+            if self.scheduler.all_workers_parked()
+                && self.scheduler.all_buckets_empty()
+                && self.scheduler.coordinator_local_deque_is_empty()
+            {
                 break;
             }
         }
@@ -15,6 +15,12 @@ pub struct ScheduleCollection;
 
 impl<VM: VMBinding> GCWork<VM> for ScheduleCollection {
     fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let base = mmtk.plan.base();
+        base.heap_sizing.on_gc_start(mmtk.plan.get_reserved_pages());
+        base.gc_overhead_limit
+            .on_gc_start(mmtk.plan.get_reserved_pages());
+        base.gc_trigger.on_gc_start(&*mmtk.plan);
+        crate::util::heap::zeroing::ZEROER.on_gc_start();
         mmtk.plan.schedule_collection(worker.scheduler());
     }
 }
@@ -181,6 +187,7 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for StopMutators<E> {
 
         trace!("stop_all_mutators start");
         mmtk.plan.base().prepare_for_stack_scanning();
+        mmtk.scheduler.timeline.mutators_stopping();
         <E::VM as VMBinding>::VMCollection::stop_all_mutators::<E>(worker.tls);
         trace!("stop_all_mutators end");
         mmtk.scheduler.notify_mutators_paused(mmtk);
@@ -231,12 +238,39 @@ impl<VM: VMBinding> GCWork<VM> for EndOfGC {
                     "VM only allows coordinator to resume mutators, but the current worker is not the coordinator.");
         }
 
-        mmtk.plan.base().set_gc_status(GcStatus::NotInGC);
+        let base = mmtk.plan.base();
+        base.gc_overhead_limit.on_gc_end(
+            mmtk.plan.get_reserved_pages(),
+            mmtk.plan.get_total_pages(),
+            base.is_user_triggered_collection(),
+        );
+        // Resize the heap for the mutators before they resume.
+        base.heap_sizing.on_gc_end(
+            &base.heap,
+            mmtk.plan.get_reserved_pages(),
+            mmtk.plan.last_collection_full_heap(),
+        );
+        base.gc_trigger.on_gc_end(&*mmtk.plan);
+        base.pause_goal.on_gc_end(
+            base.stats.current_pause(),
+            mmtk.plan.last_collection_full_heap(),
+        );
+        crate::util::heap::uncommit::UNCOMMITTER.uncommit_free_pages();
+        // The memory usage of the cgroup is checked after the free pages are uncommitted.
+        base.cgroup_memory_limit
+            .on_gc_end(mmtk.plan.get_reserved_pages());
+        crate::util::heap::zeroing::ZEROER.on_gc_end();
+
+        base.set_gc_status(GcStatus::NotInGC);
 
         // Reset the triggering information.
         mmtk.plan.base().reset_collection_trigger();
 
         <VM as VMBinding>::VMCollection::resume_mutators(worker.tls);
+        mmtk.scheduler.timeline.mutators_resumed();
+
+        // Concurrent work packets (if any) can be executed now as the mutators are running.
+        mmtk.scheduler.resume_concurrent_work();
     }
 }
 
@@ -555,8 +589,12 @@ impl<Edges: ProcessEdgesWork> ScanObjects<Edges> {
 }
 
 impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanObjects<E> {
-    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, _mmtk: &'static MMTK<E::VM>) {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
         trace!("ScanObjects");
+        mmtk.plan
+            .base()
+            .heap_sizing
+            .on_objects_traced::<E::VM>(&self.buffer);
         {
             let tls = worker.tls;
             let mut closure = ObjectsClosure::<E>::new(worker);
@@ -601,3 +639,143 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessModBuf<E> {
         }
     }
 }
+
+/// Process the slots remembered by a [`crate::plan::barriers::FieldRememberingBarrier`], by a
+/// [`crate::plan::barriers::ObjectRememberingBarrier`] in the coalescing mode, or by a
+/// [`crate::plan::barriers::CardMarkingBarrier`] for the slots written without their objects.
+/// In a nursery GC, the remembered slots are processed like roots, as they may point to nursery objects.
+/// In a full heap GC, the slots are only unlogged.
+pub struct ProcessSlotBuf<E: ProcessEdgesWork> {
+    slots: Vec<Address>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: ProcessEdgesWork> ProcessSlotBuf<E> {
+    pub fn new(slots: Vec<Address>) -> Self {
+        Self {
+            slots,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessSlotBuf<E> {
+    #[inline(always)]
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        for slot in &self.slots {
+            crate::plan::barriers::unlog_slot(*slot);
+        }
+        if mmtk.plan.is_current_gc_nursery() && !self.slots.is_empty() {
+            let slots = mem::take(&mut self.slots);
+            // A remembered slot may be in a dead object. So the slots are not real roots, and they are not cached
+            // as roots for sanity GC. Tracing from a dead object only keeps its referents alive until the next GC.
+            GCWork::do_work(&mut E::new(slots, false, mmtk), worker, mmtk)
+        }
+    }
+}
+
+/// Process the objects logged by a [`crate::plan::barriers::CardMarkingBarrier`].
+/// In a nursery GC, only the fields of the logged objects in dirty cards are processed.
+/// In a full heap GC, the objects are only unlogged.
+pub struct ProcessDirtyCards<E: ProcessEdgesWork> {
+    modbuf: Vec<ObjectReference>,
+    phantom: PhantomData<E>,
+    meta: MetadataSpec,
+}
+
+impl<E: ProcessEdgesWork> ProcessDirtyCards<E> {
+    pub fn new(modbuf: Vec<ObjectReference>, meta: MetadataSpec) -> Self {
+        Self {
+            modbuf,
+            meta,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessDirtyCards<E> {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        use crate::plan::barriers::{card_of, is_card_dirty, FieldSlotsVisitor, BYTES_IN_CARD};
+        for obj in &self.modbuf {
+            store_metadata::<E::VM>(&self.meta, *obj, 1, None, Some(Ordering::SeqCst));
+        }
+        if !mmtk.plan.is_current_gc_nursery() {
+            return;
+        }
+        let mut edges = FieldSlotsVisitor { slots: vec![] };
+        for obj in &self.modbuf {
+            let start = <E::VM as VMBinding>::VMObjectModel::object_start_ref(*obj);
+            let end = start + <E::VM as VMBinding>::VMObjectModel::get_current_size(*obj);
+            // Scan each run of consecutive dirty cards in the object.
+            let mut card = card_of(start);
+            while card < end {
+                if !is_card_dirty(card) {
+                    card += BYTES_IN_CARD;
+                    continue;
+                }
+                let run_start = card;
+                while card < end && is_card_dirty(card) {
+                    card += BYTES_IN_CARD;
+                }
+                <E::VM as VMBinding>::VMScanning::scan_object_range(
+                    worker.tls,
+                    *obj,
+                    run_start.max(start),
+                    card.min(end),
+                    &mut edges,
+                );
+            }
+        }
+        for chunk in edges.slots.chunks(E::CAPACITY) {
+            GCWork::do_work(&mut E::new(chunk.to_vec(), false, mmtk), worker, mmtk)
+        }
+    }
+}
+
+/// Clear the cards dirtied by a [`crate::plan::barriers::CardMarkingBarrier`]. This is done at the end of a GC,
+/// when all the dirty cards have been scanned.
+pub struct ClearDirtyCards {
+    cards: Vec<Address>,
+}
+
+impl ClearDirtyCards {
+    pub fn new(cards: Vec<Address>) -> Self {
+        Self { cards }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ClearDirtyCards {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        for card in &self.cards {
+            crate::plan::barriers::clear_card(*card);
+        }
+    }
+}
+
+/// Trace the objects recorded by a snapshot-at-the-beginning barrier.
+/// Each recorded object is treated as a gray object: it is traced (marked), and its fields will be scanned.
+pub struct ProcessSATBBuffer<E: ProcessEdgesWork> {
+    buffer: Vec<ObjectReference>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: ProcessEdgesWork> ProcessSATBBuffer<E> {
+    pub fn new(buffer: Vec<ObjectReference>) -> Self {
+        Self {
+            buffer,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessSATBBuffer<E> {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        trace!("ProcessSATBBuffer");
+        let mut process_edges = E::new(vec![], false, mmtk);
+        process_edges.set_worker(worker);
+        for object in &self.buffer {
+            process_edges.trace_object(*object);
+        }
+        process_edges.flush();
+    }
+}
@@ -6,6 +6,8 @@ pub(crate) use scheduler::CoordinatorMessage;
 pub(crate) use scheduler::GCWorkScheduler;
 
 mod stat;
+mod timeline;
+pub(crate) use timeline::Timeline;
 pub(self) mod work_counter;
 
 mod work;
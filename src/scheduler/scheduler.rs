@@ -1,9 +1,10 @@
 use super::stat::SchedulerStat;
 use super::work_bucket::WorkBucketStage::*;
 use super::work_bucket::*;
-use super::worker::{GCWorker, GCWorkerShared};
+use super::worker::{GCWorker, GCWorkerShared, LocalWork};
 use super::*;
 use crate::mmtk::MMTK;
+use crate::plan::Plan;
 use crate::util::opaque_pointer::*;
 use crate::vm::Collection;
 use crate::vm::{GCThreadContext, VMBinding};
@@ -41,6 +42,8 @@ pub struct GCWorkScheduler<VM: VMBinding> {
     /// the `Closure` bucket multiple times to iteratively discover and process
     /// more ephemeron objects.
     closure_end: Mutex<Option<Box<dyn Send + Fn() -> bool>>>,
+    /// The timeline of GC events, which is only recorded if the option `gc_timeline_file` is set.
+    pub(crate) timeline: Timeline,
 }
 
 // FIXME: GCWorkScheduler should be naturally Sync, but we cannot remove this `impl` yet.
@@ -55,12 +58,14 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         // Create work buckets for workers.
         let mut work_buckets = enum_map! {
             WorkBucketStage::Unconstrained => WorkBucket::new(true, worker_monitor.clone()),
+            WorkBucketStage::Concurrent => WorkBucket::new(true, worker_monitor.clone()),
             WorkBucketStage::Prepare => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::Closure => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::SoftRefClosure => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::WeakRefClosure => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::FinalRefClosure => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::PhantomRefClosure => WorkBucket::new(false, worker_monitor.clone()),
+            WorkBucketStage::RCDecrements => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::CalculateForwarding => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::SecondRoots => WorkBucket::new(false, worker_monitor.clone()),
             WorkBucketStage::RefForwarding => WorkBucket::new(false, worker_monitor.clone()),
@@ -80,7 +85,9 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
                 let cur_stages = open_stages.clone();
                 work_buckets[s].set_open_condition(move |scheduler: &GCWorkScheduler<VM>| {
                     let should_open = scheduler.are_buckets_drained(&cur_stages)
-                        && scheduler.all_workers_parked();
+                        && !scheduler.has_pending_concurrent_work()
+                        && scheduler.all_workers_parked()
+                        && scheduler.coordinator_local_deque_is_empty();
                     // Additional check before the `RefClosure` bucket opens.
                     if should_open && s == crate::scheduler::work_bucket::LAST_CLOSURE_BUCKET {
                         if let Some(closure_end) = scheduler.closure_end.lock().unwrap().as_ref() {
@@ -100,6 +107,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             open_next(WeakRefClosure);
             open_next(FinalRefClosure);
             open_next(PhantomRefClosure);
+            open_next(RCDecrements);
             open_next(CalculateForwarding);
             open_next(SecondRoots);
             open_next(RefForwarding);
@@ -128,6 +136,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             coordinator_worker_shared,
             worker_monitor,
             closure_end: Mutex::new(None),
+            timeline: Timeline::new(num_workers),
         })
     }
 
@@ -140,8 +149,18 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         self.workers_shared.iter().all(|w| w.is_parked())
     }
 
+    /// Return true if the local deque of the controller is empty. The controller does not execute the
+    /// packets in its local deque, so the packets may still be there when all the workers are parked.
+    pub fn coordinator_local_deque_is_empty(&self) -> bool {
+        self.coordinator_worker_shared.is_local_deque_empty()
+    }
+
     /// Create GC threads, including the controller thread and all workers.
     pub fn spawn_gc_threads(self: &Arc<Self>, mmtk: &'static MMTK<VM>, tls: VMThread) {
+        if !mmtk.options.gc_timeline_file.is_empty() {
+            self.timeline.enable();
+        }
+
         // Create the communication channel.
         let (sender, receiver) = channel::<CoordinatorMessage<VM>>();
 
@@ -182,7 +201,6 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         &self,
         plan: &'static C::PlanType,
     ) {
-        use crate::plan::Plan;
         use crate::scheduler::gc_work::*;
         // Stop & scan mutators (mutator scanning can happen before STW)
         self.work_buckets[WorkBucketStage::Unconstrained]
@@ -257,8 +275,39 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         *self.closure_end.lock().unwrap() = Some(f);
     }
 
+    /// Return true if the `Concurrent` bucket is active and still has work packets in it.
+    /// During a pause, the later stages cannot be opened before the concurrent work is finished.
+    fn has_pending_concurrent_work(&self) -> bool {
+        let bucket = &self.work_buckets[WorkBucketStage::Concurrent];
+        bucket.is_activated() && !bucket.is_empty()
+    }
+
     pub fn all_buckets_empty(&self) -> bool {
-        self.work_buckets.values().all(|bucket| bucket.is_empty())
+        self.work_buckets.iter().all(|(stage, bucket)| {
+            // Concurrent work that is held back will be done after the mutators are resumed.
+            bucket.is_empty() || (stage == WorkBucketStage::Concurrent && !bucket.is_activated())
+        })
+    }
+
+    /// Hold back the work packets in the `Concurrent` bucket, so they will not be executed
+    /// in the current pause. This is used by a pause that starts a concurrent phase.
+    pub fn pause_concurrent_work(&self) {
+        self.deactivate_bucket(
+            WorkBucketStage::Concurrent,
+            &self.work_buckets[WorkBucketStage::Concurrent],
+        );
+    }
+
+    /// Allow workers to execute the work packets in the `Concurrent` bucket. This is called
+    /// when mutators are resumed at the end of a pause.
+    pub fn resume_concurrent_work(&self) {
+        let bucket = &self.work_buckets[WorkBucketStage::Concurrent];
+        if !bucket.is_activated() {
+            bucket.activate();
+            self.timeline.bucket_opened(WorkBucketStage::Concurrent);
+        }
+        let _guard = self.worker_monitor.0.lock().unwrap();
+        self.worker_monitor.1.notify_all();
     }
 
     /// Open buckets if their conditions are met
@@ -268,7 +317,10 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             if id == WorkBucketStage::Unconstrained {
                 continue;
             }
-            buckets_updated |= bucket.update(self);
+            if bucket.update(self) {
+                self.timeline.bucket_opened(id);
+                buckets_updated = true;
+            }
         }
         if buckets_updated {
             // Notify the workers for new work
@@ -279,21 +331,31 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
 
     pub fn deactivate_all(&self) {
         for (stage, bucket) in self.work_buckets.iter() {
-            if stage == WorkBucketStage::Unconstrained {
+            if stage == WorkBucketStage::Unconstrained || stage == WorkBucketStage::Concurrent {
                 continue;
             }
 
-            bucket.deactivate();
+            self.deactivate_bucket(stage, bucket);
+        }
+    }
+
+    fn deactivate_bucket(&self, stage: WorkBucketStage, bucket: &WorkBucket<VM>) {
+        if bucket.is_activated() {
+            self.timeline.bucket_closed(stage);
         }
+        bucket.deactivate();
     }
 
     pub fn reset_state(&self) {
         for (stage, bucket) in self.work_buckets.iter() {
-            if stage == WorkBucketStage::Unconstrained || stage == WorkBucketStage::Prepare {
+            if stage == WorkBucketStage::Unconstrained
+                || stage == WorkBucketStage::Concurrent
+                || stage == WorkBucketStage::Prepare
+            {
                 continue;
             }
 
-            bucket.deactivate();
+            self.deactivate_bucket(stage, bucket);
         }
     }
 
@@ -314,6 +376,46 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             .unwrap();
     }
 
+    /// Wake up a parked worker, if there is any, so it can steal work from other workers.
+    /// The monitor lock is acquired before checking the workers, so that a worker that is about to park
+    /// either sees the new work, or is parked and gets notified.
+    #[inline]
+    pub fn notify_parked_worker(&self) {
+        let _guard = self.worker_monitor.0.lock().unwrap();
+        if self.workers_shared.iter().any(|w| w.is_parked()) {
+            self.worker_monitor.1.notify_one();
+        }
+    }
+
+    /// Return the work packet from a local deque if its bucket is still active. Otherwise, the bucket has
+    /// been deactivated since the packet was added, e.g. the `Concurrent` bucket is held back in a pause,
+    /// so move the packet to the bucket and return `None`.
+    pub(crate) fn accept_local_work(
+        &self,
+        (stage, work): LocalWork<VM>,
+    ) -> Option<Box<dyn GCWork<VM>>> {
+        let bucket = &self.work_buckets[stage];
+        if bucket.is_activated() {
+            Some(work)
+        } else {
+            // The caller may hold the monitor lock, so do not notify workers.
+            bucket.add_without_notify(work);
+            None
+        }
+    }
+
+    /// Steal a work packet from the local deques of other workers and the controller. Start from the worker
+    /// next to the current worker, so that idle workers do not all steal from the same worker.
+    #[inline]
+    fn steal_work(&self, worker: &GCWorker<VM>) -> Option<Box<dyn GCWork<VM>>> {
+        let n = self.num_workers();
+        (1..=n)
+            .map(|i| &self.workers_shared[(worker.ordinal + i) % n])
+            .chain(std::iter::once(&self.coordinator_worker_shared))
+            .filter(|w| !Arc::ptr_eq(w, &worker.shared))
+            .find_map(|w| w.steal().and_then(|work| self.accept_local_work(work)))
+    }
+
     #[inline]
     fn pop_scheduable_work(&self, worker: &GCWorker<VM>) -> Option<(Box<dyn GCWork<VM>>, bool)> {
         if let Some(work) = worker.shared.local_work_bucket.poll() {
@@ -324,7 +426,8 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
                 return Some((work, work_bucket.is_empty()));
             }
         }
-        None
+        // The stolen work is not from a bucket, so it never drains a bucket.
+        self.steal_work(worker).map(|work| (work, false))
     }
 
     /// Get a scheduable work. Called by workers
@@ -362,10 +465,17 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             // Park this worker
             worker.shared.parked.store(true, Ordering::SeqCst);
             if self.all_workers_parked() {
-                worker
-                    .sender
-                    .send(CoordinatorMessage::AllWorkerParked)
-                    .unwrap();
+                if worker.mmtk.plan.base().gc_in_progress() {
+                    worker
+                        .sender
+                        .send(CoordinatorMessage::AllWorkerParked)
+                        .unwrap();
+                } else if self.work_buckets[WorkBucketStage::Concurrent].is_drained() {
+                    // The workers were doing concurrent work while the mutators are running, and the controller
+                    // is waiting for a GC request rather than coordinating the workers. All the concurrent work
+                    // is done, so the plan may need a pause to finish its concurrent phase.
+                    worker.mmtk.plan.concurrent_work_drained();
+                }
             }
             // Wait
             guard = self.worker_monitor.1.wait(guard).unwrap();
@@ -398,6 +508,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         mmtk.plan.base().gc_requester.clear_request();
         debug_assert!(!self.work_buckets[WorkBucketStage::Prepare].is_activated());
         self.work_buckets[WorkBucketStage::Prepare].activate();
+        self.timeline.bucket_opened(WorkBucketStage::Prepare);
         let _guard = self.worker_monitor.0.lock().unwrap();
         self.worker_monitor.1.notify_all();
     }
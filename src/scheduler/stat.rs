@@ -33,7 +33,7 @@ pub struct SchedulerStat {
 impl SchedulerStat {
     /// Extract the work-packet name from the full type name.
     /// i.e. simplifies `crate::scheduler::gc_work::SomeWorkPacket<Semispace>` to `SomeWorkPacket`.
-    fn work_name(&self, name: &str) -> String {
+    pub(super) fn work_name(name: &str) -> String {
         let end_index = name.find('<').unwrap_or(name.len());
         let name = name[..end_index].to_owned();
         match name.rfind(':') {
@@ -51,7 +51,7 @@ impl SchedulerStat {
             total_count += c;
             let n = self.work_id_name_map[t];
             stat.insert(
-                format!("work.{}.count", self.work_name(n)),
+                format!("work.{}.count", Self::work_name(n)),
                 format!("{}", c),
             );
         }
@@ -74,15 +74,15 @@ impl SchedulerStat {
                 duration_overall.merge_inplace(&fold);
                 let name = v.first().unwrap().name();
                 stat.insert(
-                    format!("work.{}.{}.total", self.work_name(n), name),
+                    format!("work.{}.{}.total", Self::work_name(n), name),
                     format!("{:.2}", fold.total),
                 );
                 stat.insert(
-                    format!("work.{}.{}.min", self.work_name(n), name),
+                    format!("work.{}.{}.min", Self::work_name(n), name),
                     format!("{:.2}", fold.min),
                 );
                 stat.insert(
-                    format!("work.{}.{}.max", self.work_name(n), name),
+                    format!("work.{}.{}.max", Self::work_name(n), name),
                     format!("{:.2}", fold.max),
                 );
             }
@@ -0,0 +1,359 @@
+//! A timeline of GC events, exported in the Chrome Trace Event format.
+//!
+//! When the option `gc_timeline_file` is set, MMTk records when each work packet begins and ends
+//! on each GC thread, when work buckets are opened and closed, and when mutators are stopped and
+//! resumed. The timeline is written to the file at [`crate::memory_manager::harness_end`], or
+//! whenever [`crate::memory_manager::dump_gc_timeline`] is called. The file can be loaded into
+//! Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing`.
+
+use super::stat::SchedulerStat;
+use super::work_bucket::WorkBucketStage;
+use super::worker::GCWorker;
+use crate::vm::VMBinding;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// The track (shown as a thread in the trace viewers) that an event is displayed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Track {
+    Mutators,
+    Buckets,
+    Coordinator,
+    Worker(usize),
+}
+
+impl Track {
+    fn of<VM: VMBinding>(worker: &GCWorker<VM>) -> Self {
+        if worker.is_coordinator() {
+            Track::Coordinator
+        } else {
+            Track::Worker(worker.ordinal)
+        }
+    }
+
+    fn tid(self) -> usize {
+        match self {
+            Track::Mutators => 0,
+            Track::Buckets => 1,
+            Track::Coordinator => 2,
+            Track::Worker(ordinal) => 3 + ordinal,
+        }
+    }
+
+    /// The index of the event buffer of a GC thread. `None` for the tracks that are not GC threads.
+    fn buffer_index(self) -> Option<usize> {
+        match self {
+            Track::Mutators | Track::Buckets => None,
+            Track::Coordinator => Some(0),
+            Track::Worker(ordinal) => Some(1 + ordinal),
+        }
+    }
+
+    fn name(self) -> String {
+        match self {
+            Track::Mutators => "Mutators".to_owned(),
+            Track::Buckets => "Work buckets".to_owned(),
+            Track::Coordinator => "GC controller".to_owned(),
+            Track::Worker(ordinal) => format!("GC worker {}", ordinal),
+        }
+    }
+}
+
+enum EventKind {
+    WorkBegin(&'static str),
+    WorkEnd,
+    BucketOpened(WorkBucketStage),
+    BucketClosed(WorkBucketStage),
+    MutatorsStopping,
+    MutatorsResumed,
+}
+
+struct Event {
+    time: Instant,
+    track: Track,
+    kind: EventKind,
+}
+
+/// Records GC events with their timestamps. Recording is a no-op unless the timeline is enabled.
+/// Note that the events are kept in memory until the timeline is reset, so a timeline should
+/// only be enabled for diagnosis.
+///
+/// Each GC thread records the beginning and the end of its work packets in its own buffer, so the
+/// GC threads do not contend for a lock at each work packet. The other events are much less frequent,
+/// and they share one buffer. The buffers are merged when the timeline is written.
+pub struct Timeline {
+    enabled: AtomicBool,
+    start: Mutex<Instant>,
+    /// The buffers of the GC controller and the GC workers, indexed by `Track::buffer_index()`.
+    thread_events: Vec<Mutex<Vec<Event>>>,
+    /// The buffer of the events that are not recorded by a GC thread.
+    shared_events: Mutex<Vec<Event>>,
+}
+
+impl Timeline {
+    pub fn new(num_workers: usize) -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            start: Mutex::new(Instant::now()),
+            thread_events: (0..=num_workers).map(|_| Mutex::new(vec![])).collect(),
+            shared_events: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn buffer(&self, track: Track) -> &Mutex<Vec<Event>> {
+        match track.buffer_index() {
+            Some(index) => &self.thread_events[index],
+            None => &self.shared_events,
+        }
+    }
+
+    /// Discard all the recorded events, and start the timeline again from zero.
+    pub fn reset(&self) {
+        *self.start.lock().unwrap() = Instant::now();
+        for buffer in self
+            .thread_events
+            .iter()
+            .chain(std::iter::once(&self.shared_events))
+        {
+            buffer.lock().unwrap().clear();
+        }
+    }
+
+    fn record(&self, track: Track, kind: EventKind) {
+        if !self.is_enabled() {
+            return;
+        }
+        // Only the GC thread of the track locks its buffer, apart from `reset()` and `write()`,
+        // so locking the buffer of a GC thread is uncontended.
+        let time = Instant::now();
+        self.buffer(track)
+            .lock()
+            .unwrap()
+            .push(Event { time, track, kind });
+    }
+
+    #[inline]
+    pub fn work_begin<VM: VMBinding>(&self, worker: &GCWorker<VM>, name: &'static str) {
+        if self.is_enabled() {
+            self.record(Track::of(worker), EventKind::WorkBegin(name));
+        }
+    }
+
+    #[inline]
+    pub fn work_end<VM: VMBinding>(&self, worker: &GCWorker<VM>) {
+        if self.is_enabled() {
+            self.record(Track::of(worker), EventKind::WorkEnd);
+        }
+    }
+
+    pub fn bucket_opened(&self, stage: WorkBucketStage) {
+        self.record(Track::Buckets, EventKind::BucketOpened(stage));
+    }
+
+    pub fn bucket_closed(&self, stage: WorkBucketStage) {
+        self.record(Track::Buckets, EventKind::BucketClosed(stage));
+    }
+
+    /// The GC is about to stop the mutators.
+    pub fn mutators_stopping(&self) {
+        self.record(Track::Mutators, EventKind::MutatorsStopping);
+    }
+
+    /// The GC has resumed the mutators.
+    pub fn mutators_resumed(&self) {
+        self.record(Track::Mutators, EventKind::MutatorsResumed);
+    }
+
+    /// Write the recorded events to `path` as a Chrome Trace Event JSON file.
+    pub fn write_to_file(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let start = *self.start.lock().unwrap();
+        // Hold all the buffers while merging them, so no event is recorded in the middle.
+        let buffers: Vec<_> = self
+            .thread_events
+            .iter()
+            .chain(std::iter::once(&self.shared_events))
+            .map(|buffer| buffer.lock().unwrap())
+            .collect();
+        // The events of each buffer are in the order of time. A stable sort keeps the order of events
+        // with the same timestamp on the same track.
+        let mut events: Vec<&Event> = buffers.iter().flat_map(|buffer| buffer.iter()).collect();
+        events.sort_by_key(|e| e.time);
+        // Name the tracks that have events. Track names are metadata events in this format.
+        let tracks: BTreeSet<Track> = events.iter().map(|e| e.track).collect();
+
+        write!(out, "{{\"traceEvents\":[")?;
+        let mut first = true;
+        for track in tracks {
+            separate(out, &mut first)?;
+            write!(
+                out,
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":\"{}\"}}}}",
+                track.tid(),
+                track.name()
+            )?;
+        }
+        for event in events {
+            separate(out, &mut first)?;
+            let tid = event.track.tid();
+            // Microseconds since the timeline was started
+            let ts = event.time.saturating_duration_since(start).as_secs_f64() * 1e6;
+            match event.kind {
+                EventKind::WorkBegin(name) => write!(
+                    out,
+                    "{{\"name\":\"{}\",\"cat\":\"work\",\"ph\":\"B\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"args\":{{\"type\":\"{}\"}}}}",
+                    escape(&SchedulerStat::work_name(name)),
+                    tid,
+                    ts,
+                    escape(name)
+                )?,
+                EventKind::BucketOpened(stage) => write!(
+                    out,
+                    "{{\"name\":\"{:?} opened\",\"cat\":\"bucket\",\"ph\":\"i\",\"s\":\"t\",\"pid\":1,\"tid\":{},\"ts\":{:.3}}}",
+                    stage, tid, ts
+                )?,
+                EventKind::BucketClosed(stage) => write!(
+                    out,
+                    "{{\"name\":\"{:?} closed\",\"cat\":\"bucket\",\"ph\":\"i\",\"s\":\"t\",\"pid\":1,\"tid\":{},\"ts\":{:.3}}}",
+                    stage, tid, ts
+                )?,
+                EventKind::MutatorsStopping => write!(
+                    out,
+                    "{{\"name\":\"GC pause\",\"cat\":\"mutator\",\"ph\":\"B\",\"pid\":1,\"tid\":{},\"ts\":{:.3}}}",
+                    tid, ts
+                )?,
+                EventKind::WorkEnd | EventKind::MutatorsResumed => write!(
+                    out,
+                    "{{\"ph\":\"E\",\"pid\":1,\"tid\":{},\"ts\":{:.3}}}",
+                    tid, ts
+                )?,
+            }
+        }
+        writeln!(out, "\n],\"displayTimeUnit\":\"ms\"}}")
+    }
+}
+
+/// Escape a string so it can be put in a JSON string literal.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write the separator before an event, unless it is the first event.
+fn separate(out: &mut impl Write, first: &mut bool) -> io::Result<()> {
+    if *first {
+        *first = false;
+        writeln!(out)
+    } else {
+        writeln!(out, ",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_json() {
+        assert_eq!(escape("Foo<Bar>"), "Foo<Bar>");
+        assert_eq!(escape("a\"b\\c"), "a\\\"b\\\\c");
+        assert_eq!(escape("a\nb\tc\r"), "a\\nb\\tc\\r");
+        assert_eq!(escape("\u{1}"), "\\u0001");
+    }
+
+    fn write_to_string(timeline: &Timeline) -> String {
+        let mut out = vec![];
+        timeline.write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn disabled_timeline_records_nothing() {
+        let timeline = Timeline::new(1);
+        timeline.bucket_opened(WorkBucketStage::Prepare);
+        assert_eq!(
+            write_to_string(&timeline),
+            "{\"traceEvents\":[\n],\"displayTimeUnit\":\"ms\"}\n"
+        );
+    }
+
+    #[test]
+    fn merge_thread_buffers() {
+        let timeline = Timeline::new(2);
+        timeline.enable();
+        // Sleep between the events, so they have different timestamps.
+        let tick = || std::thread::sleep(std::time::Duration::from_millis(1));
+        timeline.mutators_stopping();
+        tick();
+        timeline.record(Track::Worker(1), EventKind::WorkBegin("a::Foo<\"b\">"));
+        tick();
+        timeline.record(Track::Coordinator, EventKind::WorkBegin("Bar"));
+        tick();
+        timeline.record(Track::Worker(1), EventKind::WorkEnd);
+        tick();
+        timeline.record(Track::Coordinator, EventKind::WorkEnd);
+        tick();
+        timeline.bucket_opened(WorkBucketStage::Closure);
+        tick();
+        timeline.mutators_resumed();
+
+        let json = write_to_string(&timeline);
+        assert!(json.starts_with("{\"traceEvents\":[\n"));
+        assert!(json.ends_with("\n],\"displayTimeUnit\":\"ms\"}\n"));
+        let lines: Vec<&str> = json.lines().collect();
+        // The opening line, 4 track names, 7 events, and the closing line.
+        assert_eq!(lines.len(), 1 + 4 + 7 + 1);
+        // The track names are sorted by track.
+        assert!(lines[1].contains("\"tid\":0,\"args\":{\"name\":\"Mutators\"}"));
+        assert!(lines[2].contains("\"tid\":1,\"args\":{\"name\":\"Work buckets\"}"));
+        assert!(lines[3].contains("\"tid\":2,\"args\":{\"name\":\"GC controller\"}"));
+        assert!(lines[4].contains("\"tid\":4,\"args\":{\"name\":\"GC worker 1\"}"));
+        // The events from different buffers are merged in the order of time.
+        assert!(lines[5].starts_with(
+            "{\"name\":\"GC pause\",\"cat\":\"mutator\",\"ph\":\"B\",\"pid\":1,\"tid\":0,"
+        ));
+        assert!(lines[6]
+            .starts_with("{\"name\":\"Foo\",\"cat\":\"work\",\"ph\":\"B\",\"pid\":1,\"tid\":4,"));
+        assert!(lines[6].ends_with(",\"args\":{\"type\":\"a::Foo<\\\"b\\\">\"}},"));
+        assert!(lines[7]
+            .starts_with("{\"name\":\"Bar\",\"cat\":\"work\",\"ph\":\"B\",\"pid\":1,\"tid\":2,"));
+        assert!(lines[8].starts_with("{\"ph\":\"E\",\"pid\":1,\"tid\":4,"));
+        assert!(lines[9].starts_with("{\"ph\":\"E\",\"pid\":1,\"tid\":2,"));
+        assert!(
+            lines[10].starts_with("{\"name\":\"Closure opened\",\"cat\":\"bucket\",\"ph\":\"i\",")
+        );
+        assert!(lines[11].starts_with("{\"ph\":\"E\",\"pid\":1,\"tid\":0,"));
+
+        timeline.reset();
+        assert_eq!(write_to_string(&timeline).lines().count(), 2);
+    }
+}
@@ -1,8 +1,9 @@
 use super::worker::*;
 use crate::mmtk::MMTK;
 use crate::vm::VMBinding;
+use std::any::type_name;
 #[cfg(feature = "work_packet_stats")]
-use std::any::{type_name, TypeId};
+use std::any::TypeId;
 
 /// A special kind of work that will execute on the coordinator (i.e. controller) thread
 ///
@@ -20,7 +21,7 @@ pub trait GCWork<VM: VMBinding>: 'static + Send {
     /// this should be called rather than `do_work()` so that MMTk can correctly collect
     /// statistics for the work packets.
     /// If the feature "work_packet_stats" is not enabled, this call simply forwards the call
-    /// to `do_work()`.
+    /// to `do_work()`. If the GC timeline is enabled, this also records when the work begins and ends.
     #[inline]
     fn do_work_with_stat(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
         debug!("{}", std::any::type_name::<Self>());
@@ -34,7 +35,11 @@ pub trait GCWork<VM: VMBinding>: 'static + Send {
         };
 
         // Do the actual work
+        mmtk.scheduler
+            .timeline
+            .work_begin(worker, type_name::<Self>());
         self.do_work(worker, mmtk);
+        mmtk.scheduler.timeline.work_end(worker);
 
         #[cfg(feature = "work_packet_stats")]
         // Finish collecting statistics
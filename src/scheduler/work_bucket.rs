@@ -109,6 +109,13 @@ impl<VM: VMBinding> WorkBucket<VM> {
             .push(PrioritizedWork::new(priority, work));
         self.notify_one_worker(); // FIXME: Performance
     }
+    /// Add a work packet to this bucket, with a default priority (1000), without notifying workers.
+    /// This is used when the caller holds the monitor lock.
+    pub(crate) fn add_without_notify(&self, work: Box<dyn GCWork<VM>>) {
+        self.queue
+            .write()
+            .push(PrioritizedWork::new(Self::DEFAULT_PRIORITY, work));
+    }
     /// Add a work packet to this bucket, with a default priority (1000)
     pub fn add<W: GCWork<VM>>(&self, work: W) {
         self.add_with_priority(Self::DEFAULT_PRIORITY, Box::new(work));
@@ -152,12 +159,19 @@ impl<VM: VMBinding> WorkBucket<VM> {
 #[derive(Debug, Enum, Copy, Clone, Eq, PartialEq)]
 pub enum WorkBucketStage {
     Unconstrained,
+    /// Work that runs while mutators are running, e.g. concurrent marking. Unlike other buckets,
+    /// this bucket does not follow the stop-the-world stages. It is only held back during the pauses
+    /// that must not run concurrent work (see `GCWorkScheduler::pause_concurrent_work`).
+    Concurrent,
     Prepare,
     Closure,
     SoftRefClosure,
     WeakRefClosure,
     FinalRefClosure,
     PhantomRefClosure,
+    /// Process the decrements of reference counts. Reference counting plans apply all the increments
+    /// in the closure stages, before any count is decremented in this stage.
+    RCDecrements,
     CalculateForwarding,
     SecondRoots,
     RefForwarding,
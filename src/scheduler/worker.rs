@@ -6,12 +6,11 @@ use crate::util::copy::GCWorkerCopyContext;
 use crate::util::opaque_pointer::*;
 use crate::vm::VMBinding;
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use crossbeam_deque::{Steal, Stealer, Worker};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Condvar, Mutex};
 
-const LOCALLY_CACHED_WORKS: usize = 1;
-
 /// The part shared between a GCWorker and the scheduler.
 /// This structure is used for communication, e.g. adding new work packets.
 pub struct GCWorkerShared<VM: VMBinding> {
@@ -21,14 +20,27 @@ pub struct GCWorkerShared<VM: VMBinding> {
     stat: AtomicRefCell<WorkerLocalStat<VM>>,
     /// Incoming work packets to be executed by the current worker.
     pub local_work_bucket: WorkBucket<VM>,
+    /// The local deque of the work packets created by the current worker, along with the buckets they are
+    /// added to. Only the owner worker pushes to and pops from it. Other workers steal from it with `stealer`.
+    local_deque: Worker<LocalWork<VM>>,
+    /// The stealing end of `local_deque`.
+    stealer: Stealer<LocalWork<VM>>,
 }
 
+/// A work packet in a local deque, and the bucket it was added to. The bucket may be deactivated
+/// before the packet is executed, in which case the packet is moved to the bucket.
+pub(crate) type LocalWork<VM> = (WorkBucketStage, Box<dyn GCWork<VM>>);
+
 impl<VM: VMBinding> GCWorkerShared<VM> {
     pub fn new(worker_monitor: Arc<(Mutex<()>, Condvar)>) -> Self {
+        let local_deque = Worker::new_lifo();
+        let stealer = local_deque.stealer();
         Self {
             parked: AtomicBool::new(true),
             stat: Default::default(),
             local_work_bucket: WorkBucket::new(true, worker_monitor),
+            local_deque,
+            stealer,
         }
     }
 }
@@ -52,9 +64,6 @@ pub struct GCWorker<VM: VMBinding> {
     /// True if this struct is the embedded GCWorker of the controller thread.
     /// False if this struct belongs to a standalone GCWorker thread.
     is_coordinator: bool,
-    /// Cache of work packets created by the current worker.
-    /// May be flushed to the global pool or executed locally.
-    local_work_buffer: Vec<(WorkBucketStage, Box<dyn GCWork<VM>>)>,
     /// Reference to the shared part of the GC worker.  It is used for synchronization.
     pub shared: Arc<GCWorkerShared<VM>>,
 }
@@ -78,6 +87,22 @@ impl<VM: VMBinding> GCWorkerShared<VM> {
     pub fn borrow_stat_mut(&self) -> AtomicRefMut<WorkerLocalStat<VM>> {
         self.stat.try_borrow_mut().expect(STAT_BORROWED_MSG)
     }
+
+    /// Return true if the local deque of this worker is empty.
+    pub fn is_local_deque_empty(&self) -> bool {
+        self.local_deque.is_empty()
+    }
+
+    /// Steal a work packet from the local deque of this worker. This is called by other workers.
+    pub(crate) fn steal(&self) -> Option<LocalWork<VM>> {
+        loop {
+            match self.stealer.steal() {
+                Steal::Success(work) => return Some(work),
+                Steal::Empty => return None,
+                Steal::Retry => continue,
+            }
+        }
+    }
 }
 
 impl<VM: VMBinding> GCWorker<VM> {
@@ -98,29 +123,30 @@ impl<VM: VMBinding> GCWorker<VM> {
             scheduler,
             mmtk,
             is_coordinator,
-            local_work_buffer: Vec::with_capacity(LOCALLY_CACHED_WORKS),
             shared,
         }
     }
 
+    /// Add a work packet created by the current worker.
+    ///
+    /// If the bucket is active, the packet goes to the local deque of the worker, and it will be executed
+    /// by the current worker, or stolen by an idle worker. Otherwise, the packet goes to the bucket.
+    /// A worker only parks when its local deque is empty, so the buckets of later stages will not be
+    /// opened before all the local deques are drained.
+    ///
+    /// The controller does not poll for work packets, so the packets in its local deque are always
+    /// stolen by the workers.
     #[inline]
     pub fn add_work(&mut self, bucket: WorkBucketStage, work: impl GCWork<VM>) {
         if !self.scheduler().work_buckets[bucket].is_activated() {
             self.scheduler.work_buckets[bucket].add_with_priority(1000, Box::new(work));
             return;
         }
-        self.local_work_buffer.push((bucket, Box::new(work)));
-        if self.local_work_buffer.len() > LOCALLY_CACHED_WORKS {
-            self.flush();
-        }
-    }
-
-    #[cold]
-    fn flush(&mut self) {
-        let mut buffer = Vec::with_capacity(LOCALLY_CACHED_WORKS);
-        std::mem::swap(&mut buffer, &mut self.local_work_buffer);
-        for (bucket, work) in buffer {
-            self.scheduler.work_buckets[bucket].add_with_priority(1000, work);
+        let was_empty = self.shared.local_deque.is_empty();
+        self.shared.local_deque.push((bucket, Box::new(work)));
+        if self.is_coordinator || !was_empty {
+            // There is more work than the current worker can do next. Wake up an idle worker to steal it.
+            self.scheduler.notify_parked_worker();
         }
     }
 
@@ -145,11 +171,13 @@ impl<VM: VMBinding> GCWorker<VM> {
         self.copy = crate::plan::create_gc_worker_context(tls, mmtk);
         self.shared.parked.store(false, Ordering::SeqCst);
         loop {
-            while let Some((bucket, mut work)) = self.local_work_buffer.pop() {
-                debug_assert!(self.scheduler.work_buckets[bucket].is_activated());
-                work.do_work_with_stat(self, mmtk);
-            }
-            let mut work = self.scheduler().poll(self);
+            let mut work = match self.shared.local_deque.pop() {
+                Some(local_work) => match self.scheduler().accept_local_work(local_work) {
+                    Some(work) => work,
+                    None => continue,
+                },
+                None => self.scheduler().poll(self),
+            };
             debug_assert!(!self.shared.is_parked());
             work.do_work_with_stat(self, mmtk);
         }
@@ -11,13 +11,18 @@ use downcast_rs::Downcast;
 
 #[repr(C)]
 #[derive(Debug)]
-/// A list of errors that MMTk can encounter during allocation.
+#[non_exhaustive]
+/// A list of errors that MMTk can encounter during allocation. More errors may be added in the future,
+/// so a binding that matches on the errors needs a wildcard arm.
 pub enum AllocationError {
     /// The specified heap size is too small for the given program to continue.
     HeapOutOfMemory,
     /// The OS is unable to mmap or acquire more memory. Critical error. MMTk expects the VM to
     /// abort if such an error is thrown.
     MmapOutOfMemory,
+    /// GCs take most of the execution time, but recover little memory (see the option `gc_time_limit`).
+    /// The heap is nearly full, and the program would thrash before it runs out of memory.
+    GCOverheadLimitExceeded,
 }
 
 #[inline(always)]
@@ -257,6 +262,13 @@ pub trait Allocator<VM: VMBinding>: Downcast {
                 return result;
             }
 
+            // The allocation failed after a GC. If the GCs are thrashing, fail fast instead of collecting again.
+            if plan.gc_overhead_limit.take_exceeded() {
+                trace!("Throw GCOverheadLimitExceeded!");
+                VM::VMCollection::out_of_memory(tls, AllocationError::GCOverheadLimitExceeded);
+                return result;
+            }
+
             // It is possible to have cases where a thread is blocked for another GC (non emergency)
             // immediately after being blocked for a GC (emergency) (e.g. in stress test), that is saying
             // the thread does not leave this loop between the two GCs. The local var 'emergency_collection'
@@ -1,8 +1,10 @@
 use std::mem::MaybeUninit;
+use std::ptr::addr_of;
 
 use crate::plan::Plan;
 use crate::policy::largeobjectspace::LargeObjectSpace;
 use crate::policy::mallocspace::MallocSpace;
+use crate::policy::marksweepspace::MarkSweepSpace;
 use crate::policy::space::Space;
 use crate::util::alloc::LargeObjectAllocator;
 use crate::util::alloc::MallocAllocator;
@@ -10,6 +12,7 @@ use crate::util::alloc::{Allocator, BumpAllocator, ImmixAllocator};
 use crate::util::VMMutatorThread;
 use crate::vm::VMBinding;
 
+use super::FreeListAllocator;
 use super::MarkCompactAllocator;
 
 pub(crate) const MAX_BUMP_ALLOCATORS: usize = 6;
@@ -17,6 +20,7 @@ pub(crate) const MAX_LARGE_OBJECT_ALLOCATORS: usize = 2;
 pub(crate) const MAX_MALLOC_ALLOCATORS: usize = 1;
 pub(crate) const MAX_IMMIX_ALLOCATORS: usize = 1;
 pub(crate) const MAX_MARK_COMPACT_ALLOCATORS: usize = 1;
+pub(crate) const MAX_FREE_LIST_ALLOCATORS: usize = 2;
 
 // The allocators set owned by each mutator. We provide a fixed number of allocators for each allocator type in the mutator,
 // and each plan will select part of the allocators to use.
@@ -29,6 +33,7 @@ pub struct Allocators<VM: VMBinding> {
     pub malloc: [MaybeUninit<MallocAllocator<VM>>; MAX_MALLOC_ALLOCATORS],
     pub immix: [MaybeUninit<ImmixAllocator<VM>>; MAX_IMMIX_ALLOCATORS],
     pub markcompact: [MaybeUninit<MarkCompactAllocator<VM>>; MAX_MARK_COMPACT_ALLOCATORS],
+    pub free_list: [MaybeUninit<FreeListAllocator<VM>>; MAX_FREE_LIST_ALLOCATORS],
 }
 
 impl<VM: VMBinding> Allocators<VM> {
@@ -47,6 +52,7 @@ impl<VM: VMBinding> Allocators<VM> {
             AllocatorSelector::MarkCompact(index) => {
                 self.markcompact[index as usize].assume_init_ref()
             }
+            AllocatorSelector::FreeList(index) => self.free_list[index as usize].assume_init_ref(),
             AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
         }
     }
@@ -69,10 +75,40 @@ impl<VM: VMBinding> Allocators<VM> {
             AllocatorSelector::MarkCompact(index) => {
                 self.markcompact[index as usize].assume_init_mut()
             }
+            AllocatorSelector::FreeList(index) => self.free_list[index as usize].assume_init_mut(),
             AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
         }
     }
 
+    /// The byte offset of the allocator selected by `selector` in this struct.
+    pub(crate) fn allocator_offset(selector: AllocatorSelector) -> usize {
+        let allocators = MaybeUninit::<Self>::uninit();
+        let base = allocators.as_ptr();
+        // We only compute field addresses here, and never read the uninitialized allocators.
+        let field = unsafe {
+            match selector {
+                AllocatorSelector::BumpPointer(index) => {
+                    addr_of!((*base).bump_pointer[index as usize]) as usize
+                }
+                AllocatorSelector::LargeObject(index) => {
+                    addr_of!((*base).large_object[index as usize]) as usize
+                }
+                AllocatorSelector::Malloc(index) => {
+                    addr_of!((*base).malloc[index as usize]) as usize
+                }
+                AllocatorSelector::Immix(index) => addr_of!((*base).immix[index as usize]) as usize,
+                AllocatorSelector::MarkCompact(index) => {
+                    addr_of!((*base).markcompact[index as usize]) as usize
+                }
+                AllocatorSelector::FreeList(index) => {
+                    addr_of!((*base).free_list[index as usize]) as usize
+                }
+                AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
+            }
+        };
+        field - base as usize
+    }
+
     pub fn new(
         mutator_tls: VMMutatorThread,
         plan: &'static dyn Plan<VM = VM>,
@@ -84,6 +120,7 @@ impl<VM: VMBinding> Allocators<VM> {
             malloc: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
             markcompact: unsafe { MaybeUninit::uninit().assume_init() },
+            free_list: unsafe { MaybeUninit::uninit().assume_init() },
         };
 
         for &(selector, space) in space_mapping.iter() {
@@ -124,6 +161,13 @@ impl<VM: VMBinding> Allocators<VM> {
                         plan,
                     ));
                 }
+                AllocatorSelector::FreeList(index) => {
+                    ret.free_list[index as usize].write(FreeListAllocator::new(
+                        mutator_tls.0,
+                        space.downcast_ref::<MarkSweepSpace<VM>>().unwrap(),
+                        plan,
+                    ));
+                }
                 AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
             }
         }
@@ -152,6 +196,7 @@ pub enum AllocatorSelector {
     Malloc(u8),
     Immix(u8),
     MarkCompact(u8),
+    FreeList(u8),
     None,
 }
 
@@ -9,6 +9,8 @@ use crate::policy::space::Space;
 use crate::util::conversions::bytes_to_pages;
 use crate::util::opaque_pointer::*;
 use crate::vm::VMBinding;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of;
 
 const BYTES_IN_PAGE: usize = 1 << 12;
 const BLOCK_SIZE: usize = 8 * BYTES_IN_PAGE;
@@ -43,6 +45,18 @@ impl<VM: VMBinding> BumpAllocator<VM> {
         self.reset();
         self.space = space;
     }
+
+    /// The byte offsets of the cursor and the limit in this struct.
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        unsafe {
+            (
+                addr_of!((*base).cursor) as usize - base as usize,
+                addr_of!((*base).limit) as usize - base as usize,
+            )
+        }
+    }
 }
 
 impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
@@ -0,0 +1,146 @@
+use crate::plan::{AllocationSemantics, Mutator};
+use crate::policy::marksweepspace::block::MAX_OBJECT_SIZE;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::{BumpAllocator, ImmixAllocator, MarkCompactAllocator};
+use crate::util::Address;
+use crate::vm::VMBinding;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of;
+
+/// How compiled code can allocate for an allocation semantics without calling into MMTk.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FastPathKind {
+    /// There is no fast path. Compiled code should always call the slow path.
+    SlowPathOnly,
+    /// Bump pointer allocation. Compiled code aligns the cursor, and bumps it by the allocation size.
+    /// If the new cursor is above the limit, it should call the slow path instead.
+    BumpPointer,
+}
+
+/// What compiled code needs to do after it initializes a newly allocated object.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PostAllocAction {
+    /// Nothing needs to be done.
+    None,
+    /// Set the alloc bit for the object. The alloc bits are side metadata starting at
+    /// [`ALLOC_SIDE_METADATA_ADDR`](crate::util::alloc_bit::ALLOC_SIDE_METADATA_ADDR), with one bit for
+    /// every [`MIN_OBJECT_SIZE`](crate::util::constants::MIN_OBJECT_SIZE) bytes. The bit should be set atomically.
+    SetAllocBit,
+    /// Call [`post_alloc()`](crate::memory_manager::post_alloc).
+    CallPostAlloc,
+}
+
+/// A descriptor of the allocation fast path for an allocation semantics, so a VM compiler can inline
+/// allocation without depending on the layout of [`Mutator`] or on which allocator a plan uses.
+/// The offsets are relative to the start of the `Mutator` struct. They do not change while the
+/// MMTk instance is alive, so the descriptor can be queried once and used for all mutators.
+#[repr(C)]
+pub struct AllocationFastPath<VM: VMBinding> {
+    /// The kind of the fast path. If this is `SlowPathOnly`, the offsets below are meaningless.
+    pub kind: FastPathKind,
+    /// The allocator that the plan selects for the allocation semantics.
+    pub selector: AllocatorSelector,
+    /// The byte offset of the bump pointer cursor in the mutator.
+    pub cursor_offset: usize,
+    /// The byte offset of the bump pointer limit in the mutator.
+    pub limit_offset: usize,
+    /// The bytes that the allocator reserves in front of each object. Compiled code bumps the cursor by
+    /// the object size plus these bytes, and the object starts these bytes after the aligned cursor.
+    pub reserved_header_bytes: usize,
+    /// Every allocation is aligned to at least this many bytes.
+    pub min_alignment: usize,
+    /// The largest alignment that can be requested.
+    pub max_alignment: usize,
+    /// The value that MMTk fills in the gap when the cursor is moved forward for alignment
+    /// (see [`fill_alignment_gap()`](crate::util::alloc::fill_alignment_gap)). This only helps debugging.
+    pub alignment_fill_value: usize,
+    /// What to do after the object is initialized.
+    pub post_alloc: PostAllocAction,
+    /// The largest object that this descriptor can allocate. Compiled code should allocate larger objects with
+    /// the descriptor for [`AllocationSemantics::Los`] instead, including the post-alloc action of that descriptor.
+    /// Neither this fast path nor the post-alloc action above is correct for larger objects.
+    pub max_non_los_bytes: usize,
+    /// The slow path, which takes the same arguments as [`alloc()`](crate::memory_manager::alloc).
+    /// It tries the fast path of the allocator again, as some allocators choose between slow paths
+    /// based on the request. The object it returns still needs the post-alloc action.
+    pub slow_path:
+        extern "C" fn(&mut Mutator<VM>, usize, usize, isize, AllocationSemantics) -> Address,
+}
+
+impl<VM: VMBinding> AllocationFastPath<VM> {
+    pub(crate) fn new(mutator: &Mutator<VM>, semantics: AllocationSemantics) -> Self {
+        let selector = mutator.config.allocator_mapping[semantics];
+        let (kind, offsets, reserved_header_bytes) = match selector {
+            AllocatorSelector::BumpPointer(_) => (
+                FastPathKind::BumpPointer,
+                BumpAllocator::<VM>::cursor_and_limit_offsets(),
+                0,
+            ),
+            AllocatorSelector::Immix(_) => (
+                FastPathKind::BumpPointer,
+                ImmixAllocator::<VM>::cursor_and_limit_offsets(),
+                0,
+            ),
+            AllocatorSelector::MarkCompact(_) => (
+                FastPathKind::BumpPointer,
+                MarkCompactAllocator::<VM>::cursor_and_limit_offsets(),
+                MarkCompactAllocator::<VM>::HEADER_RESERVED_IN_BYTES,
+            ),
+            _ => (FastPathKind::SlowPathOnly, (0, 0), 0),
+        };
+        let allocator_offset = match kind {
+            FastPathKind::BumpPointer => {
+                Self::allocators_offset() + Allocators::<VM>::allocator_offset(selector)
+            }
+            FastPathKind::SlowPathOnly => 0,
+        };
+        let post_alloc = if mutator.plan.constraints().needs_concurrent_workers {
+            // Concurrent plans may mark new objects while marking is in progress.
+            PostAllocAction::CallPostAlloc
+        } else {
+            unsafe { mutator.allocators.get_allocator(selector) }
+                .get_space()
+                .as_sft()
+                .post_alloc_action()
+        };
+        let max_non_los_bytes = match semantics {
+            AllocationSemantics::Default => {
+                mutator.plan.constraints().max_non_los_default_alloc_bytes
+            }
+            AllocationSemantics::NonMoving => MAX_OBJECT_SIZE,
+            _ => usize::MAX,
+        };
+        AllocationFastPath {
+            kind,
+            selector,
+            cursor_offset: allocator_offset + offsets.0,
+            limit_offset: allocator_offset + offsets.1,
+            reserved_header_bytes,
+            min_alignment: VM::MIN_ALIGNMENT,
+            max_alignment: VM::MAX_ALIGNMENT,
+            alignment_fill_value: VM::ALIGNMENT_VALUE,
+            post_alloc,
+            max_non_los_bytes,
+            slow_path: alloc_slow::<VM>,
+        }
+    }
+
+    /// The byte offset of the allocators in the mutator.
+    fn allocators_offset() -> usize {
+        let mutator = MaybeUninit::<Mutator<VM>>::uninit();
+        let base = mutator.as_ptr();
+        unsafe { addr_of!((*base).allocators) as usize - base as usize }
+    }
+}
+
+extern "C" fn alloc_slow<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    size: usize,
+    align: usize,
+    offset: isize,
+    semantics: AllocationSemantics,
+) -> Address {
+    crate::memory_manager::alloc::<VM>(mutator, size, align, offset, semantics)
+}
@@ -0,0 +1,158 @@
+use crate::plan::Plan;
+use crate::policy::marksweepspace::block::{size_class, Block, NUM_SIZE_CLASSES, SIZE_CLASSES};
+use crate::policy::marksweepspace::MarkSweepSpace;
+use crate::policy::space::Space;
+use crate::util::alloc::{allocator, Allocator};
+use crate::util::opaque_pointer::*;
+use crate::util::Address;
+use crate::vm::{ObjectModel, VMBinding};
+
+/// A segregated free list allocator for [`MarkSweepSpace`]. It keeps a free list of cells for
+/// each size class, which is taken from a block of the space.
+#[repr(C)]
+pub struct FreeListAllocator<VM: VMBinding> {
+    /// [`VMThread`] associated with this allocator instance
+    pub tls: VMThread,
+    /// [`Space`](src/policy/space/Space) instance associated with this allocator instance.
+    space: &'static MarkSweepSpace<VM>,
+    /// [`Plan`] instance that this allocator instance is associated with.
+    plan: &'static dyn Plan<VM = VM>,
+    /// The first free cell for each size class. Free cells are linked through their first word.
+    free_lists: [Address; NUM_SIZE_CLASSES],
+    /// If true, every allocation goes to the slow path, so we can check for precise stress GCs.
+    precise_stress: bool,
+}
+
+impl<VM: VMBinding> FreeListAllocator<VM> {
+    pub fn new(
+        tls: VMThread,
+        space: &'static MarkSweepSpace<VM>,
+        plan: &'static dyn Plan<VM = VM>,
+    ) -> Self {
+        FreeListAllocator {
+            tls,
+            space,
+            plan,
+            free_lists: [Address::ZERO; NUM_SIZE_CLASSES],
+            precise_stress: plan.base().is_stress_test_gc_enabled()
+                && plan.base().is_precise_stress(),
+        }
+    }
+
+    /// Drop all the free lists. This is called after a GC, as the blocks are swept and
+    /// the free cells are relinked.
+    pub fn reset(&mut self) {
+        self.free_lists = [Address::ZERO; NUM_SIZE_CLASSES];
+    }
+
+    /// Get the size class for an allocation request.
+    #[inline(always)]
+    fn size_class_for(size: usize, align: usize) -> usize {
+        let bytes = allocator::get_maximum_aligned_size::<VM>(size, align, VM::MIN_ALIGNMENT);
+        // Make sure the object reference is in the cell, so a sweep can find the object in each cell.
+        let bytes = match VM::VMObjectModel::OBJECT_REF_OFFSET_BEYOND_CELL {
+            Some(offset) => std::cmp::max(bytes, offset + VM::MIN_ALIGNMENT),
+            None => bytes,
+        };
+        assert!(
+            bytes <= crate::policy::marksweepspace::block::MAX_OBJECT_SIZE,
+            "Allocating {} bytes, which is larger than max_non_los_default_alloc_bytes and should be allocated in the large object space",
+            bytes
+        );
+        size_class(bytes)
+    }
+
+    /// Allocate from the free list of the size class. Return zero if the free list is empty.
+    #[inline(always)]
+    fn alloc_from_free_list(&mut self, size_class: usize, align: usize, offset: isize) -> Address {
+        let cell = self.free_lists[size_class];
+        if cell.is_zero() {
+            return cell;
+        }
+        self.free_lists[size_class] = unsafe { cell.load::<Address>() };
+        crate::util::memory::zero(cell, SIZE_CLASSES[size_class]);
+        allocator::align_allocation_no_fill::<VM>(cell, align, offset)
+    }
+
+    /// Take a block from the space as the free list of the size class, and allocate from it.
+    fn alloc_from_block(
+        &mut self,
+        block: Option<Block>,
+        size_class: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        match block {
+            Some(block) => {
+                self.free_lists[size_class] = block.take_free_list();
+                self.alloc_from_free_list(size_class, align, offset)
+            }
+            None => Address::ZERO,
+        }
+    }
+}
+
+impl<VM: VMBinding> Allocator<VM> for FreeListAllocator<VM> {
+    fn get_tls(&self) -> VMThread {
+        self.tls
+    }
+
+    fn get_plan(&self) -> &'static dyn Plan<VM = VM> {
+        self.plan
+    }
+
+    fn get_space(&self) -> &'static dyn Space<VM> {
+        self.space as &'static dyn Space<VM>
+    }
+
+    fn does_thread_local_allocation(&self) -> bool {
+        true
+    }
+
+    fn get_thread_local_buffer_granularity(&self) -> usize {
+        Block::BYTES
+    }
+
+    #[inline(always)]
+    fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        if self.precise_stress {
+            return self.alloc_slow(size, align, offset);
+        }
+        let size_class = Self::size_class_for(size, align);
+        let rtn = self.alloc_from_free_list(size_class, align, offset);
+        if rtn.is_zero() {
+            self.alloc_slow(size, align, offset)
+        } else {
+            rtn
+        }
+    }
+
+    fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        let size_class = Self::size_class_for(size, align);
+        let block = self.space.acquire_block(self.tls, size_class);
+        self.alloc_from_block(block, size_class, align, offset)
+    }
+
+    /// Slow path for allocation if precise stress testing has been enabled.
+    /// When a poll is needed, we acquire a clean block from the space, which polls for a GC.
+    /// Otherwise, we allocate from the free list, and only go to the space if the free list is
+    /// empty. The fast path always goes to the slow path in precise stress tests.
+    fn alloc_slow_once_precise_stress(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        need_poll: bool,
+    ) -> Address {
+        let size_class = Self::size_class_for(size, align);
+        if need_poll {
+            let block = self.space.get_clean_block(self.tls, size_class);
+            return self.alloc_from_block(block, size_class, align, offset);
+        }
+        let rtn = self.alloc_from_free_list(size_class, align, offset);
+        if !rtn.is_zero() {
+            return rtn;
+        }
+        self.alloc_slow_once(size, align, offset)
+    }
+}
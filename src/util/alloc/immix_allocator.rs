@@ -9,6 +9,8 @@ use crate::util::linear_scan::Region;
 use crate::util::opaque_pointer::VMThread;
 use crate::util::Address;
 use crate::vm::*;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of;
 
 /// Immix allocator
 #[repr(C)]
@@ -53,6 +55,18 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
         self.request_for_large = false;
         self.line = None;
     }
+
+    /// The byte offsets of the bump pointer cursor and limit (for small objects) in this struct.
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        unsafe {
+            (
+                addr_of!((*base).cursor) as usize - base as usize,
+                addr_of!((*base).limit) as usize - base as usize,
+            )
+        }
+    }
 }
 
 impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
@@ -256,7 +270,7 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
                 );
                 #[cfg(feature = "global_alloc_bit")]
                 crate::util::alloc_bit::bzero_alloc_bit(self.cursor, self.limit - self.cursor);
-                crate::util::memory::zero(self.cursor, self.limit - self.cursor);
+                crate::util::heap::zeroing::ZEROER.zero(self.cursor, self.limit - self.cursor);
                 debug_assert!(
                     align_allocation_no_fill::<VM>(self.cursor, align, offset) + size <= self.limit
                 );
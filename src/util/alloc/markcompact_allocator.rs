@@ -5,6 +5,8 @@ use crate::util::alloc::Allocator;
 use crate::util::opaque_pointer::*;
 use crate::util::Address;
 use crate::vm::VMBinding;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of;
 
 /// A thin wrapper(specific implementation) of bump allocator
 /// reserve extra bytes when allocating
@@ -25,6 +27,15 @@ impl<VM: VMBinding> MarkCompactAllocator<VM> {
     pub fn rebind(&mut self, space: &'static dyn Space<VM>) {
         self.bump_allocator.rebind(space);
     }
+
+    /// The byte offsets of the cursor and the limit of the bump allocator in this struct.
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        let bump_allocator = unsafe { addr_of!((*base).bump_allocator) as usize - base as usize };
+        let (cursor, limit) = BumpAllocator::<VM>::cursor_and_limit_offsets();
+        (bump_allocator + cursor, bump_allocator + limit)
+    }
 }
 
 impl<VM: VMBinding> Allocator<VM> for MarkCompactAllocator<VM> {
@@ -29,9 +29,17 @@ pub use malloc_allocator::MallocAllocator;
 pub mod immix_allocator;
 pub use self::immix_allocator::ImmixAllocator;
 
+/// Segregated free list allocator for the native marksweep space
+mod free_list_allocator;
+pub use free_list_allocator::FreeListAllocator;
+
 /// Mark compact allocator (actually a bump pointer allocator with an extra heade word)
 mod markcompact_allocator;
 pub use markcompact_allocator::MarkCompactAllocator;
 
+/// Descriptors of the allocation fast paths for VM compilers
+mod fastpath;
+pub use fastpath::{AllocationFastPath, FastPathKind, PostAllocAction};
+
 /// Embedded metadata pages
 pub(crate) mod embedded_meta_data;
@@ -7,6 +7,8 @@ use crate::policy::copyspace::CopySpace;
 use crate::policy::copyspace::CopySpaceCopyContext;
 use crate::policy::immix::ImmixCopyContext;
 use crate::policy::immix::ImmixSpace;
+use crate::policy::mallocspace::MallocSpace;
+use crate::policy::mallocspace::MallocSpaceCopyContext;
 use crate::policy::space::Space;
 use crate::util::object_forwarding;
 use crate::util::opaque_pointer::VMWorkerThread;
@@ -20,6 +22,7 @@ use enum_map::EnumMap;
 
 const MAX_COPYSPACE_COPY_ALLOCATORS: usize = 1;
 const MAX_IMMIX_COPY_ALLOCATORS: usize = 2;
+const MAX_MALLOC_SPACE_COPY_ALLOCATORS: usize = 1;
 
 type CopySpaceMapping<VM> = Vec<(CopySelector, &'static dyn Space<VM>)>;
 
@@ -53,6 +56,8 @@ pub struct GCWorkerCopyContext<VM: VMBinding> {
     pub copy: [MaybeUninit<CopySpaceCopyContext<VM>>; MAX_COPYSPACE_COPY_ALLOCATORS],
     /// Copy allocators for ImmixSpace
     pub immix: [MaybeUninit<ImmixCopyContext<VM>>; MAX_IMMIX_COPY_ALLOCATORS],
+    /// Copy allocators for MallocSpace
+    pub malloc: [MaybeUninit<MallocSpaceCopyContext<VM>>; MAX_MALLOC_SPACE_COPY_ALLOCATORS],
     /// The config for the plan
     config: CopyConfig<VM>,
 }
@@ -89,6 +94,10 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
             }
             CopySelector::Immix(index) => unsafe { self.immix[index as usize].assume_init_mut() }
                 .alloc_copy(original, bytes, align, offset),
+            CopySelector::MallocSpace(index) => {
+                unsafe { self.malloc[index as usize].assume_init_mut() }
+                    .alloc_copy(original, bytes, align, offset)
+            }
             CopySelector::Unused => unreachable!(),
         }
     }
@@ -115,6 +124,9 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
             CopySelector::Immix(index) => {
                 unsafe { self.immix[index as usize].assume_init_mut() }.post_copy(object, bytes)
             }
+            CopySelector::MallocSpace(index) => {
+                unsafe { self.malloc[index as usize].assume_init_mut() }.post_copy(object, bytes)
+            }
             CopySelector::Unused => unreachable!(),
         }
     }
@@ -130,6 +142,9 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 CopySelector::Immix(index) => {
                     unsafe { self.immix[*index as usize].assume_init_mut() }.prepare()
                 }
+                CopySelector::MallocSpace(index) => {
+                    unsafe { self.malloc[*index as usize].assume_init_mut() }.prepare()
+                }
                 CopySelector::Unused => {}
             }
         }
@@ -146,6 +161,9 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 CopySelector::Immix(index) => {
                     unsafe { self.immix[*index as usize].assume_init_mut() }.release()
                 }
+                CopySelector::MallocSpace(index) => {
+                    unsafe { self.malloc[*index as usize].assume_init_mut() }.release()
+                }
                 CopySelector::Unused => {}
             }
         }
@@ -165,6 +183,7 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
         let mut ret = GCWorkerCopyContext {
             copy: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
+            malloc: unsafe { MaybeUninit::uninit().assume_init() },
             config,
         };
 
@@ -185,6 +204,12 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                         space.downcast_ref::<ImmixSpace<VM>>().unwrap(),
                     ));
                 }
+                CopySelector::MallocSpace(index) => {
+                    ret.malloc[index as usize].write(MallocSpaceCopyContext::new(
+                        worker_tls,
+                        space.downcast_ref::<MallocSpace<VM>>().unwrap(),
+                    ));
+                }
                 CopySelector::Unused => unreachable!(),
             }
         }
@@ -197,6 +222,7 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
         GCWorkerCopyContext {
             copy: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
+            malloc: unsafe { MaybeUninit::uninit().assume_init() },
             config: CopyConfig::default(),
         }
     }
@@ -230,6 +256,7 @@ impl CopySemantics {
 pub enum CopySelector {
     CopySpace(u8),
     Immix(u8),
+    MallocSpace(u8),
     Unused,
 }
 
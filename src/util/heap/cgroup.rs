@@ -0,0 +1,376 @@
+//! Size the heap from the memory limit of the cgroup that the process runs in.
+//!
+//! If the option `use_cgroup_memory_limit` is set, the memory limit of the cgroup is read at `gc_init`.
+//! The cgroup of the process is looked up in `/proc/self/cgroup`, and its files are read under the cgroup
+//! file system mounted at the option `cgroup_path`: the cgroup v2 file `memory.max`, or the cgroup v1 file
+//! `memory.limit_in_bytes` of the memory controller. If the cgroup has no limit, the limits of its parents
+//! are used. The heap size is then `cgroup_heap_percent` percent of the limit. The rest of the memory is left
+//! for the VM and the other memory of the process. As the mutators allocate, the memory usage of the cgroup
+//! (`memory.current` or `memory.usage_in_bytes`) is checked from time to time, and a GC is triggered when
+//! it approaches the limit. If such a GC does not bring the memory usage down, e.g. because the freed pages
+//! are not uncommitted yet, we check less often so that we do not trigger GCs in a row.
+
+use crate::util::conversions::bytes_to_pages;
+use crate::util::options::Options;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A GC is triggered when the memory usage of the cgroup is above this fraction of the limit.
+const USAGE_TRIGGER_RATIO: f64 = 0.95;
+/// The memory usage is checked each time the reserved pages grow by this fraction of the heap size.
+const CHECK_INTERVAL_RATIO: usize = 16;
+/// Cgroup v1 reports a very large number if there is no limit.
+const UNLIMITED: usize = 1 << 60;
+/// The file that lists the cgroups of the process
+const PROC_SELF_CGROUP: &str = "/proc/self/cgroup";
+
+/// The cgroup files that give the memory limit and the memory usage
+struct CgroupFiles {
+    limit: &'static str,
+    usage: &'static str,
+}
+
+static CGROUP_V2_FILES: CgroupFiles = CgroupFiles {
+    limit: "memory.max",
+    usage: "memory.current",
+};
+
+static CGROUP_V1_FILES: CgroupFiles = CgroupFiles {
+    limit: "memory.limit_in_bytes",
+    usage: "memory.usage_in_bytes",
+};
+
+/// Read a number of bytes from a cgroup file. Return None if the file cannot be read, or if there is no limit.
+fn read_bytes(path: &Path) -> Option<usize> {
+    let content = fs::read_to_string(path).ok()?;
+    // cgroup v2 uses "max" for no limit.
+    let bytes = content.trim().parse::<usize>().ok()?;
+    if bytes >= UNLIMITED {
+        None
+    } else {
+        Some(bytes)
+    }
+}
+
+/// Find the directories of the cgroups of the process from the content of `/proc/self/cgroup`, with the
+/// cgroup file system mounted at `root`. Each line is `hierarchy-ID:controller-list:cgroup-path`. The cgroup v2
+/// hierarchy has the ID 0 and no controllers. A cgroup v1 hierarchy is mounted in a directory named after
+/// its controller, and we only look at the memory controller.
+fn find_cgroup_dirs(root: &Path, proc_self_cgroup: &str) -> Vec<(PathBuf, &'static CgroupFiles)> {
+    let mut dirs = vec![];
+    for line in proc_self_cgroup.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (id, controllers, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(id), Some(controllers), Some(path)) => (id, controllers, path),
+            _ => continue,
+        };
+        let path = path.trim_start_matches('/');
+        if id == "0" && controllers.is_empty() {
+            dirs.push((root.join(path), &CGROUP_V2_FILES));
+        } else if controllers.split(',').any(|c| c == "memory") {
+            dirs.push((root.join("memory").join(path), &CGROUP_V1_FILES));
+        }
+    }
+    // The process may not be able to see its cgroup, e.g. in a container. Try the root cgroups.
+    dirs.push((root.to_path_buf(), &CGROUP_V2_FILES));
+    dirs.push((root.join("memory"), &CGROUP_V1_FILES));
+    dirs
+}
+
+/// Find the memory limit of the process, with the cgroup file system mounted at `cgroup_path`, and the
+/// content of `/proc/self/cgroup`. The limit of a cgroup also applies to its children, so if a cgroup has
+/// no limit, we look at its parents. Return the limit in bytes, and the file for the memory usage of the
+/// cgroup with the limit.
+fn find_memory_limit(cgroup_path: &str, proc_self_cgroup: &str) -> Option<(usize, String)> {
+    let root = Path::new(cgroup_path);
+    find_cgroup_dirs(root, proc_self_cgroup)
+        .into_iter()
+        .find_map(|(dir, files)| {
+            dir.ancestors()
+                .take_while(|dir| dir.starts_with(root))
+                .find_map(|dir| {
+                    read_bytes(&dir.join(files.limit))
+                        .map(|limit| (limit, dir.join(files.usage).to_string_lossy().into_owned()))
+                })
+        })
+}
+
+pub struct CgroupMemoryLimit {
+    /// The memory limit in bytes. None if the option is not set or there is no limit.
+    limit: Option<usize>,
+    /// The file for the memory usage of the cgroup
+    usage_file: String,
+    /// The number of pages that the reserved pages grow by between the checks if the GCs keep the memory
+    /// usage down
+    min_check_interval_pages: usize,
+    /// The largest interval between the checks
+    max_check_interval_pages: usize,
+    /// The number of pages that the reserved pages need to grow by before we check the memory usage again
+    check_interval_pages: AtomicUsize,
+    /// The reserved pages when we last checked the memory usage
+    last_checked_pages: AtomicUsize,
+    /// The memory usage that triggered the current GC, or zero if the GC was not triggered by the memory usage
+    usage_at_trigger: AtomicUsize,
+}
+
+impl CgroupMemoryLimit {
+    pub fn new() -> Self {
+        CgroupMemoryLimit {
+            limit: None,
+            usage_file: String::new(),
+            min_check_interval_pages: 0,
+            max_check_interval_pages: 0,
+            check_interval_pages: AtomicUsize::new(0),
+            last_checked_pages: AtomicUsize::new(0),
+            usage_at_trigger: AtomicUsize::new(0),
+        }
+    }
+
+    /// Read the memory limit if the options ask for it, and return the heap size in bytes. If there is
+    /// no limit, this returns `heap_size`, which is the size given to `gc_init`.
+    pub fn init(&mut self, heap_size: usize, options: &Options) -> usize {
+        if !*options.use_cgroup_memory_limit {
+            return heap_size;
+        }
+        let proc_self_cgroup = fs::read_to_string(PROC_SELF_CGROUP).unwrap_or_default();
+        match find_memory_limit(&options.cgroup_path, &proc_self_cgroup) {
+            Some((limit, usage_file)) => {
+                let heap_size = limit / 100 * *options.cgroup_heap_percent;
+                info!(
+                    "The cgroup memory limit is {} bytes. Use a heap size of {} bytes.",
+                    limit, heap_size
+                );
+                self.limit = Some(limit);
+                self.usage_file = usage_file;
+                self.max_check_interval_pages = bytes_to_pages(heap_size).max(1);
+                self.min_check_interval_pages =
+                    (self.max_check_interval_pages / CHECK_INTERVAL_RATIO).max(1);
+                self.check_interval_pages
+                    .store(self.min_check_interval_pages, Ordering::Relaxed);
+                heap_size
+            }
+            None => {
+                warn!(
+                    "Cannot find a cgroup memory limit in {}. Use the heap size {} bytes.",
+                    *options.cgroup_path, heap_size
+                );
+                heap_size
+            }
+        }
+    }
+
+    /// Is the memory usage of the cgroup close to the limit? This is called when the mutators poll for
+    /// a GC. It only reads the usage when the reserved pages have grown enough since the last check.
+    pub fn is_approaching_limit(&self, reserved_pages: usize) -> bool {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return false,
+        };
+        let last_checked = self.last_checked_pages.load(Ordering::Relaxed);
+        if reserved_pages < last_checked + self.check_interval_pages.load(Ordering::Relaxed)
+            || self
+                .last_checked_pages
+                .compare_exchange(
+                    last_checked,
+                    reserved_pages,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+        {
+            // Not yet, or another thread is checking.
+            return false;
+        }
+        match read_bytes(Path::new(&self.usage_file)) {
+            Some(usage) if Self::is_close(usage, limit) => {
+                debug!(
+                    "The cgroup memory usage {} bytes is close to the limit {} bytes",
+                    usage, limit
+                );
+                self.usage_at_trigger.store(usage, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_close(usage: usize, limit: usize) -> bool {
+        usage as f64 >= limit as f64 * USAGE_TRIGGER_RATIO
+    }
+
+    /// A GC ends. `reserved_pages` is the number of reserved pages after the GC. This should be called
+    /// after the free pages are uncommitted. We check the memory usage again once the mutators have allocated
+    /// enough pages. If the GC was triggered by the memory usage, and it did not bring the usage down, another
+    /// GC is unlikely to help soon, so we double the interval between the checks.
+    pub fn on_gc_end(&self, reserved_pages: usize) {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        self.last_checked_pages
+            .store(reserved_pages, Ordering::Relaxed);
+        let usage_at_trigger = self.usage_at_trigger.swap(0, Ordering::Relaxed);
+        if usage_at_trigger == 0 {
+            return;
+        }
+        let interval = match read_bytes(Path::new(&self.usage_file)) {
+            Some(usage) if Self::is_close(usage, limit) => {
+                let interval = (self.check_interval_pages.load(Ordering::Relaxed) * 2)
+                    .min(self.max_check_interval_pages);
+                debug!(
+                    "The cgroup memory usage is {} bytes after a GC, and {} bytes before it. Check the usage again after {} pages.",
+                    usage, usage_at_trigger, interval
+                );
+                interval
+            }
+            _ => self.min_check_interval_pages,
+        };
+        self.check_interval_pages.store(interval, Ordering::Relaxed);
+    }
+}
+
+impl Default for CgroupMemoryLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_util::serial_test;
+
+    fn cgroup_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("mmtk-cgroup-test-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("memory")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn find_cgroup_v2_limit() {
+        let dir = cgroup_dir("v2");
+        let root = dir.to_str().unwrap();
+        fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+        let (limit, usage_file) = find_memory_limit(root, "0::/\n").unwrap();
+        assert_eq!(limit, 1 << 30);
+        assert_eq!(Path::new(&usage_file), dir.join("memory.current"));
+
+        // No limit
+        fs::write(dir.join("memory.max"), "max\n").unwrap();
+        assert!(find_memory_limit(root, "0::/\n").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn find_cgroup_v1_limit() {
+        let dir = cgroup_dir("v1");
+        let root = dir.to_str().unwrap();
+        fs::write(dir.join("memory/memory.limit_in_bytes"), "536870912\n").unwrap();
+        let (limit, usage_file) = find_memory_limit(root, "").unwrap();
+        assert_eq!(limit, 1 << 29);
+        assert_eq!(
+            Path::new(&usage_file),
+            dir.join("memory/memory.usage_in_bytes")
+        );
+
+        // No limit
+        fs::write(
+            dir.join("memory/memory.limit_in_bytes"),
+            "9223372036854771712\n",
+        )
+        .unwrap();
+        assert!(find_memory_limit(root, "").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn find_process_cgroup_v2() {
+        let dir = cgroup_dir("process-v2");
+        let root = dir.to_str().unwrap();
+        let cgroup = dir.join("user.slice/app.scope");
+        fs::create_dir_all(&cgroup).unwrap();
+        fs::write(dir.join("memory.max"), "max\n").unwrap();
+        fs::write(cgroup.join("memory.max"), "268435456\n").unwrap();
+        let proc_self_cgroup = "0::/user.slice/app.scope\n";
+        let (limit, usage_file) = find_memory_limit(root, proc_self_cgroup).unwrap();
+        assert_eq!(limit, 1 << 28);
+        assert_eq!(Path::new(&usage_file), cgroup.join("memory.current"));
+
+        // The limit of a parent applies if the cgroup of the process has no limit.
+        fs::write(cgroup.join("memory.max"), "max\n").unwrap();
+        fs::write(dir.join("user.slice/memory.max"), "134217728\n").unwrap();
+        let (limit, usage_file) = find_memory_limit(root, proc_self_cgroup).unwrap();
+        assert_eq!(limit, 1 << 27);
+        assert_eq!(
+            Path::new(&usage_file),
+            dir.join("user.slice/memory.current")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn find_process_cgroup_v1() {
+        let dir = cgroup_dir("process-v1");
+        let root = dir.to_str().unwrap();
+        let cgroup = dir.join("memory/docker/abc");
+        fs::create_dir_all(&cgroup).unwrap();
+        fs::write(cgroup.join("memory.limit_in_bytes"), "268435456\n").unwrap();
+        let proc_self_cgroup = "12:cpu,cpuacct:/docker/abc\n4:memory:/docker/abc\n";
+        let (limit, usage_file) = find_memory_limit(root, proc_self_cgroup).unwrap();
+        assert_eq!(limit, 1 << 28);
+        assert_eq!(Path::new(&usage_file), cgroup.join("memory.usage_in_bytes"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn back_off_if_gc_does_not_reduce_usage() {
+        serial_test(|| {
+            let dir = cgroup_dir("back-off");
+            fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+            let mut options = Options::default();
+            options.use_cgroup_memory_limit.value = true;
+            options.cgroup_path.value = dir.to_str().unwrap().to_string();
+            let mut cgroup = CgroupMemoryLimit::new();
+            cgroup.init(0, &options);
+            // Pretend the process is in the root cgroup.
+            cgroup.usage_file = dir.join("memory.current").to_string_lossy().into_owned();
+            let min_interval = cgroup.min_check_interval_pages;
+            let set_usage = |bytes: usize| {
+                fs::write(dir.join("memory.current"), format!("{}\n", bytes)).unwrap()
+            };
+            let near_limit = 1usize << 30;
+
+            // Not checked until the reserved pages grow enough.
+            set_usage(near_limit);
+            assert!(!cgroup.is_approaching_limit(min_interval - 1));
+            assert!(cgroup.is_approaching_limit(min_interval));
+
+            // The GC does not reduce the usage, so we check less often.
+            cgroup.on_gc_end(0);
+            assert_eq!(
+                cgroup.check_interval_pages.load(Ordering::Relaxed),
+                min_interval * 2
+            );
+            assert!(!cgroup.is_approaching_limit(min_interval));
+            assert!(cgroup.is_approaching_limit(min_interval * 2));
+            cgroup.on_gc_end(0);
+            assert_eq!(
+                cgroup.check_interval_pages.load(Ordering::Relaxed),
+                min_interval * 4
+            );
+
+            // The GC reduces the usage, so we check as often as before.
+            assert!(cgroup.is_approaching_limit(min_interval * 4));
+            set_usage(near_limit / 2);
+            cgroup.on_gc_end(0);
+            assert_eq!(
+                cgroup.check_interval_pages.load(Ordering::Relaxed),
+                min_interval
+            );
+            assert!(!cgroup.is_approaching_limit(min_interval));
+            fs::remove_dir_all(dir).unwrap();
+        })
+    }
+}
@@ -14,6 +14,8 @@ use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::layout::vm_layout_constants::*;
 use crate::util::heap::pageresource::CommonPageResource;
 use crate::util::heap::space_descriptor::SpaceDescriptor;
+use crate::util::heap::uncommit::UNCOMMITTER;
+use crate::util::heap::zeroing::ZEROER;
 use crate::util::memory;
 use crate::util::opaque_pointer::*;
 use crate::vm::*;
@@ -352,6 +354,12 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
         if self.protect_memory_on_release {
             self.mprotect(first, pages as _);
         }
+        // Record the release before the pages are back on the free list and can be allocated again.
+        UNCOMMITTER.release(first, conversions::pages_to_bytes(pages as _));
+        // Protected pages cannot be zeroed in the background.
+        if !self.protect_memory_on_release {
+            ZEROER.release(first, conversions::pages_to_bytes(pages as _));
+        }
 
         // FIXME
         #[allow(clippy::cast_ref_to_mut)]
@@ -0,0 +1,302 @@
+//! Resize the heap after each GC.
+//!
+//! If the option `variable_size_heap` is set, and the options `min_heap` and `max_heap` give a range of
+//! heap sizes, the heap size is recalculated at the end of each GC with the square-root rule of MemBalancer
+//! (Kirisame et al., *Optimal Heap Limits for Reducing Browser Memory Use*, OOPSLA 2022):
+//! the extra heap space above the live size is `sqrt(live * alloc_rate / (TUNING_FACTOR * gc_speed))`.
+//! Otherwise, the heap size stays at the size given to `gc_init`.
+
+use crate::util::conversions::bytes_to_pages;
+use crate::util::heap::HeapMeta;
+use crate::util::options::Options;
+use crate::util::ObjectReference;
+use crate::vm::{ObjectModel, VMBinding};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// The constant `c` in the MemBalancer rule. A smaller value gives more extra heap space to the
+/// application, and results in less frequent GCs.
+const TUNING_FACTOR: f64 = 0.2;
+/// The weight of the latest observation in the smoothed allocation rate and GC speed.
+const SMOOTHING_FACTOR: f64 = 0.5;
+
+/// What we observed about the mutators and the GCs.
+struct Observations {
+    /// When the current GC started
+    gc_start: Instant,
+    /// When the last GC ended
+    last_gc_end: Instant,
+    /// The reserved pages when the last GC ended
+    pages_after_last_gc: usize,
+    /// The reserved pages when the current GC started
+    pages_before_gc: usize,
+    /// The smoothed allocation rate, in pages per second of mutator time
+    alloc_rate: Option<f64>,
+    /// The smoothed GC speed, in pages of traced objects per second of GC time
+    gc_speed: Option<f64>,
+}
+
+pub struct HeapSizing {
+    /// Is the heap size allowed to change?
+    variable: bool,
+    /// The minimum heap size in pages
+    min_pages: usize,
+    /// The maximum heap size in pages
+    max_pages: usize,
+    observations: Mutex<Observations>,
+    /// The bytes of the objects traced in the current GC. A nursery GC only traces the surviving
+    /// nursery objects, so this measures the work of the GC better than the live size of the heap.
+    traced_bytes: AtomicUsize,
+}
+
+impl HeapSizing {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        HeapSizing {
+            variable: false,
+            min_pages: 0,
+            max_pages: 0,
+            observations: Mutex::new(Observations {
+                gc_start: now,
+                last_gc_end: now,
+                pages_after_last_gc: 0,
+                pages_before_gc: 0,
+                alloc_rate: None,
+                gc_speed: None,
+            }),
+            traced_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Can the heap size change? This is known after `init()`.
+    #[inline(always)]
+    pub fn is_variable(&self) -> bool {
+        self.variable
+    }
+
+    /// Decide the range of the heap size from the options, and return the initial heap size in pages.
+    /// The initial heap size is the size given to `gc_init`, clamped to the range.
+    pub fn init(&mut self, heap_size: usize, options: &Options) -> usize {
+        let max_heap = if *options.max_heap == 0 {
+            heap_size.max(*options.min_heap)
+        } else {
+            *options.max_heap
+        };
+        let min_heap = if *options.min_heap == 0 {
+            heap_size.min(max_heap)
+        } else {
+            *options.min_heap
+        };
+        // The options do not allow a min_heap larger than max_heap.
+        debug_assert!(min_heap <= max_heap);
+        self.min_pages = bytes_to_pages(min_heap);
+        self.max_pages = bytes_to_pages(max_heap);
+        self.variable = *options.variable_size_heap && self.min_pages < self.max_pages;
+        self.observations.lock().unwrap().last_gc_end = Instant::now();
+        bytes_to_pages(heap_size)
+            .max(self.min_pages)
+            .min(self.max_pages)
+    }
+
+    /// A GC starts. `reserved_pages` is the number of reserved pages before the GC.
+    pub fn on_gc_start(&self, reserved_pages: usize) {
+        if !self.variable {
+            return;
+        }
+        let mut obs = self.observations.lock().unwrap();
+        obs.gc_start = Instant::now();
+        obs.pages_before_gc = reserved_pages;
+        self.traced_bytes.store(0, Ordering::Relaxed);
+
+        let mutator_time = obs.gc_start.duration_since(obs.last_gc_end).as_secs_f64();
+        let allocated = reserved_pages.saturating_sub(obs.pages_after_last_gc);
+        if mutator_time > 0f64 {
+            obs.alloc_rate = Some(smooth(obs.alloc_rate, allocated as f64 / mutator_time));
+        }
+    }
+
+    /// Count the objects traced by a GC worker. This is called once for each work packet that scans objects.
+    #[inline]
+    pub fn on_objects_traced<VM: VMBinding>(&self, objects: &[ObjectReference]) {
+        if !self.variable {
+            return;
+        }
+        let bytes: usize = objects
+            .iter()
+            .map(|o| VM::VMObjectModel::get_current_size(*o))
+            .sum();
+        self.traced_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// A GC ends. `reserved_pages` is the number of reserved pages after the GC, and `full_heap`
+    /// tells whether the GC collected the whole heap. The new heap size is stored to `heap`.
+    pub fn on_gc_end(&self, heap: &HeapMeta, reserved_pages: usize, full_heap: bool) {
+        if !self.variable {
+            return;
+        }
+        let mut obs = self.observations.lock().unwrap();
+        obs.last_gc_end = Instant::now();
+        obs.pages_after_last_gc = reserved_pages;
+
+        let gc_time = obs.last_gc_end.duration_since(obs.gc_start).as_secs_f64();
+        let traced_pages = bytes_to_pages(self.traced_bytes.load(Ordering::Relaxed));
+        // The plans that do not trace objects with the common work packets (e.g. reference counting)
+        // do not report the traced objects, so we do not know their GC speed.
+        if gc_time > 0f64 && traced_pages > 0 {
+            obs.gc_speed = Some(smooth(obs.gc_speed, traced_pages as f64 / gc_time));
+        }
+
+        let (alloc_rate, gc_speed) = match (obs.alloc_rate, obs.gc_speed) {
+            (Some(g), Some(s)) if s > 0f64 => (g, s),
+            _ => return,
+        };
+        let old_pages = heap.get_total_pages();
+        let new_pages =
+            self.new_heap_pages(reserved_pages, alloc_rate, gc_speed, old_pages, full_heap);
+        if new_pages != old_pages {
+            info!(
+                "Resize heap from {} pages to {} pages (live = {} pages, before GC = {} pages)",
+                old_pages, new_pages, reserved_pages, obs.pages_before_gc
+            );
+            heap.total_pages.store(new_pages, Ordering::Relaxed);
+        }
+    }
+
+    /// Calculate the heap size in pages after a GC with the MemBalancer rule. `live_pages` is the number of
+    /// reserved pages after the GC, and `old_pages` is the current heap size.
+    fn new_heap_pages(
+        &self,
+        live_pages: usize,
+        alloc_rate: f64,
+        gc_speed: f64,
+        old_pages: usize,
+        full_heap: bool,
+    ) -> usize {
+        let live = live_pages as f64;
+        let extra = (live * alloc_rate / (TUNING_FACTOR * gc_speed)).sqrt();
+        let mut new_pages = live_pages + extra as usize;
+        // A nursery GC does not tell us the live size of the whole heap, so we do not shrink the heap after it.
+        if !full_heap {
+            new_pages = new_pages.max(old_pages);
+        }
+        new_pages.max(self.min_pages).min(self.max_pages)
+    }
+}
+
+impl Default for HeapSizing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn smooth(old: Option<f64>, new: f64) -> f64 {
+    match old {
+        Some(old) => old * (1f64 - SMOOTHING_FACTOR) + new * SMOOTHING_FACTOR,
+        None => new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::constants::BYTES_IN_PAGE;
+    use crate::util::test_util::serial_test;
+
+    const MB: usize = 1024 * 1024;
+
+    fn init(heap_size: usize, min_heap: usize, max_heap: usize) -> (HeapSizing, usize) {
+        let mut options = Options::default();
+        options.variable_size_heap.value = true;
+        options.min_heap.value = min_heap;
+        options.max_heap.value = max_heap;
+        let mut sizing = HeapSizing::new();
+        let pages = sizing.init(heap_size, &options);
+        (sizing, pages)
+    }
+
+    #[test]
+    fn init_with_heap_size_only() {
+        serial_test(|| {
+            let (sizing, pages) = init(64 * MB, 0, 0);
+            assert_eq!(pages, 64 * MB / BYTES_IN_PAGE);
+            assert!(!sizing.is_variable());
+        })
+    }
+
+    #[test]
+    fn init_clamps_heap_size() {
+        serial_test(|| {
+            let (sizing, pages) = init(16 * MB, 32 * MB, 128 * MB);
+            assert_eq!(pages, 32 * MB / BYTES_IN_PAGE);
+            assert!(sizing.is_variable());
+
+            let (_, pages) = init(256 * MB, 32 * MB, 128 * MB);
+            assert_eq!(pages, 128 * MB / BYTES_IN_PAGE);
+        })
+    }
+
+    #[test]
+    fn init_with_one_bound() {
+        serial_test(|| {
+            // The heap size given to gc_init is the other bound.
+            let (sizing, pages) = init(64 * MB, 0, 128 * MB);
+            assert_eq!(pages, 64 * MB / BYTES_IN_PAGE);
+            assert_eq!(sizing.min_pages, 64 * MB / BYTES_IN_PAGE);
+            assert_eq!(sizing.max_pages, 128 * MB / BYTES_IN_PAGE);
+
+            let (sizing, pages) = init(64 * MB, 32 * MB, 0);
+            assert_eq!(pages, 64 * MB / BYTES_IN_PAGE);
+            assert_eq!(sizing.min_pages, 32 * MB / BYTES_IN_PAGE);
+            assert_eq!(sizing.max_pages, 64 * MB / BYTES_IN_PAGE);
+        })
+    }
+
+    #[test]
+    fn square_root_rule() {
+        serial_test(|| {
+            let (sizing, _) = init(1024 * BYTES_IN_PAGE, 1, 1 << 30);
+            // extra = sqrt(1000 * 2000 / (0.2 * 1000)) = 100
+            assert_eq!(sizing.new_heap_pages(1000, 2000f64, 1000f64, 0, true), 1100);
+            // A faster GC gives less extra space: sqrt(1000 * 2000 / (0.2 * 1000000)) = 3.16
+            assert_eq!(
+                sizing.new_heap_pages(1000, 2000f64, 1000000f64, 0, true),
+                1003
+            );
+        })
+    }
+
+    #[test]
+    fn no_shrinking_after_nursery_gc() {
+        serial_test(|| {
+            let (sizing, _) = init(1024 * BYTES_IN_PAGE, 1, 1 << 30);
+            assert_eq!(
+                sizing.new_heap_pages(1000, 2000f64, 1000f64, 5000, true),
+                1100
+            );
+            assert_eq!(
+                sizing.new_heap_pages(1000, 2000f64, 1000f64, 5000, false),
+                5000
+            );
+            assert_eq!(
+                sizing.new_heap_pages(1000, 2000f64, 1000f64, 500, false),
+                1100
+            );
+        })
+    }
+
+    #[test]
+    fn new_heap_size_within_bounds() {
+        serial_test(|| {
+            let (sizing, _) = init(0, 2000 * BYTES_IN_PAGE, 4000 * BYTES_IN_PAGE);
+            assert_eq!(sizing.new_heap_pages(1000, 2000f64, 1000f64, 0, true), 2000);
+            assert_eq!(sizing.new_heap_pages(5000, 2000f64, 1000f64, 0, true), 4000);
+        })
+    }
+
+    #[test]
+    fn smoothing() {
+        assert_eq!(smooth(None, 10f64), 10f64);
+        assert_eq!(smooth(Some(10f64), 20f64), 15f64);
+    }
+}
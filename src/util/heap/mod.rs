@@ -1,16 +1,22 @@
 mod accounting;
+mod cgroup;
 #[macro_use]
 pub mod layout;
 pub mod freelistpageresource;
 mod heap_meta;
+mod heap_sizing;
 pub mod monotonepageresource;
 pub mod pageresource;
 pub mod space_descriptor;
+pub(crate) mod uncommit;
 mod vmrequest;
+pub(crate) mod zeroing;
 
 pub use self::accounting::PageAccounting;
+pub use self::cgroup::CgroupMemoryLimit;
 pub use self::freelistpageresource::FreeListPageResource;
 pub use self::heap_meta::HeapMeta;
+pub use self::heap_sizing::HeapSizing;
 pub use self::monotonepageresource::MonotonePageResource;
 pub use self::pageresource::PageResource;
 pub use self::vmrequest::VMRequest;
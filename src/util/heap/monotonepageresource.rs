@@ -14,6 +14,8 @@ use super::pageresource::{PRAllocFail, PRAllocResult};
 use super::PageResource;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::space_descriptor::SpaceDescriptor;
+use crate::util::heap::uncommit::UNCOMMITTER;
+use crate::util::heap::zeroing::ZEROER;
 use crate::vm::VMBinding;
 use std::marker::PhantomData;
 
@@ -285,6 +287,9 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
                 _ => unreachable!(),
             };
             let pages = bytes_to_pages(_start - start);
+            if guard.cursor > cursor {
+                self.release_pages_extent(cursor, guard.cursor - cursor);
+            }
             self.common.accounting.reset();
             self.common.accounting.reserve_and_commit(pages);
             guard.current_chunk = chunk;
@@ -296,12 +301,13 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
 
     #[inline]
     unsafe fn release_pages(&self, guard: &mut MutexGuard<MonotonePageResourceSync>) {
-        // TODO: concurrent zeroing
         if self.common().contiguous {
-            guard.cursor = match guard.conditional {
+            let start = match guard.conditional {
                 MonotonePageResourceConditional::Contiguous { start: _start, .. } => _start,
                 _ => unreachable!(),
             };
+            self.release_pages_extent(start, guard.cursor - start);
+            guard.cursor = start;
         } else if !guard.cursor.is_zero() {
             let bytes = guard.cursor - guard.current_chunk;
             self.release_pages_extent(guard.current_chunk, bytes);
@@ -317,9 +323,11 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
         }
     }
 
-    fn release_pages_extent(&self, _first: Address, bytes: usize) {
+    fn release_pages_extent(&self, first: Address, bytes: usize) {
         let pages = crate::util::conversions::bytes_to_pages(bytes);
         debug_assert!(bytes == crate::util::conversions::pages_to_bytes(pages));
+        UNCOMMITTER.release(first, bytes);
+        ZEROER.release(first, bytes);
         // FIXME ZERO_PAGES_ON_RELEASE
         // FIXME Options.protectOnRelease
         // FIXME VM.events.tracePageReleased
@@ -0,0 +1,269 @@
+//! Return the memory of free pages to the OS.
+//!
+//! Page resources tell the [`Uncommitter`] when they release pages, and spaces tell it when they acquire
+//! pages again. The pages that stay free are still committed (i.e. backed by physical memory) until they
+//! are uncommitted with `madvise(MADV_DONTNEED)` at the end of a GC. If the option `uncommit_delay_ms`
+//! is not 0, only the pages that have been free for at least that long are uncommitted, so pages that
+//! are quickly reused (e.g. a nursery) do not need to be faulted in again. In that case, a background
+//! thread uncommits the pages when their delay expires, so the memory is returned even if there is no GC
+//! for a long time.
+
+use crate::util::conversions::{bytes_to_pages, pages_to_bytes};
+use crate::util::heap::zeroing::ZEROER;
+use crate::util::memory;
+use crate::util::options::Options;
+use crate::util::Address;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    /// The global uncommitter. Like the page resources, it is shared by all the spaces.
+    pub static ref UNCOMMITTER: Uncommitter = Uncommitter::new();
+}
+
+/// A range of free pages that are still committed.
+struct FreeRange {
+    end: Address,
+    /// When the range was released. For a coalesced range, this is the time of the latest release.
+    released: Instant,
+}
+
+pub struct Uncommitter {
+    /// Should we uncommit free pages?
+    enabled: AtomicBool,
+    /// How long (in milliseconds) pages need to stay free before we uncommit them
+    delay_ms: AtomicUsize,
+    /// Free ranges that are still committed, indexed by their start addresses. The ranges do not overlap.
+    free_ranges: Mutex<BTreeMap<Address, FreeRange>>,
+    /// Wakes up the background thread when pages are released.
+    released: Condvar,
+    /// The number of pages in `free_ranges`
+    free_committed_pages: AtomicUsize,
+}
+
+impl Uncommitter {
+    fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            delay_ms: AtomicUsize::new(0),
+            free_ranges: Mutex::new(BTreeMap::new()),
+            released: Condvar::new(),
+            free_committed_pages: AtomicUsize::new(0),
+        }
+    }
+
+    /// Configure the uncommitter from the options. This is called once in `gc_init()`. If the pages are
+    /// uncommitted after a delay, this starts the background thread that uncommits them.
+    pub fn configure(&'static self, options: &Options) {
+        self.enabled
+            .store(*options.uncommit_free_pages, Ordering::Relaxed);
+        self.delay_ms
+            .store(*options.uncommit_delay_ms, Ordering::Relaxed);
+        if *options.uncommit_free_pages && *options.uncommit_delay_ms != 0 {
+            std::thread::Builder::new()
+                .name("MMTk uncommitter".to_owned())
+                .spawn(move || self.run())
+                .expect("Failed to spawn the uncommitter thread");
+        }
+    }
+
+    #[inline(always)]
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms.load(Ordering::Relaxed) as u64)
+    }
+
+    /// The loop of the background thread. It sleeps until the earliest free range expires, or until
+    /// more pages are released, and uncommits the expired ranges.
+    fn run(&self) {
+        let delay = self.delay();
+        let mut ranges = self.free_ranges.lock().unwrap();
+        loop {
+            self.uncommit_expired(&mut ranges, Instant::now(), delay);
+            let next_expiry = ranges.values().map(|range| range.released + delay).min();
+            ranges = match next_expiry {
+                Some(time) => {
+                    let timeout = time.saturating_duration_since(Instant::now());
+                    self.released.wait_timeout(ranges, timeout).unwrap().0
+                }
+                None => self.released.wait(ranges).unwrap(),
+            };
+        }
+    }
+
+    /// The number of pages that are free but still committed.
+    pub fn free_committed_pages(&self) -> usize {
+        self.free_committed_pages.load(Ordering::Relaxed)
+    }
+
+    /// Pages in the range are released by a page resource.
+    pub fn release(&self, start: Address, bytes: usize) {
+        if !self.is_enabled() || bytes == 0 {
+            return;
+        }
+        let mut start = start;
+        let mut end = start + bytes;
+        let mut ranges = self.free_ranges.lock().unwrap();
+        // Coalesce with the adjacent ranges.
+        if let Some((&prev_start, prev)) = ranges.range(..start).next_back() {
+            debug_assert!(prev.end <= start, "{} is already free", start);
+            if prev.end == start {
+                start = prev_start;
+                ranges.remove(&prev_start);
+            }
+        }
+        if let Some(next) = ranges.remove(&end) {
+            end = next.end;
+        }
+        debug_assert!(
+            ranges.range(start..end).next().is_none(),
+            "Part of {}..{} is already free",
+            start,
+            end
+        );
+        ranges.insert(
+            start,
+            FreeRange {
+                end,
+                released: Instant::now(),
+            },
+        );
+        self.free_committed_pages
+            .fetch_add(bytes_to_pages(bytes), Ordering::Relaxed);
+        self.released.notify_one();
+    }
+
+    /// Pages in the range are acquired by a space. This must be called before the pages are used.
+    pub fn reuse(&self, start: Address, bytes: usize) {
+        if !self.is_enabled() {
+            return;
+        }
+        let end = start + bytes;
+        let mut ranges = self.free_ranges.lock().unwrap();
+        let overlapping: Vec<Address> = ranges
+            .range(..end)
+            .rev()
+            .take_while(|(_, range)| range.end > start)
+            .map(|(&range_start, _)| range_start)
+            .collect();
+        for range_start in overlapping {
+            let range = ranges.remove(&range_start).unwrap();
+            let reused_start = range_start.max(start);
+            let reused_end = range.end.min(end);
+            self.free_committed_pages
+                .fetch_sub(bytes_to_pages(reused_end - reused_start), Ordering::Relaxed);
+            // Put back the parts that are still free.
+            if range_start < start {
+                ranges.insert(
+                    range_start,
+                    FreeRange {
+                        end: start,
+                        released: range.released,
+                    },
+                );
+            }
+            if range.end > end {
+                ranges.insert(
+                    end,
+                    FreeRange {
+                        end: range.end,
+                        released: range.released,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Uncommit the free pages that have been free for long enough. This is called at the end of a GC.
+    pub fn uncommit_free_pages(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut ranges = self.free_ranges.lock().unwrap();
+        self.uncommit_expired(&mut ranges, Instant::now(), self.delay());
+    }
+
+    /// Uncommit the ranges that have been free for at least `delay` at `now`. The caller holds the lock of
+    /// the free ranges while uncommitting, so no space can reuse the pages in the meantime.
+    fn uncommit_expired(
+        &self,
+        ranges: &mut MutexGuard<BTreeMap<Address, FreeRange>>,
+        now: Instant,
+        delay: Duration,
+    ) {
+        let expired: Vec<(Address, Address)> = ranges
+            .iter()
+            .filter(|(_, range)| now.saturating_duration_since(range.released) >= delay)
+            .map(|(&start, range)| (start, range.end))
+            .collect();
+        let mut uncommitted_pages = 0;
+        for (start, end) in expired {
+            ranges.remove(&start);
+            if let Err(e) = memory::madvise_dontneed(start, end - start) {
+                warn!("Failed to uncommit {}..{}: {}", start, end, e);
+            }
+            ZEROER.forget(start, end - start);
+            uncommitted_pages += bytes_to_pages(end - start);
+        }
+        self.free_committed_pages
+            .fetch_sub(uncommitted_pages, Ordering::Relaxed);
+        if uncommitted_pages > 0 {
+            debug!(
+                "Uncommitted {} bytes of free pages",
+                pages_to_bytes(uncommitted_pages)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::constants::BYTES_IN_PAGE;
+    use crate::util::heap::layout::vm_layout_constants::HEAP_START;
+
+    fn page(i: usize) -> Address {
+        HEAP_START + i * BYTES_IN_PAGE
+    }
+
+    #[test]
+    fn release_and_reuse() {
+        let uncommitter = Uncommitter::new();
+        uncommitter.enabled.store(true, Ordering::Relaxed);
+        uncommitter.release(page(0), 4 * BYTES_IN_PAGE);
+        uncommitter.release(page(4), 4 * BYTES_IN_PAGE);
+        assert_eq!(uncommitter.free_committed_pages(), 8);
+        // The two releases are coalesced into one range.
+        assert_eq!(uncommitter.free_ranges.lock().unwrap().len(), 1);
+
+        // Reusing pages in the middle splits the range.
+        uncommitter.reuse(page(2), 3 * BYTES_IN_PAGE);
+        assert_eq!(uncommitter.free_committed_pages(), 5);
+        {
+            let ranges = uncommitter.free_ranges.lock().unwrap();
+            let ranges: Vec<(Address, Address)> = ranges.iter().map(|(&s, r)| (s, r.end)).collect();
+            assert_eq!(ranges, vec![(page(0), page(2)), (page(5), page(8))]);
+        }
+
+        // Reusing pages across both ranges removes the overlapping parts.
+        uncommitter.reuse(page(1), 5 * BYTES_IN_PAGE);
+        assert_eq!(uncommitter.free_committed_pages(), 3);
+
+        // Reusing pages that are not free does nothing.
+        uncommitter.reuse(page(10), BYTES_IN_PAGE);
+        assert_eq!(uncommitter.free_committed_pages(), 3);
+    }
+
+    #[test]
+    fn disabled() {
+        let uncommitter = Uncommitter::new();
+        uncommitter.release(page(0), 4 * BYTES_IN_PAGE);
+        assert_eq!(uncommitter.free_committed_pages(), 0);
+        assert!(uncommitter.free_ranges.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+//! Zero the memory of pages before they are used for allocation.
+//!
+//! Spaces that need zeroed memory (e.g. the nursery and the Immix space) zero the pages when they acquire
+//! them, and the Immix allocator zeroes the recyclable lines it allocates into. The option `nursery_zeroing`
+//! selects how the memory is zeroed:
+//! * `Temporal`: The allocating thread zeroes the memory with normal stores.
+//! * `Nontemporal`: The allocating thread zeroes the memory with non-temporal stores, which bypass the cache.
+//! * `Concurrent`: A background thread zeroes the pages released by a GC while the mutators run, so the
+//!   pages are likely to be zeroed already when a space acquires them. Allocators only get pages zeroed by
+//!   the background thread: if a space acquires pages that are not zeroed yet, the background thread zeroes
+//!   them first, and the space waits for it.
+//! * `Adaptive`: Like `Concurrent` if there is more than one CPU to run the background thread. The allocating
+//!   thread uses normal stores for small ranges that it will soon allocate into, and non-temporal stores for
+//!   large ranges.
+
+use crate::util::constants::LOG_BYTES_IN_KBYTE;
+use crate::util::memory;
+use crate::util::options::{NurseryZeroingOptions, Options};
+use crate::util::Address;
+use atomic::Atomic;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+
+lazy_static! {
+    /// The global zeroer. Like the page resources, it is shared by all the spaces.
+    pub static ref ZEROER: Zeroer = Zeroer::new();
+}
+
+/// The zeroing thread zeroes at most this many bytes at a time, so a space that acquires the pages
+/// does not wait for long.
+const ZEROING_CHUNK_BYTES: usize = 256 << LOG_BYTES_IN_KBYTE;
+/// With the `Adaptive` strategy, ranges larger than this are zeroed with non-temporal stores.
+const ADAPTIVE_TEMPORAL_LIMIT: usize = 256 << LOG_BYTES_IN_KBYTE;
+
+/// Non-overlapping address ranges, from their starts to their ends
+type Ranges = BTreeMap<Address, Address>;
+
+struct ZeroingState {
+    /// Free ranges that are not zeroed yet
+    dirty: Ranges,
+    /// Free ranges that are zeroed by the zeroing thread
+    zeroed: Ranges,
+    /// Ranges acquired by spaces that are waiting for the zeroing thread to zero them. The zeroing
+    /// thread zeroes them before the free ranges, even during a GC.
+    urgent: Ranges,
+    /// The range that the zeroing thread is zeroing. It is in neither `dirty` nor `zeroed`.
+    in_progress: Option<(Address, Address)>,
+    /// Should the zeroing thread run? It only runs while the mutators run, so it does not compete with GC workers.
+    active: bool,
+}
+
+pub struct Zeroer {
+    strategy: Atomic<NurseryZeroingOptions>,
+    /// Do we zero free pages in a background thread?
+    background: AtomicBool,
+    state: Mutex<ZeroingState>,
+    /// Notified when the zeroing thread may run, and when it finishes zeroing a range.
+    cond: Condvar,
+}
+
+impl Zeroer {
+    fn new() -> Self {
+        Self {
+            strategy: Atomic::new(NurseryZeroingOptions::Temporal),
+            background: AtomicBool::new(false),
+            state: Mutex::new(ZeroingState {
+                dirty: BTreeMap::new(),
+                zeroed: BTreeMap::new(),
+                urgent: BTreeMap::new(),
+                in_progress: None,
+                active: true,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// Set the zeroing strategy from the options, and start the zeroing thread if the strategy needs it.
+    pub fn configure(&'static self, options: &Options) {
+        let strategy = *options.nursery_zeroing;
+        self.strategy.store(strategy, Ordering::Relaxed);
+        let background = match strategy {
+            NurseryZeroingOptions::Temporal | NurseryZeroingOptions::Nontemporal => false,
+            NurseryZeroingOptions::Concurrent => true,
+            NurseryZeroingOptions::Adaptive => num_cpus::get() > 1,
+        };
+        if background && !self.background.swap(true, Ordering::SeqCst) {
+            std::thread::Builder::new()
+                .name("MMTk Zeroing Thread".to_string())
+                .spawn(move || self.run())
+                .expect("Failed to spawn the zeroing thread");
+        }
+    }
+
+    /// Zero the range on the current thread, with the stores that the strategy prefers.
+    pub fn zero(&self, start: Address, bytes: usize) {
+        match self.strategy.load(Ordering::Relaxed) {
+            NurseryZeroingOptions::Temporal | NurseryZeroingOptions::Concurrent => {
+                memory::zero(start, bytes)
+            }
+            NurseryZeroingOptions::Nontemporal => memory::zero_nontemporal(start, bytes),
+            NurseryZeroingOptions::Adaptive => {
+                if bytes > ADAPTIVE_TEMPORAL_LIMIT {
+                    memory::zero_nontemporal(start, bytes)
+                } else {
+                    memory::zero(start, bytes)
+                }
+            }
+        }
+    }
+
+    /// Pages in the range are released by a page resource. They may be zeroed in the background from now on.
+    pub fn release(&self, start: Address, bytes: usize) {
+        if !self.background.load(Ordering::Relaxed) || bytes == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        debug_assert!(!overlaps(&state.zeroed, start, start + bytes));
+        insert_range(&mut state.dirty, start, start + bytes);
+    }
+
+    /// Pages in the range are acquired by a space. This must be called for every space before the pages
+    /// are used, so the zeroing thread stops zeroing them. If `needs_zeroing` is true, the pages are zeroed
+    /// when this returns. With a background zeroing thread, the pages that are not zeroed yet are zeroed
+    /// by the zeroing thread rather than the current thread.
+    pub fn reuse(&self, start: Address, bytes: usize, needs_zeroing: bool) {
+        if !self.background.load(Ordering::Relaxed) {
+            if needs_zeroing {
+                self.zero(start, bytes);
+            }
+            return;
+        }
+        let end = start + bytes;
+        let mut state = self.state.lock().unwrap();
+        // Wait if the zeroing thread is writing to the pages.
+        while matches!(state.in_progress, Some((s, e)) if s < end && start < e) {
+            state = self.cond.wait(state).unwrap();
+        }
+        remove_range(&mut state.dirty, start, end);
+        let zeroed = remove_range(&mut state.zeroed, start, end);
+        if !needs_zeroing {
+            return;
+        }
+        // Ask the zeroing thread to zero the gaps between the ranges that are zeroed already.
+        let mut cursor = start;
+        for (zeroed_start, zeroed_end) in zeroed {
+            if cursor < zeroed_start {
+                insert_range(&mut state.urgent, cursor, zeroed_start);
+            }
+            cursor = zeroed_end;
+        }
+        if cursor < end {
+            insert_range(&mut state.urgent, cursor, end);
+        }
+        self.cond.notify_all();
+        while overlaps(&state.urgent, start, end)
+            || matches!(state.in_progress, Some((s, e)) if s < end && start < e)
+        {
+            state = self.cond.wait(state).unwrap();
+        }
+    }
+
+    /// Pages in the range are no longer backed by memory (see [`crate::util::heap::uncommit`]). Zeroing them
+    /// in the background would commit them again.
+    pub fn forget(&self, start: Address, bytes: usize) {
+        if !self.background.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        remove_range(&mut state.dirty, start, start + bytes);
+    }
+
+    /// A GC starts. The zeroing thread stops after the range it is zeroing.
+    pub fn on_gc_start(&self) {
+        if self.background.load(Ordering::Relaxed) {
+            self.state.lock().unwrap().active = false;
+        }
+    }
+
+    /// A GC ends. The zeroing thread zeroes the pages released by the GC.
+    pub fn on_gc_end(&self) {
+        if self.background.load(Ordering::Relaxed) {
+            self.state.lock().unwrap().active = true;
+            self.cond.notify_all();
+        }
+    }
+
+    /// The loop of the zeroing thread.
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            // Zero the pages that spaces are waiting for first.
+            let (ranges, urgent) = if !state.urgent.is_empty() {
+                (&mut state.urgent, true)
+            } else if state.active && !state.dirty.is_empty() {
+                (&mut state.dirty, false)
+            } else {
+                state = self.cond.wait(state).unwrap();
+                continue;
+            };
+            let (&start, &end) = ranges.iter().next().unwrap();
+            let end = end.min(start + ZEROING_CHUNK_BYTES);
+            remove_range(ranges, start, end);
+            state.in_progress = Some((start, end));
+            drop(state);
+
+            memory::zero_nontemporal(start, end - start);
+
+            state = self.state.lock().unwrap();
+            state.in_progress = None;
+            // The urgent ranges are already acquired by spaces, so they are not free.
+            if !urgent {
+                insert_range(&mut state.zeroed, start, end);
+            }
+            // Wake up the spaces waiting for the range.
+            self.cond.notify_all();
+        }
+    }
+}
+
+fn overlaps(ranges: &Ranges, start: Address, end: Address) -> bool {
+    ranges
+        .range(..end)
+        .next_back()
+        .map_or(false, |(_, &range_end)| range_end > start)
+}
+
+/// Insert a range that does not overlap with the existing ranges, and coalesce it with the adjacent ranges.
+fn insert_range(ranges: &mut Ranges, start: Address, end: Address) {
+    let mut start = start;
+    let mut end = end;
+    if let Some((&prev_start, &prev_end)) = ranges.range(..start).next_back() {
+        if prev_end == start {
+            start = prev_start;
+            ranges.remove(&prev_start);
+        }
+    }
+    if let Some(next_end) = ranges.remove(&end) {
+        end = next_end;
+    }
+    ranges.insert(start, end);
+}
+
+/// Remove the parts of the existing ranges that overlap with the given range. Return the removed parts
+/// in address order.
+fn remove_range(ranges: &mut Ranges, start: Address, end: Address) -> Vec<(Address, Address)> {
+    let overlapping: Vec<(Address, Address)> = ranges
+        .range(..end)
+        .rev()
+        .take_while(|(_, range_end)| **range_end > start)
+        .map(|(&range_start, &range_end)| (range_start, range_end))
+        .collect();
+    let mut removed = vec![];
+    for (range_start, range_end) in overlapping.into_iter().rev() {
+        ranges.remove(&range_start);
+        // Put back the parts that are outside the given range.
+        if range_start < start {
+            ranges.insert(range_start, start);
+        }
+        if range_end > end {
+            ranges.insert(end, range_end);
+        }
+        removed.push((range_start.max(start), range_end.min(end)));
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::constants::BYTES_IN_PAGE;
+    use crate::util::heap::layout::vm_layout_constants::HEAP_START;
+
+    fn page(i: usize) -> Address {
+        HEAP_START + i * BYTES_IN_PAGE
+    }
+
+    #[test]
+    fn insert_and_remove_ranges() {
+        let mut ranges = Ranges::new();
+        insert_range(&mut ranges, page(0), page(4));
+        insert_range(&mut ranges, page(4), page(8));
+        insert_range(&mut ranges, page(10), page(12));
+        // The adjacent ranges are coalesced.
+        assert_eq!(
+            ranges.clone().into_iter().collect::<Vec<_>>(),
+            vec![(page(0), page(8)), (page(10), page(12))]
+        );
+        assert!(overlaps(&ranges, page(7), page(9)));
+        assert!(!overlaps(&ranges, page(8), page(10)));
+
+        // Removing a range across both ranges returns the overlapping parts, and keeps the rest.
+        let removed = remove_range(&mut ranges, page(2), page(11));
+        assert_eq!(removed, vec![(page(2), page(8)), (page(10), page(11))]);
+        assert_eq!(
+            ranges.into_iter().collect::<Vec<_>>(),
+            vec![(page(0), page(2)), (page(11), page(12))]
+        );
+    }
+    /// Create a zeroer with a background zeroing thread.
+    fn background_zeroer() -> &'static Zeroer {
+        let zeroer: &'static Zeroer = Box::leak(Box::new(Zeroer::new()));
+        zeroer
+            .strategy
+            .store(NurseryZeroingOptions::Concurrent, Ordering::Relaxed);
+        zeroer.background.store(true, Ordering::SeqCst);
+        std::thread::spawn(move || zeroer.run());
+        zeroer
+    }
+
+    fn is_zeroed(memory: &[u8], from: usize, to: usize) -> bool {
+        memory[from..to].iter().all(|b| *b == 0)
+    }
+
+    #[test]
+    fn zeroed_before_reuse() {
+        let zeroer = background_zeroer();
+        let bytes = 16 * BYTES_IN_PAGE;
+        let mut memory = vec![0xffu8; bytes];
+        let start = Address::from_mut_ptr(memory.as_mut_ptr());
+
+        // The free pages are zeroed by the zeroing thread while it is active.
+        zeroer.release(start, bytes);
+        zeroer.reuse(start, 4 * BYTES_IN_PAGE, true);
+        assert!(is_zeroed(&memory, 0, 4 * BYTES_IN_PAGE));
+
+        // During a GC, the zeroing thread only zeroes the pages that are acquired.
+        zeroer.on_gc_start();
+        memory[8 * BYTES_IN_PAGE..].fill(0xff);
+        zeroer.reuse(start + 8 * BYTES_IN_PAGE, 8 * BYTES_IN_PAGE, true);
+        assert!(is_zeroed(&memory, 8 * BYTES_IN_PAGE, bytes));
+        zeroer.on_gc_end();
+    }
+
+    #[test]
+    fn not_zeroed_if_not_needed() {
+        let zeroer = background_zeroer();
+        let bytes = 4 * BYTES_IN_PAGE;
+        let mut memory = vec![0xffu8; bytes];
+        let start = Address::from_mut_ptr(memory.as_mut_ptr());
+
+        // Released while inactive, so the zeroing thread does not zero the pages before they are acquired.
+        zeroer.on_gc_start();
+        zeroer.release(start, bytes);
+        zeroer.reuse(start, bytes, false);
+        zeroer.on_gc_end();
+        assert!(memory.iter().all(|b| *b == 0xff));
+        assert!(zeroer.state.lock().unwrap().dirty.is_empty());
+    }
+}
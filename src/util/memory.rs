@@ -17,6 +17,36 @@ pub fn zero(start: Address, len: usize) {
     wrap_libc_call(&|| unsafe { libc::memset(ptr, 0, len) }, ptr).unwrap()
 }
 
+/// Zero the given range with non-temporal stores, which bypass the cache. For a large range that will
+/// not be accessed soon, this is faster than [`zero`], and it does not evict other data from the cache.
+/// On architectures other than x86_64, this is the same as [`zero`].
+pub fn zero_nontemporal(start: Address, len: usize) {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        use std::arch::x86_64::{__m128i, _mm_setzero_si128, _mm_sfence, _mm_stream_si128};
+        const ALIGN: usize = 16;
+        let end = start + len;
+        let aligned_start = start.align_up(ALIGN);
+        let aligned_end = end.align_down(ALIGN);
+        if aligned_start >= aligned_end {
+            return zero(start, len);
+        }
+        // Non-temporal stores need to be aligned. Zero the unaligned head and tail as usual.
+        zero(start, aligned_start - start);
+        let zeros = _mm_setzero_si128();
+        let mut cursor = aligned_start;
+        while cursor < aligned_end {
+            _mm_stream_si128(cursor.to_mut_ptr::<__m128i>(), zeros);
+            cursor += ALIGN;
+        }
+        // Non-temporal stores are weakly ordered. Make them visible before we return.
+        _mm_sfence();
+        zero(aligned_end, end - aligned_end);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    zero(start, len);
+}
+
 /// Demand-zero mmap:
 /// This function mmaps the memory and guarantees to zero all mapped memory.
 /// This function WILL overwrite existing memory mapping. The user of this function
@@ -83,6 +113,15 @@ pub fn munmap(start: Address, size: usize) -> Result<()> {
     wrap_libc_call(&|| unsafe { libc::munmap(start.to_mut_ptr(), size) }, 0)
 }
 
+/// Return the physical memory of the given range to the OS. The range stays mapped, and reads from the range
+/// will see zeros afterwards.
+pub fn madvise_dontneed(start: Address, size: usize) -> Result<()> {
+    wrap_libc_call(
+        &|| unsafe { libc::madvise(start.to_mut_ptr(), size, libc::MADV_DONTNEED) },
+        0,
+    )
+}
+
 /// Properly handle errors from a mmap Result, including invoking the binding code in the case of
 /// an OOM error.
 pub fn handle_mmap_error<VM: VMBinding>(error: Error, tls: VMThread) -> ! {
@@ -9,4 +9,9 @@ impl VMGlobalLogBitSpec {
     pub fn mark_as_unlogged<VM: VMBinding>(&self, object: ObjectReference, order: Ordering) {
         store_metadata::<VM>(self, object, 1, None, Some(order))
     }
+
+    /// Mark the log bit as logged (0 means logged)
+    pub fn mark_as_logged<VM: VMBinding>(&self, object: ObjectReference, order: Ordering) {
+        store_metadata::<VM>(self, object, 0, None, Some(order))
+    }
 }
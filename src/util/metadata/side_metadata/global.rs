@@ -287,7 +287,13 @@ impl SideMetadataContext {
             }
             #[cfg(target_pointer_width = "32")]
             {
-                lsize += metadata_bytes_per_chunk(spec.log_bytes_in_region, spec.log_num_of_bits);
+                // A global spec used by a policy is laid out with the other global specs.
+                if spec.is_global {
+                    try_mmap_contiguous_metadata_space(start, size, spec, no_reserve)?;
+                } else {
+                    lsize +=
+                        metadata_bytes_per_chunk(spec.log_bytes_in_region, spec.log_num_of_bits);
+                }
             }
         }
 
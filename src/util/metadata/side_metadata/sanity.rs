@@ -237,7 +237,12 @@ impl SideMetadataSanity {
     /// Returns `Ok(())` if no issue is detected, or `Err` otherwise.
     ///
     fn verify_local_specs(&self) -> Result<()> {
-        let local_specs = self.get_all_specs(false);
+        // A policy may list a global spec that the plan does not use. It is laid out with the global specs.
+        let local_specs: Vec<SideMetadataSpec> = self
+            .get_all_specs(false)
+            .into_iter()
+            .filter(|spec| !spec.is_global)
+            .collect();
 
         verify_local_specs_size(&local_specs)?;
 
@@ -323,10 +328,11 @@ impl SideMetadataSanity {
         }
 
         for spec in &metadata_context.local {
-            // Make sure all input local specs are actually local
+            // A global spec may be used by a policy even if the plan does not use it, but it must not
+            // be listed by both the plan and the policy.
             assert!(
-                !spec.is_global,
-                "Global spec {:#?} detected in the policy-specific specs: {:#?}",
+                !spec.is_global || !metadata_context.global.contains(spec),
+                "Global spec {:#?} detected in both the global specs and the policy-specific specs: {:#?}",
                 spec, metadata_context.local
             );
             // The first call from each policy inserts the relevant (spec, hashmap) pair.
@@ -334,7 +340,7 @@ impl SideMetadataSanity {
             // This should work with multi mmtk instances, because the local side metadata specs are assumed to be constant per policy.
             if first_call {
                 // initialise the related hashmap
-                content_sanity_map.insert(*spec, HashMap::new());
+                content_sanity_map.entry(*spec).or_insert_with(HashMap::new);
             } else if !self
                 .specs_sanity_map
                 .get(policy_name)
@@ -58,6 +58,14 @@ define_side_metadata_specs!(
     ALLOC_BIT       = (global: true, log_num_of_bits: 0, log_bytes_in_region: LOG_MIN_OBJECT_SIZE as usize),
     // Track chunks used by (malloc) marksweep
     MS_ACTIVE_CHUNK = (global: true, log_num_of_bits: 3, log_bytes_in_region: LOG_BYTES_IN_CHUNK as usize),
+    // Reference counts of objects (reference counting)
+    RC_COUNT        = (global: true, log_num_of_bits: 1, log_bytes_in_region: LOG_MIN_OBJECT_SIZE as usize),
+    // Number of live objects that span each immix line (reference counting)
+    RC_LINE_COUNT   = (global: true, log_num_of_bits: 3, log_bytes_in_region: crate::policy::immix::line::Line::LOG_BYTES),
+    // Log the slots remembered by the field barrier
+    FIELD_LOG_BIT   = (global: true, log_num_of_bits: 0, log_bytes_in_region: LOG_BYTES_IN_ADDRESS as usize),
+    // Dirty cards marked by the card-marking barrier
+    CARD_TABLE      = (global: true, log_num_of_bits: 3, log_bytes_in_region: LOG_CARD_BYTES),
 );
 
 // This defines all LOCAL side metadata used by mmtk-core.
@@ -75,6 +83,18 @@ define_side_metadata_specs!(
     IX_BLOCK_MARK   = (global: false, log_num_of_bits: 3, log_bytes_in_region: crate::policy::immix::block::Block::LOG_BYTES),
     // Mark chunks by immix
     IX_CHUNK_MARK   = (global: false, log_num_of_bits: 3, log_bytes_in_region: crate::policy::immix::chunk::Chunk::LOG_BYTES),
+    // Pin objects in immix
+    IX_PINNING_BIT  = (global: false, log_num_of_bits: 0, log_bytes_in_region: LOG_MIN_OBJECT_SIZE as usize),
+    // Record allocation state for (native) marksweep blocks
+    MS_BLOCK_STATE  = (global: false, log_num_of_bits: 3, log_bytes_in_region: crate::policy::marksweepspace::block::Block::LOG_BYTES),
+    // Record size class for (native) marksweep blocks
+    MS_BLOCK_SIZE_CLASS = (global: false, log_num_of_bits: 3, log_bytes_in_region: crate::policy::marksweepspace::block::Block::LOG_BYTES),
+    // Record the first free cell for (native) marksweep blocks
+    MS_BLOCK_FREE_LIST = (global: false, log_num_of_bits: LOG_BITS_IN_WORD, log_bytes_in_region: crate::policy::marksweepspace::block::Block::LOG_BYTES),
+    // Mark live objects by markcompact with side forwarding
+    MC_LIVE_BIT     = (global: false, log_num_of_bits: 0, log_bytes_in_region: LOG_MIN_OBJECT_SIZE as usize),
+    // Compacted address of the first live object in each block by markcompact with side forwarding
+    MC_FORWARDING_OFFSET = (global: false, log_num_of_bits: LOG_BITS_IN_WORD, log_bytes_in_region: crate::policy::markcompactspace::LOG_BYTES_IN_FORWARDING_BLOCK),
 );
 
 #[cfg(test)]
@@ -26,6 +26,28 @@ custom_derive! {
         PageProtect,
         Immix,
         MarkCompact,
+        ConcurrentImmix,
+        RefCount,
+        StickyImmix,
+        GenMarkSweep,
+    }
+}
+
+custom_derive! {
+    #[derive(Copy, Clone, EnumFromStr, Debug, PartialEq)]
+    pub enum MarkSweepSpaceSelector {
+        Malloc,
+        Native,
+    }
+}
+
+custom_derive! {
+    #[derive(Copy, Clone, EnumFromStr, Debug, PartialEq)]
+    pub enum GenBarrierSelector {
+        NoBarrier,
+        ObjectBarrier,
+        FieldBarrier,
+        CardBarrier,
     }
 }
 
@@ -193,6 +215,28 @@ mod process_tests {
             assert!(!success);
         })
     }
+
+    #[test]
+    fn test_process_min_heap_larger_than_max_heap() {
+        serial_test(|| {
+            let options = UnsafeOptionsWrapper::new(Options::default());
+            assert!(unsafe { options.process("max_heap", "4096") });
+            assert!(!unsafe { options.process("min_heap", "8192") });
+            assert_eq!(*options.min_heap, 0);
+            assert!(unsafe { options.process("min_heap", "4096") });
+            assert!(!unsafe { options.process("max_heap", "2048") });
+            assert_eq!(*options.max_heap, 4096);
+        })
+    }
+}
+
+impl Options {
+    /// Check the constraints between options, which cannot be checked by the validator of each option.
+    /// Setting an option fails if it breaks these constraints.
+    fn is_consistent(&self) -> bool {
+        // 0 means the bound is not set.
+        *self.min_heap == 0 || *self.max_heap == 0 || *self.min_heap <= *self.max_heap
+    }
 }
 
 fn always_valid<T>(_: &T) -> bool {
@@ -258,13 +302,18 @@ macro_rules! options {
                         // Validate
                         let validate_fn = $validator;
                         let is_valid = validate_fn(val);
-                        if is_valid {
-                            // Only set value if valid.
-                            self.$name.value = val.clone();
-                        } else {
+                        if !is_valid {
                             eprintln!("Warn: unable to set {}={:?}. Invalid value. Default value will be used.", s, val);
+                            return false;
+                        }
+                        // Only keep the value if it is consistent with the other options.
+                        let old = std::mem::replace(&mut self.$name.value, val.clone());
+                        if !self.is_consistent() {
+                            self.$name.value = old;
+                            eprintln!("Warn: unable to set {}={:?}. It conflicts with other options. The previous value will be used.", s, val);
+                            return false;
                         }
-                        is_valid
+                        true
                     } else {
                         eprintln!("Warn: unable to set {}={:?}. Cant parse value. Default value will be used.", s, val);
                         false
@@ -323,11 +372,47 @@ options! {
     // The upper bound of nursery size. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
     max_nursery:           usize                [env_var: true, command_line: true]  [|v: &usize| *v > 0 ] = DEFAULT_MAX_NURSERY,
     // The lower bound of nusery size. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
+    // If it is smaller than max_nursery, generational plans adapt the nursery size between the two bounds after each nursery GC.
+    // The default is the same as max_nursery, so the nursery size is fixed unless min_nursery is set to a smaller value.
     min_nursery:           usize                [env_var: true, command_line: true]  [|v: &usize| *v > 0 ] = DEFAULT_MIN_NURSERY,
     // Should a major GC be performed when a system GC is required?
     full_heap_system_gc:   bool                 [env_var: true, command_line: true]  [always_valid] = false,
-    // Should we shrink/grow the heap to adjust to application working set? (not supported)
+    // Should we shrink/grow the heap to adjust to application working set? The heap size only changes
+    // if min_heap and max_heap give a range of sizes.
     variable_size_heap:    bool                 [env_var: true, command_line: true]  [always_valid] = true,
+    // The lower bound of the heap size in bytes. If this is 0, the heap size given to gc_init() is used.
+    // This needs to be initialized before gc_init() (currently by setting env vars)
+    min_heap:              usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // The upper bound of the heap size in bytes. If this is 0, the heap size given to gc_init() is used.
+    // This needs to be initialized before gc_init() (currently by setting env vars). It cannot be smaller than min_heap.
+    max_heap:              usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // Should we return the memory of free pages to the OS (with madvise) at the end of each GC?
+    // This needs to be initialized before gc_init() (currently by setting env vars)
+    uncommit_free_pages:   bool                 [env_var: true, command_line: true]  [always_valid] = false,
+    // Only return the memory of pages that have been free for at least this many milliseconds. If this is 0,
+    // all the free pages are returned at the end of each GC. Otherwise, a background thread returns the pages
+    // when their delay expires. This needs to be initialized before gc_init().
+    uncommit_delay_ms:     usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // The pause time goal in milliseconds. If this is not 0, MMTk adjusts the nursery size (within min_nursery and max_nursery),
+    // defragmentation and full heap GCs to keep pauses under it on a best-effort basis. This needs to be initialized before gc_init().
+    max_pause_ms:          usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // The GC overhead limit: if the GCs take more than this percentage of time, and recover less than gc_heap_free_limit
+    // percent of the heap, in gc_overhead_limit_gcs GCs in a row, MMTk reports GCOverheadLimitExceeded to the VM.
+    // If this is 0, there is no limit. This needs to be initialized before gc_init() (currently by setting env vars)
+    gc_time_limit:         usize                [env_var: true, command_line: true]  [|v: &usize| *v <= 100] = 0,
+    // See gc_time_limit. The percentage of the heap that a GC needs to recover to not count towards the GC overhead limit.
+    gc_heap_free_limit:    usize                [env_var: true, command_line: true]  [|v: &usize| *v <= 100] = 2,
+    // See gc_time_limit. The number of GCs in a row that need to exceed the GC overhead limit.
+    gc_overhead_limit_gcs: usize                [env_var: true, command_line: true]  [|v: &usize| *v > 0] = 5,
+    // Should we size the heap from the memory limit of the cgroup (v1 or v2) that the process runs in? If a limit is found,
+    // the heap size is cgroup_heap_percent percent of the limit instead of the size given to gc_init(), and a GC is triggered
+    // when the memory usage of the cgroup approaches the limit. This needs to be initialized before gc_init().
+    use_cgroup_memory_limit: bool               [env_var: true, command_line: true]  [always_valid] = false,
+    // Where the cgroup file system is mounted. The cgroup of the process is looked up in /proc/self/cgroup, and its files are
+    // read under this directory (memory.max for cgroup v2, or memory/<cgroup>/memory.limit_in_bytes for cgroup v1).
+    cgroup_path:           String               [env_var: true, command_line: true]  [always_valid] = "/sys/fs/cgroup".to_string(),
+    // The percentage of the cgroup memory limit that is used as the heap size. See use_cgroup_memory_limit.
+    cgroup_heap_percent:   usize                [env_var: true, command_line: true]  [|v: &usize| *v > 0 && *v <= 100] = 50,
     // Should finalization be disabled?
     no_finalizer:          bool                 [env_var: true, command_line: true]  [always_valid] = false,
     // Should reference type processing be disabled?
@@ -336,7 +421,14 @@ options! {
     // We disable weak reference processing by default, as we are still working on it. This will be changed to `false`
     // once weak reference processing is implemented properly.
     no_reference_types:    bool                 [env_var: true, command_line: true]  [always_valid] = true,
-    // The zeroing approach to use for new object allocations. Affects each plan differently. (not supported)
+    // The space for objects in the MarkSweep plan: Malloc uses MallocSpace, and Native uses MarkSweepSpace with MMTk's own free lists.
+    // The space is created in gc_init(), so this needs to be set before gc_init().
+    marksweep_space:       MarkSweepSpaceSelector[env_var: true, command_line: true]  [always_valid] = MarkSweepSpaceSelector::Malloc,
+    // The write barrier of the generational plans (GenCopy, GenImmix and GenMarkSweep). NoBarrier is only correct
+    // if every nursery GC is a full heap GC (FULL_NURSERY_GC), and is used to measure the barrier overhead.
+    // This needs to be initialized before creating an MMTk instance (currently by setting env vars)
+    gen_barrier:           GenBarrierSelector   [env_var: true, command_line: false] [crate::plan::is_gen_barrier_supported] = GenBarrierSelector::ObjectBarrier,
+    // The zeroing approach to use for the memory that spaces acquire for new object allocations (see util::heap::zeroing)
     nursery_zeroing:       NurseryZeroingOptions[env_var: true, command_line: true]  [always_valid] = NurseryZeroingOptions::Temporal,
     // How frequent (every X bytes) should we do a stress GC?
     stress_factor:         usize                [env_var: true, command_line: true]  [always_valid] = DEFAULT_STRESS_FACTOR,
@@ -362,7 +454,12 @@ options! {
     work_perf_events:       PerfEventOptions     [env_var: true, command_line: true] [|_| cfg!(all(feature = "perf_counter", feature = "work_packet_stats"))] = PerfEventOptions {events: vec![]},
     // Measuring perf events for GC and mutators
     // TODO: Ideally this option should only be included when the features 'perf_counter' are enabled. The current macro does not allow us to do this.
-    phase_perf_events:      PerfEventOptions     [env_var: true, command_line: true] [|_| cfg!(feature = "perf_counter")] = PerfEventOptions {events: vec![]}
+    phase_perf_events:      PerfEventOptions     [env_var: true, command_line: true] [|_| cfg!(feature = "perf_counter")] = PerfEventOptions {events: vec![]},
+    // The file to write a timeline of GC events to, in the Chrome Trace Event format that can be loaded into Perfetto.
+    // The timeline includes the begin and end of each work packet, the opening and closing of work buckets, and the stopping
+    // and resuming of mutators. It is recorded if this is not empty, and written at harness_end. NOTE that all the events
+    // are kept in memory, and recording them may slow down GC. This needs to be set before initialize_collection().
+    gc_timeline_file:       String               [env_var: true, command_line: true] [always_valid] = String::new()
 }
 
 #[cfg(test)]
@@ -10,6 +10,7 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 pub const MAX_PHASES: usize = 1 << 12;
 pub const MAX_COUNTERS: usize = 100;
@@ -38,13 +39,27 @@ impl SharedStats {
     }
 }
 
+/// The pause times of GCs
+#[derive(Default)]
+struct PauseTimes {
+    /// When the last GC started, if there has been a GC
+    current_start: Option<Instant>,
+    /// The number of GCs since the stats were started
+    count: usize,
+    /// The total pause time since the stats were started
+    total: Duration,
+    /// The longest pause time since the stats were started
+    max: Duration,
+}
+
 /// GC statistics
 ///
 /// The struct holds basic GC statistics, like the GC count,
-/// and an array of counters.
+/// the pause time of each GC, and an array of counters.
 pub struct Stats {
     gc_count: AtomicUsize,
     total_time: Arc<Mutex<Timer>>,
+    pause_times: Mutex<PauseTimes>,
     // crate `pfm` uses libpfm4 under the hood for parsing perf event names
     // Initialization of libpfm4 is required before we can use `PerfEvent` types
     #[cfg(feature = "perf_counter")]
@@ -52,6 +67,8 @@ pub struct Stats {
 
     pub shared: Arc<SharedStats>,
     counters: Mutex<Vec<Arc<Mutex<dyn Counter + Send>>>>,
+    /// Values that are reported as they are at the end, rather than accumulated over the phases
+    gauges: Mutex<Vec<(String, Arc<AtomicUsize>)>>,
     exceeded_phase_limit: AtomicBool,
 }
 
@@ -95,11 +112,13 @@ impl Stats {
         Stats {
             gc_count: AtomicUsize::new(0),
             total_time: t,
+            pause_times: Mutex::new(PauseTimes::default()),
             #[cfg(feature = "perf_counter")]
             perfmon,
 
             shared,
             counters: Mutex::new(counters),
+            gauges: Mutex::new(vec![]),
             exceeded_phase_limit: AtomicBool::new(false),
         }
     }
@@ -132,6 +151,17 @@ impl Stats {
         Mutex::new(SizeCounter::new(u, v))
     }
 
+    /// Create a gauge. Unlike a counter, a gauge is not accumulated. Its latest value is printed
+    /// with the statistics, e.g. the current size of something that is resized over time.
+    pub fn new_gauge(&self, name: &str) -> Arc<AtomicUsize> {
+        let gauge = Arc::new(AtomicUsize::new(0));
+        self.gauges
+            .lock()
+            .unwrap()
+            .push((name.to_string(), gauge.clone()));
+        gauge
+    }
+
     pub fn new_timer(
         &self,
         name: &str,
@@ -152,6 +182,7 @@ impl Stats {
 
     pub fn start_gc(&self) {
         self.gc_count.fetch_add(1, Ordering::SeqCst);
+        self.pause_times.lock().unwrap().current_start = Some(Instant::now());
         if !self.get_gathering_stats() {
             return;
         }
@@ -171,6 +202,15 @@ impl Stats {
         if !self.get_gathering_stats() {
             return;
         }
+        {
+            let mut pause_times = self.pause_times.lock().unwrap();
+            if let Some(start) = pause_times.current_start {
+                let pause = start.elapsed();
+                pause_times.count += 1;
+                pause_times.total += pause;
+                pause_times.max = pause_times.max.max(pause);
+            }
+        }
         if self.get_phase() < MAX_PHASES - 1 {
             let counters = self.counters.lock().unwrap();
             for counter in &(*counters) {
@@ -183,6 +223,15 @@ impl Stats {
         }
     }
 
+    /// The time since the last GC started. This is only meaningful during a GC.
+    pub fn current_pause(&self) -> Duration {
+        self.pause_times
+            .lock()
+            .unwrap()
+            .current_start
+            .map_or(Duration::ZERO, |start| start.elapsed())
+    }
+
     pub fn print_stats<VM: VMBinding>(&self, mmtk: &'static MMTK<VM>) {
         println!(
             "============================ MMTk Statistics Totals ============================"
@@ -202,6 +251,18 @@ impl Stats {
             }
             print!("\t");
         }
+        for (_, gauge) in self.gauges.lock().unwrap().iter() {
+            print!("{}\t", gauge.load(Ordering::Relaxed));
+        }
+        {
+            let pause_times = self.pause_times.lock().unwrap();
+            let mean = if pause_times.count > 0 {
+                pause_times.total.as_secs_f64() * 1e3 / pause_times.count as f64
+            } else {
+                0f64
+            };
+            print!("{:.3}\t{:.3}\t", pause_times.max.as_secs_f64() * 1e3, mean);
+        }
         for value in scheduler_stat.values() {
             print!("{}\t", value);
         }
@@ -223,6 +284,10 @@ impl Stats {
                 print!("{}.other\t{}.stw\t", c.name(), c.name());
             }
         }
+        for (name, _) in self.gauges.lock().unwrap().iter() {
+            print!("{}\t", name);
+        }
+        print!("pause.max\tpause.mean\t");
         for name in scheduler_stat.keys() {
             print!("{}\t", name);
         }
@@ -237,6 +302,13 @@ impl Stats {
             debug_assert!(false);
         }
         self.shared.set_gathering_stats(true);
+        {
+            // Only count the pauses since the stats were started.
+            let mut pause_times = self.pause_times.lock().unwrap();
+            pause_times.count = 0;
+            pause_times.total = Duration::ZERO;
+            pause_times.max = Duration::ZERO;
+        }
 
         for c in &(*counters) {
             c.lock().unwrap().start();
@@ -1,3 +1,4 @@
+use crate::plan::GCTriggerPolicy;
 use crate::plan::MutatorContext;
 use crate::scheduler::gc_work::ProcessEdgesWork;
 use crate::scheduler::*;
@@ -81,6 +82,9 @@ pub trait Collection<VM: VMBinding> {
     ///  * Heap OOM: This is the case where the specified heap size is insufficient to execute the
     ///    application. MMTk expects the binding to notify the VM about this OOM. MMTk makes no
     ///    assumptions about whether the VM will continue executing or abort immediately.
+    ///  * GC overhead OOM: This is the case where GCs take most of the time but recover little
+    ///    memory, if the option `gc_time_limit` is set. It is reported like a heap OOM, but before
+    ///    the heap is completely exhausted.
     ///
     /// See [`AllocationError`] for more information.
     ///
@@ -102,4 +106,11 @@ pub trait Collection<VM: VMBinding> {
 
     /// Delegate to the VM binding for reference processing.
     fn process_weak_refs<E: ProcessEdgesWork<VM = VM>>(_worker: &mut GCWorker<VM>) {}
+
+    /// Create a policy that decides when to trigger a GC. MMTk calls this once in `gc_init()`.
+    /// If this returns `None`, MMTk triggers a GC whenever the plan requires one
+    /// (see [`PlanGCTrigger`](crate::plan::PlanGCTrigger)).
+    fn create_gc_trigger() -> Option<Box<dyn GCTriggerPolicy<VM>>> {
+        None
+    }
 }
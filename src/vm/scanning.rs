@@ -1,7 +1,7 @@
 use crate::plan::Mutator;
 use crate::scheduler::ProcessEdgesWork;
-use crate::util::VMWorkerThread;
 use crate::util::{Address, ObjectReference};
+use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::VMBinding;
 
 // Callback trait of scanning functions that report edges.
@@ -11,6 +11,21 @@ pub trait EdgeVisitor {
     // TODO: Add visit_soft_edge, visit_weak_edge, ... here.
 }
 
+/// Only pass the edges in `[start, end)` to the inner edge visitor.
+struct RangeFilter<'a, EV: EdgeVisitor> {
+    start: Address,
+    end: Address,
+    edge_visitor: &'a mut EV,
+}
+
+impl<EV: EdgeVisitor> EdgeVisitor for RangeFilter<'_, EV> {
+    fn visit_edge(&mut self, edge: Address) {
+        if self.start <= edge && edge < self.end {
+            self.edge_visitor.visit_edge(edge);
+        }
+    }
+}
+
 /// VM-specific methods for scanning roots/objects.
 pub trait Scanning<VM: VMBinding> {
     /// Scan stack roots after all mutators are paused.
@@ -34,6 +49,47 @@ pub trait Scanning<VM: VMBinding> {
         edge_visitor: &mut EV,
     );
 
+    /// Delegated scanning of a object in a mutator thread, visiting each pointer field encountered.
+    /// This is used by barriers that need the fields of an object that is being modified, e.g. the SATB barrier
+    /// records the objects that the fields of an object point to before the object is modified.
+    ///
+    /// Arguments:
+    /// * `tls`: The VM-specific thread-local storage for the current mutator.
+    /// * `object`: The object to be scanned.
+    /// * `edge_visitor`: Called back for each edge.
+    fn scan_object_in_mutator<EV: EdgeVisitor>(
+        tls: VMMutatorThread,
+        object: ObjectReference,
+        edge_visitor: &mut EV,
+    );
+
+    /// Delegated scanning of the pointer fields of an object that are in the address range `[start, end)`.
+    /// This is used to scan only the dirty cards of an object with the card-marking barrier.
+    ///
+    /// The default implementation scans the whole object and filters the edges. Bindings with large arrays
+    /// should override this to only visit the fields in the range.
+    ///
+    /// Arguments:
+    /// * `tls`: The VM-specific thread-local storage for the current worker.
+    /// * `object`: The object to be scanned.
+    /// * `start`: The start of the range.
+    /// * `end`: The end of the range (exclusive).
+    /// * `edge_visitor`: Called back for each edge in the range.
+    fn scan_object_range<EV: EdgeVisitor>(
+        tls: VMWorkerThread,
+        object: ObjectReference,
+        start: Address,
+        end: Address,
+        edge_visitor: &mut EV,
+    ) {
+        let mut filter = RangeFilter {
+            start,
+            end,
+            edge_visitor,
+        };
+        Self::scan_object(tls, object, &mut filter);
+    }
+
     /// MMTk calls this method at the first time during a collection that thread's stacks
     /// have been scanned. This can be used (for example) to clean up
     /// obsolete compiled methods that are no longer being executed.
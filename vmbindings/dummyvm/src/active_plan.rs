@@ -4,6 +4,7 @@ use mmtk::util::opaque_pointer::*;
 use mmtk::Mutator;
 use crate::DummyVM;
 use crate::SINGLETON;
+use crate::threads;
 
 pub struct VMActivePlan<> {}
 
@@ -13,23 +14,22 @@ impl ActivePlan<DummyVM> for VMActivePlan {
     }
 
     fn number_of_mutators() -> usize {
-        unimplemented!()
+        threads::number_of_mutators()
     }
 
-    fn is_mutator(_tls: VMThread) -> bool {
-        // FIXME
-        true
+    fn is_mutator(tls: VMThread) -> bool {
+        threads::is_mutator(tls)
     }
 
-    fn mutator(_tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
-        unimplemented!()
+    fn mutator(tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
+        unsafe { &mut *threads::get_mutator(tls) }
     }
 
     fn reset_mutator_iterator() {
-        unimplemented!()
+        threads::reset_mutator_iterator()
     }
 
     fn get_next_mutator() -> Option<&'static mut Mutator<DummyVM>> {
-        unimplemented!()
+        threads::next_mutator().map(|m| unsafe { &mut *m })
     }
 }
@@ -12,6 +12,7 @@ use mmtk::Mutator;
 use mmtk::MMTK;
 use crate::DummyVM;
 use crate::SINGLETON;
+use crate::threads;
 
 #[no_mangle]
 pub extern "C" fn mmtk_gc_init(heap_size: usize) {
@@ -24,11 +25,15 @@ pub extern "C" fn mmtk_gc_init(heap_size: usize) {
 
 #[no_mangle]
 pub extern "C" fn mmtk_bind_mutator(tls: VMMutatorThread) -> *mut Mutator<DummyVM> {
-    Box::into_raw(memory_manager::bind_mutator(&SINGLETON, tls))
+    let mutator = Box::into_raw(memory_manager::bind_mutator(&SINGLETON, tls));
+    threads::register_mutator(tls, mutator);
+    mutator
 }
 
 #[no_mangle]
 pub extern "C" fn mmtk_destroy_mutator(mutator: *mut Mutator<DummyVM>) {
+    memory_manager::flush_mutator(unsafe { &mut *mutator });
+    threads::unregister_mutator(mutator);
     memory_manager::destroy_mutator(unsafe { Box::from_raw(mutator) })
 }
 
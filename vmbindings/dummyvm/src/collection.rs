@@ -1,30 +1,76 @@
+use mmtk::memory_manager;
 use mmtk::vm::Collection;
 use mmtk::vm::GCThreadContext;
 use mmtk::MutatorContext;
+use mmtk::util::Address;
 use mmtk::util::opaque_pointer::*;
 use mmtk::scheduler::*;
+use mmtk::plan::GCTriggerPolicy;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
 use crate::DummyVM;
+use crate::SINGLETON;
+use crate::threads;
 
 pub struct VMCollection {}
 
+lazy_static! {
+    /// The GC trigger policy to be used by MMTk. This is taken by MMTk in `gc_init()`.
+    static ref GC_TRIGGER: Mutex<Option<Box<dyn GCTriggerPolicy<DummyVM>>>> = Mutex::new(None);
+}
+
+/// Set the GC trigger policy. This needs to be called before `gc_init()`.
+pub fn set_gc_trigger(policy: Box<dyn GCTriggerPolicy<DummyVM>>) {
+    *GC_TRIGGER.lock().unwrap() = Some(policy);
+}
+
+/// The address of the context of a GC thread identifies the thread.
+fn gc_thread_tls<T>(context: *mut T) -> VMWorkerThread {
+    VMWorkerThread(VMThread(OpaquePointer::from_address(Address::from_mut_ptr(context))))
+}
+
 impl Collection<DummyVM> for VMCollection {
     fn stop_all_mutators<E: ProcessEdgesWork<VM=DummyVM>>(_tls: VMWorkerThread) {
-        unimplemented!()
+        threads::stop_all_mutators()
     }
 
     fn resume_mutators(_tls: VMWorkerThread) {
-        unimplemented!()
+        threads::resume_mutators()
     }
 
     fn block_for_gc(_tls: VMMutatorThread) {
-        panic!("block_for_gc is not implemented")
+        threads::block_for_gc()
     }
 
-    fn spawn_gc_thread(_tls: VMThread, _ctx: GCThreadContext<DummyVM>) {
-
+    fn spawn_gc_thread(_tls: VMThread, ctx: GCThreadContext<DummyVM>) {
+        std::thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| match ctx {
+                GCThreadContext::Controller(mut controller) => {
+                    let tls = gc_thread_tls(&mut *controller);
+                    memory_manager::start_control_collector::<DummyVM>(&SINGLETON, tls, &mut *controller);
+                }
+                GCThreadContext::Worker(mut worker) => {
+                    let tls = gc_thread_tls(&mut *worker);
+                    memory_manager::start_worker::<DummyVM>(&SINGLETON, tls, &mut *worker);
+                }
+            }));
+            if let Err(payload) = result {
+                let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                    s.to_string()
+                } else if let Some(s) = payload.downcast_ref::<String>() {
+                    s.clone()
+                } else {
+                    "unknown panic".to_string()
+                };
+                threads::gc_thread_panicked(message);
+            }
+        });
     }
 
     fn prepare_mutator<T: MutatorContext<DummyVM>>(_tls_w: VMWorkerThread, _tls_m: VMMutatorThread, _mutator: &T) {
-        unimplemented!()
+    }
+
+    fn create_gc_trigger() -> Option<Box<dyn GCTriggerPolicy<DummyVM>>> {
+        GC_TRIGGER.lock().unwrap().take()
     }
 }
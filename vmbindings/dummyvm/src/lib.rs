@@ -12,6 +12,8 @@ pub mod object_model;
 pub mod active_plan;
 pub mod reference_glue;
 pub mod api;
+pub mod threads;
+pub mod runtime;
 
 #[cfg(test)]
 mod tests;
@@ -1,90 +1,175 @@
+use crate::DummyVM;
+use mmtk::util::constants::{BITS_IN_BYTE, BYTES_IN_WORD};
+use mmtk::util::conversions::raw_align_up;
 use mmtk::util::copy::{CopySemantics, GCWorkerCopyContext};
-use mmtk::util::metadata::header_metadata::HeaderMetadataSpec;
+use mmtk::util::metadata::header_metadata::{self, HeaderMetadataSpec};
 use mmtk::util::{Address, ObjectReference};
 use mmtk::vm::*;
 use std::sync::atomic::Ordering;
-use crate::DummyVM;
 
 pub struct VMObjectModel {}
 
+// DummyVM objects are made of words:
+//
+//   start          objref
+//   | status word  | size | number of references | reference fields ... | payload ...
+//
+// The status word holds the forwarding pointer and the forwarding bits. `size` is the size of the
+// whole object in bytes, starting from the status word. All the other per-object metadata is on the side.
+
 // This is intentionally set to a non-zero value to see if it breaks.
-// Change this if you want to test other values.
-#[cfg(target_pointer_width = "64")]
-pub const OBJECT_REF_OFFSET: usize = 6;
-#[cfg(target_pointer_width = "32")]
-pub const OBJECT_REF_OFFSET: usize = 2;
+// Change this if you want to test other values. It has to stay word aligned, as the forwarding pointer
+// is stored in the status word with its low bits used for the forwarding bits.
+pub const OBJECT_REF_OFFSET: usize = BYTES_IN_WORD;
+
+/// The bit offset of the status word from the object reference.
+const STATUS_BIT_OFFSET: isize = -((OBJECT_REF_OFFSET * BITS_IN_BYTE) as isize);
+/// The offset of the size word from the object reference.
+const SIZE_OFFSET: usize = 0;
+/// The offset of the number of reference fields from the object reference.
+const NUM_REFS_OFFSET: usize = BYTES_IN_WORD;
+/// The offset of the first reference field from the object reference.
+const FIELDS_OFFSET: usize = 2 * BYTES_IN_WORD;
+
+/// The size of the object header in bytes.
+pub const HEADER_BYTES: usize = OBJECT_REF_OFFSET + FIELDS_OFFSET;
+
+/// Return the size in bytes of an object with `num_refs` reference fields and `payload_bytes` bytes of payload.
+pub fn object_bytes(num_refs: usize, payload_bytes: usize) -> usize {
+    raw_align_up(
+        HEADER_BYTES + num_refs * BYTES_IN_WORD + payload_bytes,
+        BYTES_IN_WORD,
+    )
+}
+
+/// Initialize the header of an object allocated at `start`, and clear its reference fields.
+/// Return the reference to the new object.
+pub fn initialize_object(start: Address, num_refs: usize, bytes: usize) -> ObjectReference {
+    debug_assert!(bytes >= object_bytes(num_refs, 0));
+    let object = unsafe { (start + OBJECT_REF_OFFSET).to_object_reference() };
+    unsafe {
+        // Clear the forwarding bits, as the memory may be reused.
+        start.store::<usize>(0);
+        (object.to_address() + SIZE_OFFSET).store::<usize>(bytes);
+        (object.to_address() + NUM_REFS_OFFSET).store::<usize>(num_refs);
+    }
+    for i in 0..num_refs {
+        unsafe { get_field_slot(object, i).store(ObjectReference::NULL) };
+    }
+    object
+}
+
+/// Return the number of reference fields of an object.
+pub fn get_num_refs(object: ObjectReference) -> usize {
+    unsafe { (object.to_address() + NUM_REFS_OFFSET).load::<usize>() }
+}
+
+/// Return the address of the `index`-th reference field of an object.
+pub fn get_field_slot(object: ObjectReference, index: usize) -> Address {
+    debug_assert!(index < get_num_refs(object));
+    object.to_address() + FIELDS_OFFSET + index * BYTES_IN_WORD
+}
+
+/// Return the start address of the payload of an object.
+pub fn get_payload(object: ObjectReference) -> Address {
+    object.to_address() + FIELDS_OFFSET + get_num_refs(object) * BYTES_IN_WORD
+}
 
 impl ObjectModel<DummyVM> for VMObjectModel {
-    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::in_header(0);
-    const LOCAL_FORWARDING_POINTER_SPEC: VMLocalForwardingPointerSpec = VMLocalForwardingPointerSpec::in_header(0);
-    const LOCAL_FORWARDING_BITS_SPEC: VMLocalForwardingBitsSpec = VMLocalForwardingBitsSpec::in_header(0);
-    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::in_header(0);
-    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec = VMLocalLOSMarkNurserySpec::in_header(0);
+    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::side_first();
+    const LOCAL_FORWARDING_POINTER_SPEC: VMLocalForwardingPointerSpec =
+        VMLocalForwardingPointerSpec::in_header(STATUS_BIT_OFFSET);
+    const LOCAL_FORWARDING_BITS_SPEC: VMLocalForwardingBitsSpec =
+        VMLocalForwardingBitsSpec::in_header(STATUS_BIT_OFFSET);
+    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::side_first();
+    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec =
+        VMLocalLOSMarkNurserySpec::side_after(Self::LOCAL_MARK_BIT_SPEC.as_spec());
 
     fn load_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _mask: Option<usize>,
-        _atomic_ordering: Option<Ordering>,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        mask: Option<usize>,
+        atomic_ordering: Option<Ordering>,
     ) -> usize {
-        // Do nothing at this moment.
-        0
+        header_metadata::load_metadata(metadata_spec, object, mask, atomic_ordering)
     }
 
     fn store_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _mask: Option<usize>,
-        _atomic_ordering: Option<Ordering>,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        mask: Option<usize>,
+        atomic_ordering: Option<Ordering>,
     ) {
-        // Do nothing at this moment.
+        header_metadata::store_metadata(metadata_spec, object, val, mask, atomic_ordering)
     }
 
     fn compare_exchange_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _old_val: usize,
-        _new_val: usize,
-        _mask: Option<usize>,
-        _success_order: Ordering,
-        _failure_order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        old_val: usize,
+        new_val: usize,
+        mask: Option<usize>,
+        success_order: Ordering,
+        failure_order: Ordering,
     ) -> bool {
-        unimplemented!()
+        header_metadata::compare_exchange_metadata(
+            metadata_spec,
+            object,
+            old_val,
+            new_val,
+            mask,
+            success_order,
+            failure_order,
+        )
     }
 
     fn fetch_add_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        order: Ordering,
     ) -> usize {
-        unimplemented!()
+        header_metadata::fetch_add_metadata(metadata_spec, object, val, order)
     }
 
     fn fetch_sub_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        order: Ordering,
     ) -> usize {
-        unimplemented!()
+        header_metadata::fetch_sub_metadata(metadata_spec, object, val, order)
     }
 
     fn copy(
-        _from: ObjectReference,
-        _semantics: CopySemantics,
-        _copy_context: &mut GCWorkerCopyContext<DummyVM>,
+        from: ObjectReference,
+        semantics: CopySemantics,
+        copy_context: &mut GCWorkerCopyContext<DummyVM>,
     ) -> ObjectReference {
-        unimplemented!()
+        let bytes = Self::get_size_when_copied(from);
+        let align = Self::get_align_when_copied(from);
+        let offset = Self::get_align_offset_when_copied(from);
+        let dst = copy_context.alloc_copy(from, bytes, align, offset, semantics);
+        let to = Self::get_reference_when_copied_to(from, dst);
+        Self::copy_to(from, to, dst);
+        copy_context.post_copy(to, bytes, semantics);
+        to
     }
 
-    fn copy_to(_from: ObjectReference, _to: ObjectReference, _region: Address) -> Address {
-        unimplemented!()
+    fn copy_to(from: ObjectReference, to: ObjectReference, _region: Address) -> Address {
+        let bytes = Self::get_current_size(from);
+        let src = Self::object_start_ref(from);
+        let dst = Self::object_start_ref(to);
+        if src != dst {
+            // The source and the destination may overlap when objects are compacted.
+            unsafe { std::ptr::copy::<u8>(src.to_ptr(), dst.to_mut_ptr(), bytes) };
+        }
+        dst + bytes
     }
 
-    fn get_current_size(_object: ObjectReference) -> usize {
-        unimplemented!()
+    fn get_current_size(object: ObjectReference) -> usize {
+        unsafe { (object.to_address() + SIZE_OFFSET).load::<usize>() }
     }
 
     fn get_size_when_copied(object: ObjectReference) -> usize {
@@ -99,8 +184,8 @@ impl ObjectModel<DummyVM> for VMObjectModel {
         0
     }
 
-    fn get_reference_when_copied_to(_from: ObjectReference, _to: Address) -> ObjectReference {
-        unimplemented!()
+    fn get_reference_when_copied_to(_from: ObjectReference, to: Address) -> ObjectReference {
+        unsafe { (to + OBJECT_REF_OFFSET).to_object_reference() }
     }
 
     fn get_type_descriptor(_reference: ObjectReference) -> &'static [i8] {
@@ -111,11 +196,19 @@ impl ObjectModel<DummyVM> for VMObjectModel {
         object.to_address().sub(OBJECT_REF_OFFSET)
     }
 
-    fn ref_to_address(_object: ObjectReference) -> Address {
-        unimplemented!()
+    fn ref_to_address(object: ObjectReference) -> Address {
+        object.to_address()
     }
 
-    fn dump_object(_object: ObjectReference) {
-        unimplemented!()
+    fn dump_object(object: ObjectReference) {
+        let fields: Vec<ObjectReference> = (0..get_num_refs(object))
+            .map(|i| unsafe { get_field_slot(object, i).load::<ObjectReference>() })
+            .collect();
+        println!(
+            "{}: size = {}, fields = {:?}",
+            object,
+            Self::get_current_size(object),
+            fields
+        );
     }
 }
@@ -0,0 +1,103 @@
+//! The mutator operations of DummyVM: allocating objects, and reading and writing their reference fields.
+//!
+//! An object returned by `alloc_object()` may be moved by any later allocation. Objects that need
+//! to survive across allocations should be kept on the root stack of the mutator (see `threads::push_root()`),
+//! or be reachable from an object on the root stack.
+
+use crate::api::{mmtk_alloc, mmtk_post_alloc};
+use crate::object_model;
+use crate::threads;
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::ObjectReference;
+use mmtk::{AllocationSemantics, Mutator, MutatorContext};
+
+/// Allocate and initialize an object with `num_refs` reference fields and `payload_bytes` bytes of payload.
+/// This is a safepoint.
+pub fn alloc_object(
+    mutator: &mut Mutator<DummyVM>,
+    num_refs: usize,
+    payload_bytes: usize,
+) -> ObjectReference {
+    alloc_object_with_semantics(
+        mutator,
+        num_refs,
+        payload_bytes,
+        AllocationSemantics::Default,
+    )
+}
+
+/// Allocate and initialize an object like `alloc_object()`, with the given allocation semantics.
+/// This is a safepoint.
+pub fn alloc_object_with_semantics(
+    mutator: &mut Mutator<DummyVM>,
+    num_refs: usize,
+    payload_bytes: usize,
+    semantics: AllocationSemantics,
+) -> ObjectReference {
+    threads::safepoint();
+    let bytes = object_model::object_bytes(num_refs, payload_bytes);
+    let start = mmtk_alloc(mutator, bytes, BYTES_IN_WORD, 0, semantics);
+    assert!(!start.is_zero(), "failed to allocate {} bytes", bytes);
+    let object = object_model::initialize_object(start, num_refs, bytes);
+    mmtk_post_alloc(mutator, object, bytes, semantics);
+    object
+}
+
+/// Read the `index`-th reference field of an object.
+pub fn read_field(object: ObjectReference, index: usize) -> ObjectReference {
+    unsafe { object_model::get_field_slot(object, index).load::<ObjectReference>() }
+}
+
+/// Write `value` to the `index`-th reference field of an object, with the write barrier of the plan.
+pub fn write_field(
+    mutator: &mut Mutator<DummyVM>,
+    object: ObjectReference,
+    index: usize,
+    value: ObjectReference,
+) {
+    let slot = object_model::get_field_slot(object, index);
+    mutator.record_modifying_node(object);
+    unsafe { slot.store(value) };
+    mutator.record_modified_field(object, slot);
+}
+
+/// Write `value` to the `index`-th reference field of an object, with the write barrier of the plan for the slot only,
+/// as if the mutator did not know which object the slot belongs to. Not all the barriers support this.
+pub fn write_slot(
+    mutator: &mut Mutator<DummyVM>,
+    object: ObjectReference,
+    index: usize,
+    value: ObjectReference,
+) {
+    let slot = object_model::get_field_slot(object, index);
+    mutator.record_modifying_edge(slot);
+    unsafe { slot.store(value) };
+    mutator.record_modified_edge(slot);
+}
+
+/// Copy `count` references from the fields of `src` starting at `src_index` to the fields of `dst` starting at
+/// `dst_index`, with the array-copy barrier of the plan.
+pub fn copy_fields(
+    mutator: &mut Mutator<DummyVM>,
+    src: ObjectReference,
+    src_index: usize,
+    dst: ObjectReference,
+    dst_index: usize,
+    count: usize,
+) {
+    if count == 0 {
+        return;
+    }
+    assert!(src_index + count <= object_model::get_num_refs(src));
+    assert!(dst_index + count <= object_model::get_num_refs(dst));
+    memory_manager::object_reference_array_copy(
+        mutator,
+        src,
+        object_model::get_field_slot(src, src_index),
+        dst,
+        object_model::get_field_slot(dst, dst_index),
+        count,
+    );
+}
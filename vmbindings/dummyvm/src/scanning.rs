@@ -1,40 +1,96 @@
+use crate::object_model;
+use crate::threads;
 use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
 use mmtk::scheduler::*;
+use mmtk::util::constants::BYTES_IN_WORD;
 use mmtk::util::opaque_pointer::*;
-use mmtk::util::ObjectReference;
+use mmtk::util::{Address, ObjectReference};
 use mmtk::vm::EdgeVisitor;
 use mmtk::vm::Scanning;
 use mmtk::Mutator;
 
 pub struct VMScanning {}
 
+/// Create work packets to process the root slots.
+fn process_root_slots<W: ProcessEdgesWork<VM = DummyVM>>(slots: Vec<Address>) {
+    for chunk in slots.chunks(W::CAPACITY) {
+        memory_manager::add_work_packet(
+            &SINGLETON,
+            WorkBucketStage::Closure,
+            W::new(chunk.to_vec(), true, &SINGLETON),
+        );
+    }
+}
+
+/// Visit the reference fields of an object. Scanning an object does not depend on the thread that scans it.
+fn scan_fields<EV: EdgeVisitor>(object: ObjectReference, edge_visitor: &mut EV) {
+    for i in 0..object_model::get_num_refs(object) {
+        edge_visitor.visit_edge(object_model::get_field_slot(object, i));
+    }
+}
+
 impl Scanning<DummyVM> for VMScanning {
     fn scan_thread_roots<W: ProcessEdgesWork<VM = DummyVM>>() {
-        unimplemented!()
+        process_root_slots::<W>(threads::root_slots(None));
     }
     fn scan_thread_root<W: ProcessEdgesWork<VM = DummyVM>>(
-        _mutator: &'static mut Mutator<DummyVM>,
+        mutator: &'static mut Mutator<DummyVM>,
         _tls: VMWorkerThread,
     ) {
-        unimplemented!()
+        process_root_slots::<W>(threads::root_slots(Some(mutator)));
     }
     fn scan_vm_specific_roots<W: ProcessEdgesWork<VM = DummyVM>>() {
-        unimplemented!()
+        // DummyVM has no roots other than the root stacks of its threads.
     }
     fn scan_object<EV: EdgeVisitor>(
         _tls: VMWorkerThread,
-        _object: ObjectReference,
-        _edge_visitor: &mut EV,
+        object: ObjectReference,
+        edge_visitor: &mut EV,
+    ) {
+        scan_fields(object, edge_visitor);
+    }
+    fn scan_object_in_mutator<EV: EdgeVisitor>(
+        _tls: VMMutatorThread,
+        object: ObjectReference,
+        edge_visitor: &mut EV,
+    ) {
+        scan_fields(object, edge_visitor);
+    }
+    fn scan_object_range<EV: EdgeVisitor>(
+        _tls: VMWorkerThread,
+        object: ObjectReference,
+        start: Address,
+        end: Address,
+        edge_visitor: &mut EV,
     ) {
-        unimplemented!()
+        // The reference fields are contiguous, so we start from the first field in the range.
+        let num_refs = object_model::get_num_refs(object);
+        if num_refs == 0 {
+            return;
+        }
+        let fields = object_model::get_field_slot(object, 0);
+        let first = if start > fields {
+            (start - fields + BYTES_IN_WORD - 1) / BYTES_IN_WORD
+        } else {
+            0
+        };
+        for i in first..num_refs {
+            let slot = object_model::get_field_slot(object, i);
+            if slot >= end {
+                break;
+            }
+            edge_visitor.visit_edge(slot);
+        }
     }
     fn notify_initial_thread_scan_complete(_partial_scan: bool, _tls: VMWorkerThread) {
-        unimplemented!()
+        // Do nothing
     }
     fn supports_return_barrier() -> bool {
-        unimplemented!()
+        false
     }
     fn prepare_for_roots_re_scanning() {
-        unimplemented!()
+        // Do nothing
     }
 }
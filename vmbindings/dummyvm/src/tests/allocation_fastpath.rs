@@ -0,0 +1,89 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::current_thread_tls;
+use crate::tests::fixtures::plan_can_collect;
+use crate::threads;
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::alloc::FastPathKind;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+/// Larger than the largest object that the default allocator of the Immix plans and the native mark-sweep
+/// space can allocate.
+const LARGE_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// Allocate an object with the slow path of the fast path descriptor for the object size, as compiled code would
+/// do when the fast path fails. Objects larger than `max_non_los_bytes` of the default descriptor are allocated
+/// with the descriptor for the large object space.
+fn alloc_with_descriptor(mutator: &mut Mutator<DummyVM>, payload_bytes: usize) -> ObjectReference {
+    let bytes = object_model::object_bytes(0, payload_bytes);
+    let mut semantics = AllocationSemantics::Default;
+    let mut fastpath = memory_manager::get_allocation_fastpath(mutator, semantics);
+    if bytes > fastpath.max_non_los_bytes {
+        semantics = AllocationSemantics::Los;
+        fastpath = memory_manager::get_allocation_fastpath(mutator, semantics);
+        assert!(bytes <= fastpath.max_non_los_bytes);
+    }
+    let start = (fastpath.slow_path)(mutator, bytes, BYTES_IN_WORD, 0, semantics);
+    assert!(!start.is_zero());
+    let object = object_model::initialize_object(start, 0, bytes);
+    memory_manager::post_alloc::<DummyVM>(mutator, object, bytes, semantics);
+    object
+}
+
+/// This test allocates through the slow path in the fast path descriptor, and checks that the cursor
+/// and the limit at the offsets in the descriptor match the allocation. It then allocates a large object
+/// with the descriptor for its size, and checks that the object survives a GC.
+#[test]
+pub fn allocation_fastpath() {
+    const MB: usize = 1024 * 1024;
+    // 1MB heap
+    mmtk_gc_init(MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let semantics = AllocationSemantics::Default;
+    let fastpath = memory_manager::get_allocation_fastpath(mutator, semantics);
+    assert_eq!(
+        fastpath.selector,
+        memory_manager::get_allocator_mapping(&crate::SINGLETON, semantics)
+    );
+
+    let bytes = object_model::object_bytes(0, BYTES_IN_WORD);
+    let start = (fastpath.slow_path)(mutator, bytes, BYTES_IN_WORD, 0, semantics);
+    assert!(!start.is_zero());
+
+    if fastpath.kind == FastPathKind::BumpPointer {
+        let base = Address::from_mut_ptr(mutator);
+        let cursor = unsafe { (base + fastpath.cursor_offset).load::<Address>() };
+        let limit = unsafe { (base + fastpath.limit_offset).load::<Address>() };
+        assert_eq!(cursor, start + bytes);
+        assert!(cursor <= limit);
+    }
+
+    let object = object_model::initialize_object(start, 0, bytes);
+    memory_manager::post_alloc::<DummyVM>(mutator, object, bytes, semantics);
+
+    let large = alloc_with_descriptor(mutator, LARGE_PAYLOAD_BYTES);
+    unsafe { object_model::get_payload(large).store::<usize>(LARGE_PAYLOAD_BYTES) };
+    let root = threads::push_root(mutator, large);
+    if plan_can_collect() {
+        let pauses = threads::pause_count();
+        mmtk_handle_user_collection_request(current_thread_tls());
+        assert!(threads::pause_count() > pauses, "No GC happened");
+    }
+    let large = threads::get_root(mutator, root);
+    assert_eq!(
+        unsafe { object_model::get_payload(large).load::<usize>() },
+        LARGE_PAYLOAD_BYTES
+    );
+
+    mmtk_destroy_mutator(handle);
+}
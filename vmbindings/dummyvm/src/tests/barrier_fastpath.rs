@@ -0,0 +1,64 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::runtime::*;
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::{BarrierFastPathKind, LogBitLocation};
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::vm::{ObjectModel, VMBinding};
+use std::sync::atomic::Ordering;
+
+/// Read the log bit of an object in the way that compiled code would, with the barrier fast path descriptor.
+fn load_log_bit(location: LogBitLocation, object: ObjectReference) -> u8 {
+    let o = object.to_address().as_usize();
+    let (byte, bit) = match location {
+        LogBitLocation::OnSide {
+            base,
+            byte_shift,
+            bit_shift,
+            bit_mask,
+        } => (base + (o >> byte_shift), (o >> bit_shift) & bit_mask),
+        LogBitLocation::InHeader { byte_offset, bit } => {
+            (object.to_address() + byte_offset, bit as usize)
+        }
+        LogBitLocation::None => unreachable!(),
+    };
+    (unsafe { byte.load::<u8>() } >> bit) & 1
+}
+
+/// This test checks that the log bit described by the barrier fast path descriptor is the log bit that MMTk uses,
+/// and that the slow path logs the object.
+#[test]
+pub fn barrier_fastpath() {
+    const MB: usize = 1024 * 1024;
+    // 1MB heap
+    mmtk_gc_init(MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let fastpath = memory_manager::get_barrier_fastpath(&crate::SINGLETON);
+    assert_eq!(
+        fastpath.selector,
+        crate::SINGLETON.get_plan().constraints().barrier
+    );
+    match fastpath.kind {
+        BarrierFastPathKind::PostWriteLogBit | BarrierFastPathKind::PreWriteLogBit => {
+            let log_bit = &<DummyVM as VMBinding>::VMObjectModel::GLOBAL_LOG_BIT_SPEC;
+            let object = alloc_object(mutator, 0, BYTES_IN_WORD);
+            log_bit.mark_as_unlogged::<DummyVM>(object, Ordering::SeqCst);
+            assert_eq!(load_log_bit(fastpath.log_bit, object), 1);
+            (fastpath.slow_path.unwrap())(mutator, object);
+            assert_eq!(load_log_bit(fastpath.log_bit, object), 0);
+        }
+        BarrierFastPathKind::NoBarrier | BarrierFastPathKind::SlowPathOnly => {
+            assert_eq!(fastpath.log_bit, LogBitLocation::None);
+            assert!(fastpath.slow_path.is_none());
+        }
+    }
+
+    mmtk_destroy_mutator(handle);
+}
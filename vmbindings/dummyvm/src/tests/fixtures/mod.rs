@@ -1,11 +1,39 @@
 use atomic_refcell::AtomicRefCell;
 use std::sync::Once;
 
+use mmtk::memory_manager;
 use mmtk::AllocationSemantics;
-use mmtk::util::{ObjectReference, VMThread, VMMutatorThread};
+use mmtk::Mutator;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::{Address, ObjectReference, OpaquePointer, VMThread, VMMutatorThread};
 
 use crate::api::*;
-use crate::object_model::OBJECT_REF_OFFSET;
+use crate::object_model;
+use crate::runtime;
+use crate::threads;
+use crate::DummyVM;
+use crate::SINGLETON;
+
+/// Return whether the plan selected for the test can collect garbage. Tests that need GC should
+/// return early if it cannot.
+pub fn plan_can_collect() -> bool {
+    format!("{:?}", *SINGLETON.get_options().plan) != "NoGC"
+}
+
+/// Return whether the plan selected for the test is the plan with the given name. Tests for a specific plan
+/// should return early if it is not selected.
+pub fn plan_is(name: &str) -> bool {
+    format!("{:?}", *SINGLETON.get_options().plan) == name
+}
+
+/// Return a distinct thread-local pointer for the current thread, to be used as its mutator tls.
+pub fn current_thread_tls() -> VMMutatorThread {
+    thread_local! {
+        static TLS: u8 = 0;
+    }
+    let addr = TLS.with(|tls| Address::from_ptr(tls));
+    VMMutatorThread(VMThread(OpaquePointer::from_address(addr)))
+}
 
 pub trait FixtureContent {
     fn create() -> Self;
@@ -60,9 +88,119 @@ impl FixtureContent for SingleObject {
         let addr = mmtk_alloc(handle, size, 8, 0, semantics);
         assert!(!addr.is_zero());
 
-        let objref = unsafe { addr.add(OBJECT_REF_OFFSET).to_object_reference() };
+        let objref = object_model::initialize_object(addr, 0, size);
         mmtk_post_alloc(handle, objref, size, semantics);
 
         SingleObject { objref }
     }
 }
+
+/// The payload size of the garbage objects allocated by `build_linked_list()`.
+const GARBAGE_PAYLOAD_BYTES: usize = 168;
+
+/// Build a linked list of `length` nodes, allocating `garbage_per_node` garbage objects after each node.
+/// Each node points to the next node and to a leaf object, and both record the index of the node in their payload.
+/// Return the index of the head of the list on the root stack of the mutator.
+pub fn build_linked_list(mutator: &mut Mutator<DummyVM>, length: usize, garbage_per_node: usize) -> usize {
+    let head = threads::push_root(mutator, ObjectReference::NULL);
+    for i in (0..length).rev() {
+        let leaf = runtime::alloc_object(mutator, 0, BYTES_IN_WORD);
+        unsafe { object_model::get_payload(leaf).store::<usize>(i) };
+        threads::push_root(mutator, leaf);
+        // The leaf may be moved by this allocation. Reload it from the root stack.
+        let node = runtime::alloc_object(mutator, 2, BYTES_IN_WORD);
+        unsafe { object_model::get_payload(node).store::<usize>(i) };
+        let leaf = threads::pop_root(mutator);
+        runtime::write_field(mutator, node, 0, threads::get_root(mutator, head));
+        runtime::write_field(mutator, node, 1, leaf);
+        threads::set_root(mutator, head, node);
+        for _ in 0..garbage_per_node {
+            runtime::alloc_object(mutator, 1, GARBAGE_PAYLOAD_BYTES);
+        }
+    }
+    head
+}
+
+/// Check a linked list built by `build_linked_list()`.
+pub fn verify_linked_list(head: ObjectReference, length: usize) {
+    let mut node = head;
+    for i in 0..length {
+        assert!(!node.is_null(), "The list ends at node {}", i);
+        assert_eq!(unsafe { object_model::get_payload(node).load::<usize>() }, i);
+        let leaf = runtime::read_field(node, 1);
+        assert_eq!(unsafe { object_model::get_payload(leaf).load::<usize>() }, i);
+        node = runtime::read_field(node, 0);
+    }
+    assert!(node.is_null(), "The list is longer than {}", length);
+}
+
+/// Return the `index`-th node of a list built by `build_linked_list()`.
+pub fn get_node(head: ObjectReference, index: usize) -> ObjectReference {
+    let mut node = head;
+    for _ in 0..index {
+        node = runtime::read_field(node, 0);
+    }
+    node
+}
+
+/// A function that writes a reference to a field of an object with the write barrier, e.g. `runtime::write_field()`.
+pub type WriteField = fn(&mut Mutator<DummyVM>, ObjectReference, usize, ObjectReference);
+
+const SMALL_NURSERY_LIST_LENGTH: usize = 100;
+const SMALL_NURSERY_ROUNDS: usize = 1000;
+const SMALL_NURSERY_GARBAGE_PER_ROUND: usize = 100;
+
+/// Build a list, and then allocate garbage with a small nursery for a generational plan. In each round, a leaf
+/// of the list is replaced with a new object using `writes` in turn, so once the list is promoted, the new leaf is
+/// only referenced by a mature object and nursery GCs need the barrier to find it. No leaf is replaced if `writes`
+/// is empty. The list is verified after each round and after a full heap GC requested at the end, and `verify` is
+/// called with the head of the list after the rounds and after the full heap GC.
+pub fn replace_leaves_in_small_nursery(writes: &[WriteField], verify: fn(ObjectReference)) {
+    const MB: usize = 1024 * 1024;
+    // 2MB nursery, so there are a few nursery GCs before the heap is full.
+    assert!(memory_manager::process_bulk(
+        &SINGLETON,
+        "min_nursery=2097152 max_nursery=2097152 full_heap_system_gc=true"
+    ));
+    // 32MB heap. The garbage is about 17MB.
+    mmtk_gc_init(32 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    let head = build_linked_list(mutator, SMALL_NURSERY_LIST_LENGTH, 0);
+    let mut nursery_gcs = 0;
+    for round in 0..SMALL_NURSERY_ROUNDS {
+        if !writes.is_empty() {
+            let index = round % SMALL_NURSERY_LIST_LENGTH;
+            let leaf = runtime::alloc_object(mutator, 0, BYTES_IN_WORD);
+            unsafe { object_model::get_payload(leaf).store::<usize>(index) };
+            let node = get_node(threads::get_root(mutator, head), index);
+            writes[round % writes.len()](mutator, node, 1, leaf);
+        }
+
+        let pauses = threads::pause_count();
+        for _ in 0..SMALL_NURSERY_GARBAGE_PER_ROUND {
+            runtime::alloc_object(mutator, 1, 19 * BYTES_IN_WORD);
+        }
+        if threads::pause_count() > pauses && !SINGLETON.get_plan().last_collection_full_heap() {
+            nursery_gcs += 1;
+        }
+        verify_linked_list(threads::get_root(mutator, head), SMALL_NURSERY_LIST_LENGTH);
+    }
+    assert!(nursery_gcs > 0, "No nursery GC happened");
+    verify(threads::get_root(mutator, head));
+
+    let pauses = threads::pause_count();
+    mmtk_handle_user_collection_request(tls);
+    assert!(threads::pause_count() > pauses, "No GC happened");
+    assert!(
+        SINGLETON.get_plan().last_collection_full_heap(),
+        "The user requested GC is not a full heap GC"
+    );
+    verify_linked_list(threads::get_root(mutator, head), SMALL_NURSERY_LIST_LENGTH);
+    verify(threads::get_root(mutator, head));
+
+    mmtk_destroy_mutator(handle);
+}
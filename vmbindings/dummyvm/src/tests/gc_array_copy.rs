@@ -0,0 +1,73 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+
+const ARRAY_LENGTH: usize = 1000;
+
+/// This test copies references from one array to another with the array-copy barrier, after both arrays
+/// have survived a GC. The copied references are only reachable from the destination array after the copy,
+/// and they should survive the following GCs.
+#[test]
+pub fn gc_array_copy() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 32MB heap
+    mmtk_gc_init(32 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    let src = alloc_object(mutator, ARRAY_LENGTH, 0);
+    let src = threads::push_root(mutator, src);
+    let dst = alloc_object(mutator, ARRAY_LENGTH, 0);
+    let dst = threads::push_root(mutator, dst);
+    // Make the arrays old, so the copied references are from old objects to young objects.
+    mmtk_handle_user_collection_request(tls);
+
+    for i in 0..ARRAY_LENGTH {
+        let element = alloc_object(mutator, 0, BYTES_IN_WORD);
+        unsafe { object_model::get_payload(element).store::<usize>(i) };
+        write_field(mutator, threads::get_root(mutator, src), i, element);
+    }
+    // Copy the second half of the source to the first half of the destination, and clear the source.
+    let half = ARRAY_LENGTH / 2;
+    copy_fields(
+        mutator,
+        threads::get_root(mutator, src),
+        half,
+        threads::get_root(mutator, dst),
+        0,
+        half,
+    );
+    let empty = alloc_object(mutator, 0, 0);
+    threads::set_root(mutator, src, empty);
+
+    // Allocate garbage to trigger GCs.
+    let pauses = threads::pause_count();
+    while threads::pause_count() < pauses + 3 {
+        alloc_object(mutator, 1, 256);
+    }
+
+    let dst = threads::get_root(mutator, dst);
+    for i in 0..half {
+        let element = read_field(dst, i);
+        assert_eq!(
+            unsafe { object_model::get_payload(element).load::<usize>() },
+            half + i
+        );
+    }
+    for i in half..ARRAY_LENGTH {
+        assert!(read_field(dst, i).is_null());
+    }
+
+    mmtk_destroy_mutator(handle);
+}
@@ -0,0 +1,11 @@
+// GITHUB-CI: MMTK_PLAN=GenCopy
+
+use crate::tests::gc_field_barrier::replace_leaves_with_barrier;
+
+/// Run the test with the card-marking barrier. The leaves written with `write_field()` are found by scanning
+/// the dirty cards of the logged nodes (`ProcessDirtyCards`), and the cards are cleared at the end of each GC
+/// (`ClearDirtyCards`). The leaves written with `write_slot()` are remembered as slots.
+#[test]
+pub fn gc_card_barrier() {
+    replace_leaves_with_barrier("CardBarrier");
+}
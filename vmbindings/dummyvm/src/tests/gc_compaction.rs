@@ -0,0 +1,52 @@
+// GITHUB-CI: MMTK_PLAN=MarkCompact
+
+use crate::api::*;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+
+/// The dense list takes about 4MB, which spans a few compaction regions (1MB each).
+const DENSE_LIST_LENGTH: usize = 40000;
+/// The sparse list has about 16KB of garbage after each node.
+const SPARSE_LIST_LENGTH: usize = 100;
+const SPARSE_LIST_GARBAGE_PER_NODE: usize = 100;
+
+/// This test builds a list without garbage, so the live objects span a few compaction regions and cross the region
+/// boundaries, and a list with a lot of garbage, so the objects in later regions are compacted into earlier regions.
+/// Both lists should be intact after each compaction.
+#[test]
+pub fn gc_compaction() {
+    if !plan_is("MarkCompact") {
+        return;
+    }
+    compact_lists();
+}
+
+/// The body of the test. This is also used by the test for the `markcompact_side_forwarding` feature.
+pub fn compact_lists() {
+    const MB: usize = 1024 * 1024;
+    // 32MB heap
+    mmtk_gc_init(32 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    let sparse = build_linked_list(mutator, SPARSE_LIST_LENGTH, SPARSE_LIST_GARBAGE_PER_NODE);
+    let dense = build_linked_list(mutator, DENSE_LIST_LENGTH, 0);
+    for _ in 0..3 {
+        let pauses = threads::pause_count();
+        mmtk_handle_user_collection_request(tls);
+        assert!(threads::pause_count() > pauses, "No GC happened");
+        verify_linked_list(threads::get_root(mutator, sparse), SPARSE_LIST_LENGTH);
+        verify_linked_list(threads::get_root(mutator, dense), DENSE_LIST_LENGTH);
+        // Garbage between the GCs, so each GC compacts different regions.
+        for _ in 0..10000 {
+            alloc_object(mutator, 1, 19 * BYTES_IN_WORD);
+        }
+    }
+
+    mmtk_destroy_mutator(handle);
+}
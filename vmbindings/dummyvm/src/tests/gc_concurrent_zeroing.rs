@@ -0,0 +1,47 @@
+// GITHUB-CI: MMTK_PLAN=GenCopy
+// GITHUB-CI: MMTK_PLAN=Immix
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+
+const PAYLOAD_WORDS: usize = 32;
+const OBJECTS: usize = 150_000;
+
+/// This test allocates with concurrent nursery zeroing, and dirties each object after it is allocated.
+/// The memory is reused after each GC, so every new object should find its payload already zeroed.
+#[test]
+pub fn gc_concurrent_zeroing() {
+    if !plan_is("GenCopy") && !plan_is("Immix") {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    assert!(memory_manager::process_bulk(
+        &SINGLETON,
+        "nursery_zeroing=Concurrent"
+    ));
+    // 8MB heap. The garbage is about 40MB.
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    for _ in 0..OBJECTS {
+        let object = alloc_object(mutator, 0, PAYLOAD_WORDS * BYTES_IN_WORD);
+        let payload = object_model::get_payload(object);
+        for i in 0..PAYLOAD_WORDS {
+            let slot = payload + i * BYTES_IN_WORD;
+            assert_eq!(unsafe { slot.load::<usize>() }, 0, "{} is not zeroed", slot);
+            unsafe { slot.store(usize::MAX) };
+        }
+    }
+    assert!(threads::pause_count() > 0, "No GC happened");
+
+    mmtk_destroy_mutator(handle);
+}
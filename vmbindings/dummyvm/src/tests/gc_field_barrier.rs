@@ -0,0 +1,30 @@
+// GITHUB-CI: MMTK_PLAN=GenCopy
+
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::SINGLETON;
+
+/// Select the barrier of the generational plans, and run a GenCopy test with it. The barrier is selected by an
+/// env var, as it needs to be set before the MMTk instance is created. So this should be called before anything
+/// else uses `SINGLETON`.
+///
+/// The test keeps replacing the leaves of a promoted list with new objects while allocating garbage with a small
+/// nursery. Each new leaf is only referenced by a mature object, so nursery GCs need the barrier to find it.
+/// The leaves are written with `write_field()` and `write_slot()` in turn, so the barrier needs to support both.
+pub fn replace_leaves_with_barrier(barrier: &str) {
+    std::env::set_var("MMTK_GEN_BARRIER", barrier);
+    if !plan_is("GenCopy") {
+        return;
+    }
+    assert_eq!(
+        format!("{:?}", SINGLETON.get_plan().constraints().barrier),
+        barrier
+    );
+    replace_leaves_in_small_nursery(&[write_field, write_slot], |_| {});
+}
+
+/// Run the test with the field barrier, which remembers the modified slots.
+#[test]
+pub fn gc_field_barrier() {
+    replace_leaves_with_barrier("FieldBarrier");
+}
@@ -0,0 +1,27 @@
+// GITHUB-CI: MMTK_PLAN=GenMarkSweep
+
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use mmtk::util::ObjectReference;
+
+/// Check that all the nodes and leaves of the list have been promoted to the mature malloc space,
+/// which does not move objects.
+fn verify_promoted(head: ObjectReference) {
+    let mut node = head;
+    while !node.is_null() {
+        assert!(!node.is_movable(), "Node {} is not promoted", node);
+        assert!(!read_field(node, 1).is_movable());
+        node = read_field(node, 0);
+    }
+}
+
+/// This test allocates a list in the nursery and then allocates garbage with a small nursery. The list is
+/// promoted into the malloc space by the first nursery GC, and it should be intact after later nursery GCs and
+/// full heap GCs that sweep the malloc space.
+#[test]
+pub fn gc_gen_marksweep() {
+    if !plan_is("GenMarkSweep") {
+        return;
+    }
+    replace_leaves_in_small_nursery(&[], verify_promoted);
+}
@@ -0,0 +1,27 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::opaque_pointer::*;
+
+/// This test builds a linked list while allocating garbage, so that several GCs happen while the list is alive.
+/// The list is kept alive from the root stack of the mutator, and should be intact after the GCs.
+#[test]
+pub fn gc_linked_list() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 32MB heap. The garbage is about 40MB, and the list is less than 1MB.
+    mmtk_gc_init(32 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let head = build_linked_list(mutator, 1000, 200);
+    assert!(threads::pause_count() > 0, "No GC happened");
+    verify_linked_list(threads::get_root(mutator, head), 1000);
+
+    mmtk_destroy_mutator(handle);
+}
@@ -0,0 +1,37 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::opaque_pointer::*;
+
+const MUTATORS: usize = 4;
+
+/// This test runs several mutator threads. Each of them builds its own linked list while allocating garbage,
+/// so the GCs have to stop all the mutators, and scan the root stacks of all of them.
+#[test]
+pub fn gc_multiple_mutators() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 32MB heap
+    mmtk_gc_init(32 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+
+    let handles: Vec<_> = (0..MUTATORS)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let handle = mmtk_bind_mutator(current_thread_tls());
+                let mutator = unsafe { &mut *handle };
+                let head = build_linked_list(mutator, 500, 100);
+                verify_linked_list(threads::get_root(mutator, head), 500);
+                mmtk_destroy_mutator(handle);
+            })
+        })
+        .collect();
+    for thread in handles {
+        thread.join().unwrap();
+    }
+    assert!(threads::pause_count() > 0, "No GC happened");
+}
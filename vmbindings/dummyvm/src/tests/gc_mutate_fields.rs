@@ -0,0 +1,82 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::DummyVM;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::Mutator;
+
+const LIST_LENGTH: usize = 200;
+const ROUNDS: usize = 500;
+const GARBAGE_PER_ROUND: usize = 200;
+
+/// Move the leaf of each node of the list to the previous node, and the leaf of the head to the tail.
+/// Each leaf is only referenced by one node, so the reference to a leaf is removed from one object and
+/// stored to another object. This does not allocate, so the objects do not move.
+fn rotate_leaves(mutator: &mut Mutator<DummyVM>, head: ObjectReference) {
+    let first_leaf = read_field(head, 1);
+    let mut node = head;
+    loop {
+        let next = read_field(node, 0);
+        if next.is_null() {
+            write_field(mutator, node, 1, first_leaf);
+            return;
+        }
+        write_field(mutator, node, 1, read_field(next, 1));
+        node = next;
+    }
+}
+
+/// Check that the leaf of the `i`-th node is the leaf that was created for the `(i + rotations)`-th node.
+fn verify_leaves(head: ObjectReference, rotations: usize) {
+    let mut node = head;
+    for i in 0..LIST_LENGTH {
+        assert_eq!(
+            unsafe { object_model::get_payload(node).load::<usize>() },
+            i
+        );
+        let leaf = read_field(node, 1);
+        assert_eq!(
+            unsafe { object_model::get_payload(leaf).load::<usize>() },
+            (i + rotations) % LIST_LENGTH,
+            "Wrong leaf at node {} after {} rotations",
+            i,
+            rotations
+        );
+        node = read_field(node, 0);
+    }
+    assert!(node.is_null());
+}
+
+/// This test keeps moving references between the objects of a list while allocating garbage, so the fields are
+/// modified while GCs are in progress (e.g. during concurrent marking), and between GCs (e.g. across the epochs of
+/// reference counting). The barriers must make sure that no object that is still referenced is reclaimed.
+#[test]
+pub fn gc_mutate_fields() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 16MB heap. The garbage is about 20MB.
+    mmtk_gc_init(16 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let head = build_linked_list(mutator, LIST_LENGTH, 0);
+    for rotations in 1..=ROUNDS {
+        rotate_leaves(mutator, threads::get_root(mutator, head));
+        for _ in 0..GARBAGE_PER_ROUND {
+            alloc_object(mutator, 1, 19 * BYTES_IN_WORD);
+        }
+        verify_leaves(threads::get_root(mutator, head), rotations);
+    }
+    assert!(threads::pause_count() > 0, "No GC happened");
+
+    mmtk_destroy_mutator(handle);
+}
@@ -0,0 +1,96 @@
+// GITHUB-CI: MMTK_PLAN=MarkSweep
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::alloc::AllocatorSelector;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+use std::collections::HashSet;
+
+const LIST_LENGTH: usize = 100;
+const GARBAGE_PER_NODE: usize = 10;
+const OBJECTS: usize = 10000;
+/// Larger than the largest size class of the native mark-sweep space.
+const LARGE_PAYLOAD_BYTES: usize = 16 * 1024;
+
+/// This test uses the native mark-sweep space for MarkSweep. It allocates objects of the same size, and keeps
+/// every other object alive. After a GC sweeps the blocks, the cells of the dead objects should be reused by
+/// later allocations, and the live objects should be intact. A large object should be allocated in the large
+/// object space rather than in the size classes of the native space.
+#[test]
+pub fn gc_native_marksweep() {
+    if !plan_is("MarkSweep") {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    assert!(memory_manager::process_bulk(
+        &SINGLETON,
+        "marksweep_space=Native"
+    ));
+    // 8MB heap
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+    assert_eq!(
+        memory_manager::get_allocator_mapping(&SINGLETON, AllocationSemantics::Default),
+        AllocatorSelector::FreeList(0)
+    );
+
+    let head = build_linked_list(mutator, LIST_LENGTH, GARBAGE_PER_NODE);
+    let large = alloc_object(mutator, 0, LARGE_PAYLOAD_BYTES);
+    unsafe { object_model::get_payload(large).store::<usize>(LARGE_PAYLOAD_BYTES) };
+    let large_root = threads::push_root(mutator, large);
+
+    let mut live: Vec<(usize, Address)> = vec![];
+    let mut dead: HashSet<Address> = HashSet::new();
+    for i in 0..OBJECTS {
+        let object = alloc_object(mutator, 0, 2 * BYTES_IN_WORD);
+        unsafe { object_model::get_payload(object).store::<usize>(i) };
+        if i % 2 == 0 {
+            live.push((threads::push_root(mutator, object), object.to_address()));
+        } else {
+            dead.insert(object.to_address());
+        }
+    }
+
+    let pauses = threads::pause_count();
+    mmtk_handle_user_collection_request(tls);
+    assert!(threads::pause_count() > pauses, "No GC happened");
+
+    // Allocate as many objects as the dead ones. Blocks with free cells are used before clean blocks.
+    let mut reused = 0;
+    for _ in 0..dead.len() {
+        let object = alloc_object(mutator, 0, 2 * BYTES_IN_WORD);
+        unsafe { object_model::get_payload(object).store::<usize>(usize::MAX) };
+        if dead.contains(&object.to_address()) {
+            reused += 1;
+        }
+    }
+    assert!(reused > 0, "No free cell was reused");
+
+    for (n, (root, address)) in live.iter().enumerate() {
+        let object = threads::get_root(mutator, *root);
+        assert_eq!(object.to_address(), *address);
+        assert_eq!(
+            unsafe { object_model::get_payload(object).load::<usize>() },
+            n * 2
+        );
+    }
+    let large = threads::get_root(mutator, large_root);
+    assert_eq!(
+        unsafe { object_model::get_payload(large).load::<usize>() },
+        LARGE_PAYLOAD_BYTES
+    );
+    verify_linked_list(threads::get_root(mutator, head), LIST_LENGTH);
+
+    mmtk_destroy_mutator(handle);
+}
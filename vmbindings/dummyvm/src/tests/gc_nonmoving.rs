@@ -0,0 +1,87 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::AllocationSemantics;
+
+const LIST_LENGTH: usize = 100;
+/// Larger than the size classes of the non-moving space
+const LARGE_PAYLOAD_BYTES: usize = 12 * 1024;
+const LARGE_OBJECT_MARK: usize = 42;
+
+/// This test builds a linked list of non-moving objects, with garbage in between, and explicitly requests GCs.
+/// After each GC, the nodes should stay at the same addresses, and the list should be intact. A non-moving object
+/// that is too large for the non-moving space should be allocated in the large object space, and should not move either.
+#[test]
+pub fn gc_nonmoving() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 8MB heap
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    let large = alloc_object_with_semantics(
+        mutator,
+        0,
+        LARGE_PAYLOAD_BYTES,
+        AllocationSemantics::NonMoving,
+    );
+    unsafe { object_model::get_payload(large).store::<usize>(LARGE_OBJECT_MARK) };
+    let large_address = large.to_address();
+    let large = threads::push_root(mutator, large);
+
+    let mut addresses: Vec<Address> = vec![];
+    let head = threads::push_root(mutator, ObjectReference::NULL);
+    for i in (0..LIST_LENGTH).rev() {
+        // Some garbage in the non-moving space and in the default space
+        alloc_object_with_semantics(mutator, 0, BYTES_IN_WORD, AllocationSemantics::NonMoving);
+        alloc_object(mutator, 0, BYTES_IN_WORD);
+        let node =
+            alloc_object_with_semantics(mutator, 1, BYTES_IN_WORD, AllocationSemantics::NonMoving);
+        assert!(!node.is_movable());
+        unsafe { object_model::get_payload(node).store::<usize>(i) };
+        write_field(mutator, node, 0, threads::get_root(mutator, head));
+        threads::set_root(mutator, head, node);
+        addresses.push(node.to_address());
+    }
+    addresses.reverse();
+
+    for _ in 0..3 {
+        let pauses = threads::pause_count();
+        mmtk_handle_user_collection_request(tls);
+        assert!(threads::pause_count() > pauses, "No GC happened");
+        let mut node = threads::get_root(mutator, head);
+        for (i, address) in addresses.iter().enumerate() {
+            assert_eq!(node.to_address(), *address, "Node {} has moved", i);
+            assert_eq!(
+                unsafe { object_model::get_payload(node).load::<usize>() },
+                i
+            );
+            node = read_field(node, 0);
+        }
+        assert!(node.is_null());
+        let large = threads::get_root(mutator, large);
+        assert_eq!(
+            large.to_address(),
+            large_address,
+            "The large object has moved"
+        );
+        assert_eq!(
+            unsafe { object_model::get_payload(large).load::<usize>() },
+            LARGE_OBJECT_MARK
+        );
+    }
+
+    mmtk_destroy_mutator(handle);
+}
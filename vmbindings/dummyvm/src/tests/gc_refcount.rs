@@ -0,0 +1,87 @@
+// GITHUB-CI: MMTK_PLAN=RefCount
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::DummyVM;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::Mutator;
+
+const LIST_LENGTH: usize = 100;
+const ROUNDS: usize = 500;
+const GARBAGE_PER_ROUND: usize = 200;
+
+/// Swap the leaves of each pair of adjacent nodes. The leaves of the first list are written with the object
+/// barrier, and the leaves of the second list are written with the slot barrier.
+fn swap_leaves(mutator: &mut Mutator<DummyVM>, head: ObjectReference, by_slot: bool) {
+    let mut node = head;
+    while !node.is_null() {
+        let next = read_field(node, 0);
+        if next.is_null() {
+            return;
+        }
+        let leaf = read_field(node, 1);
+        if by_slot {
+            write_slot(mutator, node, 1, read_field(next, 1));
+            write_slot(mutator, next, 1, leaf);
+        } else {
+            write_field(mutator, node, 1, read_field(next, 1));
+            write_field(mutator, next, 1, leaf);
+        }
+        node = read_field(next, 0);
+    }
+}
+
+/// Check that the leaf of each node is the leaf of its pair node after an odd number of swaps,
+/// or its own leaf after an even number of swaps.
+fn verify_leaves(head: ObjectReference, swaps: usize) {
+    let mut node = head;
+    for i in 0..LIST_LENGTH {
+        let leaf = read_field(node, 1);
+        let expected = if swaps % 2 == 0 { i } else { i ^ 1 };
+        assert_eq!(
+            unsafe { object_model::get_payload(leaf).load::<usize>() },
+            expected,
+            "Wrong leaf at node {} after {} swaps",
+            i,
+            swaps
+        );
+        node = read_field(node, 0);
+    }
+    assert!(node.is_null());
+}
+
+/// This test modifies the fields of two lists between reference counting pauses, with both the object barrier
+/// and the slot barrier. The old referents are decremented in the pauses, and the new referents are incremented.
+/// Each leaf is referenced by one node at a time, so a leaf is reclaimed too early if an increment is lost.
+#[test]
+pub fn gc_refcount() {
+    if !plan_is("RefCount") {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 8MB heap. The garbage is about 17MB, so there are a few pauses.
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let by_object = build_linked_list(mutator, LIST_LENGTH, 0);
+    let by_slot = build_linked_list(mutator, LIST_LENGTH, 0);
+    for swaps in 1..=ROUNDS {
+        swap_leaves(mutator, threads::get_root(mutator, by_object), false);
+        swap_leaves(mutator, threads::get_root(mutator, by_slot), true);
+        for _ in 0..GARBAGE_PER_ROUND {
+            alloc_object(mutator, 1, 19 * BYTES_IN_WORD);
+        }
+        verify_leaves(threads::get_root(mutator, by_object), swaps);
+        verify_leaves(threads::get_root(mutator, by_slot), swaps);
+    }
+    assert!(threads::pause_count() > 1, "Not enough GCs happened");
+
+    mmtk_destroy_mutator(handle);
+}
@@ -0,0 +1,14 @@
+// GITHUB-CI: MMTK_PLAN=MarkCompact
+// GITHUB-CI: FEATURES=markcompact_side_forwarding
+
+use crate::tests::fixtures::*;
+use crate::tests::gc_compaction::compact_lists;
+
+/// Run the compaction test with the forwarding pointers computed from side metadata.
+#[test]
+pub fn gc_side_forwarding() {
+    if !plan_is("MarkCompact") {
+        return;
+    }
+    compact_lists();
+}
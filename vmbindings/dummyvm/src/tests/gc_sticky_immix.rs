@@ -0,0 +1,16 @@
+// GITHUB-CI: MMTK_PLAN=StickyImmix
+// GITHUB-CI: FEATURES=sticky_immix_copy
+
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+
+/// This test keeps replacing the leaves of a list with new objects while allocating garbage with a small nursery.
+/// Once the list is promoted, each new leaf is only referenced by a mature object, so nursery GCs need the
+/// object barrier to find it. The list should be intact after both nursery GCs and full heap GCs.
+#[test]
+pub fn gc_sticky_immix() {
+    if !plan_is("StickyImmix") {
+        return;
+    }
+    replace_leaves_in_small_nursery(&[write_field], |_| {});
+}
@@ -0,0 +1,47 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::collection;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::DummyVM;
+use mmtk::plan::GCTriggerPolicy;
+use mmtk::util::opaque_pointer::*;
+use mmtk::Plan;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of times the policy is consulted.
+static POLLS: AtomicUsize = AtomicUsize::new(0);
+
+/// A policy that never asks for a GC.
+struct NeverTrigger;
+
+impl GCTriggerPolicy<DummyVM> for NeverTrigger {
+    fn is_gc_required(&self, _plan_requires_gc: bool, _plan: &dyn Plan<VM = DummyVM>) -> bool {
+        POLLS.fetch_add(1, Ordering::SeqCst);
+        false
+    }
+}
+
+/// This test installs a GC trigger policy that never asks for a GC. A GC should still be triggered when
+/// the heap is full, as the allocation cannot succeed otherwise.
+#[test]
+pub fn gc_trigger_never() {
+    if !plan_can_collect() {
+        return;
+    }
+    collection::set_gc_trigger(Box::new(NeverTrigger));
+    const MB: usize = 1024 * 1024;
+    // 8MB heap. The garbage is about 20MB, and the list is less than 1MB.
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let handle = mmtk_bind_mutator(current_thread_tls());
+    let mutator = unsafe { &mut *handle };
+
+    let head = build_linked_list(mutator, 500, 200);
+    assert!(POLLS.load(Ordering::SeqCst) > 0, "The policy is not used");
+    assert!(threads::pause_count() > 0, "No GC happened");
+    verify_linked_list(threads::get_root(mutator, head), 500);
+
+    mmtk_destroy_mutator(handle);
+}
@@ -0,0 +1,85 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+
+const RING_LENGTH: usize = 100;
+const LARGE_OBJECT_MARK: usize = 42;
+
+/// Check the ring built by the test: every node points to the next node and to the large object.
+fn verify_ring(first: ObjectReference, large: ObjectReference) {
+    assert_eq!(
+        unsafe { object_model::get_payload(large).load::<usize>() },
+        LARGE_OBJECT_MARK
+    );
+    let mut node = first;
+    for i in 0..RING_LENGTH {
+        assert_eq!(
+            unsafe { object_model::get_payload(node).load::<usize>() },
+            i
+        );
+        assert_eq!(read_field(node, 1), large);
+        node = read_field(node, 0);
+    }
+    assert_eq!(node, first, "The ring is not closed");
+}
+
+/// This test builds a cyclic object graph that points to a large object, and explicitly requests GCs.
+/// The graph should be intact after each GC.
+#[test]
+pub fn gc_user_request() {
+    if !plan_can_collect() {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    // 8MB heap
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    // The large object goes to the large object space.
+    let large = alloc_object(mutator, 0, MB);
+    unsafe { object_model::get_payload(large).store::<usize>(LARGE_OBJECT_MARK) };
+    let large = threads::push_root(mutator, large);
+
+    let first = alloc_object(mutator, 2, BYTES_IN_WORD);
+    unsafe { object_model::get_payload(first).store::<usize>(0) };
+    write_field(mutator, first, 1, threads::get_root(mutator, large));
+    let first = threads::push_root(mutator, first);
+    let last = threads::push_root(mutator, threads::get_root(mutator, first));
+    for i in 1..RING_LENGTH {
+        let node = alloc_object(mutator, 2, BYTES_IN_WORD);
+        unsafe { object_model::get_payload(node).store::<usize>(i) };
+        write_field(mutator, node, 1, threads::get_root(mutator, large));
+        write_field(mutator, threads::get_root(mutator, last), 0, node);
+        threads::set_root(mutator, last, node);
+    }
+    write_field(
+        mutator,
+        threads::get_root(mutator, last),
+        0,
+        threads::get_root(mutator, first),
+    );
+    // Only the first node and the large object are kept on the root stack.
+    threads::pop_root(mutator);
+
+    for _ in 0..3 {
+        let pauses = threads::pause_count();
+        mmtk_handle_user_collection_request(tls);
+        assert!(threads::pause_count() > pauses, "No GC happened");
+        verify_ring(
+            threads::get_root(mutator, first),
+            threads::get_root(mutator, large),
+        );
+    }
+
+    mmtk_destroy_mutator(handle);
+}
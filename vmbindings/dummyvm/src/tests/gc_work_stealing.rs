@@ -0,0 +1,77 @@
+// GITHUB-CI: MMTK_PLAN=SemiSpace
+
+use crate::api::*;
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::scheduler::{GCWork, GCWorker, WorkBucketStage};
+use mmtk::util::opaque_pointer::*;
+use mmtk::MMTK;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const CHILDREN: usize = 100;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    /// The ordinals of the workers that executed the children.
+    static ref CHILD_WORKERS: Mutex<HashSet<usize>> = Mutex::new(HashSet::new());
+}
+static CHILDREN_DONE: AtomicUsize = AtomicUsize::new(0);
+/// The ordinal of the worker that executed the parent.
+static PARENT_WORKER: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// A packet that records the worker executing it.
+struct Child;
+
+impl GCWork<DummyVM> for Child {
+    fn do_work(&mut self, worker: &mut GCWorker<DummyVM>, _mmtk: &'static MMTK<DummyVM>) {
+        CHILD_WORKERS.lock().unwrap().insert(worker.ordinal);
+        CHILDREN_DONE.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A packet that adds the children to the local deque of its worker, and then blocks until all the
+/// children are executed. Its worker cannot pop the children while it is blocked, so the children
+/// can only be executed if the other workers steal them.
+struct Parent;
+
+impl GCWork<DummyVM> for Parent {
+    fn do_work(&mut self, worker: &mut GCWorker<DummyVM>, _mmtk: &'static MMTK<DummyVM>) {
+        PARENT_WORKER.store(worker.ordinal, Ordering::SeqCst);
+        for _ in 0..CHILDREN {
+            worker.add_work(WorkBucketStage::Unconstrained, Child);
+        }
+        let start = Instant::now();
+        while CHILDREN_DONE.load(Ordering::SeqCst) < CHILDREN && start.elapsed() < TIMEOUT {
+            std::thread::yield_now();
+        }
+    }
+}
+
+/// This test checks that the packets in the local deque of a busy worker are stolen by idle workers.
+#[test]
+pub fn gc_work_stealing() {
+    // The number of workers needs to be set before the MMTk instance is created.
+    std::env::set_var("MMTK_THREADS", "4");
+    if memory_manager::num_of_workers(&SINGLETON) < 2 {
+        return;
+    }
+    const MB: usize = 1024 * 1024;
+    mmtk_gc_init(MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+
+    memory_manager::add_work_packet(&SINGLETON, WorkBucketStage::Unconstrained, Parent);
+    let start = Instant::now();
+    while CHILDREN_DONE.load(Ordering::SeqCst) < CHILDREN {
+        assert!(start.elapsed() < TIMEOUT, "The children were not stolen");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let parent_worker = PARENT_WORKER.load(Ordering::SeqCst);
+    assert!(
+        !CHILD_WORKERS.lock().unwrap().contains(&parent_worker),
+        "The worker of the parent executed a child"
+    );
+}
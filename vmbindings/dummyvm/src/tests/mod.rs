@@ -14,5 +14,27 @@ mod allocate_with_re_enable_collection;
 mod malloc;
 #[cfg(feature = "is_mmtk_object")]
 mod conservatism;
+#[cfg(feature = "object_pinning")]
+mod pin_object;
 mod is_in_mmtk_spaces;
+mod gc_linked_list;
+mod gc_user_request;
+mod gc_trigger_never;
+mod gc_nonmoving;
+mod gc_mutate_fields;
+mod gc_multiple_mutators;
+mod gc_array_copy;
+mod gc_refcount;
+mod gc_sticky_immix;
+mod gc_field_barrier;
+mod gc_card_barrier;
+mod gc_gen_marksweep;
+mod gc_native_marksweep;
+mod gc_compaction;
+mod gc_work_stealing;
+mod gc_concurrent_zeroing;
+#[cfg(feature = "markcompact_side_forwarding")]
+mod gc_side_forwarding;
+mod allocation_fastpath;
+mod barrier_fastpath;
 mod fixtures;
@@ -0,0 +1,92 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=object_pinning
+
+use crate::api::*;
+use crate::object_model;
+use crate::runtime::*;
+use crate::tests::fixtures::*;
+use crate::threads;
+use crate::SINGLETON;
+use mmtk::memory_manager::{self, is_pinned, pin_object, unpin_object};
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::opaque_pointer::*;
+
+const MARK: usize = 42;
+
+/// This test pins an object. Immix should pin it, and keep it in place in a defrag GC. CopySpace and
+/// MarkCompact cannot pin objects. The other plans should report the pinning state consistently.
+#[test]
+pub fn pin_and_unpin() {
+    const MB: usize = 1024 * 1024;
+    // A user requested GC is a defrag GC in Immix if it collects the full heap.
+    assert!(memory_manager::process_bulk(
+        &SINGLETON,
+        "full_heap_system_gc=true"
+    ));
+    // 8MB heap
+    mmtk_gc_init(8 * MB);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    let tls = current_thread_tls();
+    let handle = mmtk_bind_mutator(tls);
+    let mutator = unsafe { &mut *handle };
+
+    // Surround the object with garbage, so its block is fragmented after a GC.
+    for _ in 0..1000 {
+        alloc_object(mutator, 0, 4 * BYTES_IN_WORD);
+    }
+    let object = alloc_object(mutator, 0, BYTES_IN_WORD);
+    unsafe { object_model::get_payload(object).store::<usize>(MARK) };
+    let root = threads::push_root(mutator, object);
+    for _ in 0..1000 {
+        alloc_object(mutator, 0, 4 * BYTES_IN_WORD);
+    }
+
+    if plan_is("Immix") {
+        assert!(pin_object(object), "Immix should pin an object");
+        assert!(is_pinned(object), "A pinned object should be pinned");
+
+        let pauses = threads::pause_count();
+        mmtk_handle_user_collection_request(tls);
+        assert!(threads::pause_count() > pauses, "No GC happened");
+        assert_eq!(
+            threads::get_root(mutator, root),
+            object,
+            "A pinned object should not be moved"
+        );
+        assert_eq!(
+            unsafe { object_model::get_payload(object).load::<usize>() },
+            MARK
+        );
+
+        assert!(unpin_object(object), "A pinned object should be unpinned");
+        assert!(
+            !is_pinned(object),
+            "An unpinned object should not be pinned"
+        );
+        assert!(
+            !unpin_object(object),
+            "An object should not be unpinned twice"
+        );
+    } else if plan_is("SemiSpace") || plan_is("GenCopy") || plan_is("MarkCompact") {
+        assert!(!pin_object(object), "The plan cannot pin objects");
+        assert!(!is_pinned(object));
+        assert!(!unpin_object(object));
+    } else if pin_object(object) {
+        assert!(is_pinned(object), "A pinned object should be pinned");
+        if unpin_object(object) {
+            assert!(
+                !is_pinned(object),
+                "An unpinned object should not be pinned"
+            );
+        } else {
+            // The object is in a space that never moves objects.
+            assert!(is_pinned(object));
+        }
+    } else {
+        // The policy cannot pin objects.
+        assert!(!is_pinned(object));
+        assert!(!unpin_object(object));
+    }
+
+    mmtk_destroy_mutator(handle);
+}
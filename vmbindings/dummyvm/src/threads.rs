@@ -0,0 +1,225 @@
+//! The thread registry of DummyVM, and its stop-the-world protocol.
+//!
+//! Every mutator thread is registered along with its root stack. Mutators cooperate with the GC
+//! at safepoints: a mutator parks itself when it blocks for a GC, or when it reaches a safepoint
+//! while the GC has asked the mutators to stop. The world is stopped once every registered mutator
+//! is parked, and parked mutators are released when the GC resumes the mutators.
+
+use crate::DummyVM;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::Mutator;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// A registered mutator thread.
+pub struct MutatorThread {
+    pub tls: VMMutatorThread,
+    pub mutator: *mut Mutator<DummyVM>,
+    /// The root stack of the thread. Every non-null reference on the stack is a root,
+    /// and it is updated in place if the GC moves the object.
+    pub roots: Vec<ObjectReference>,
+}
+
+struct Threads {
+    mutators: Vec<MutatorThread>,
+    /// The index of the mutator that `next_mutator()` returns next.
+    cursor: usize,
+    /// Whether the GC has asked the mutators to stop.
+    stop_requested: bool,
+    /// The number of mutators parked at a safepoint.
+    parked: usize,
+    /// The number of pauses that have finished. Parked mutators wait for it to change.
+    pauses: usize,
+    /// Set if a GC thread panicked, so parked mutators fail instead of waiting forever.
+    gc_panic: Option<String>,
+}
+
+// The mutator pointers are only dereferenced by their own thread, or by GC threads while the thread is parked.
+unsafe impl Send for Threads {}
+
+lazy_static! {
+    static ref THREADS: Mutex<Threads> = Mutex::new(Threads {
+        mutators: vec![],
+        cursor: 0,
+        stop_requested: false,
+        parked: 0,
+        pauses: 0,
+        gc_panic: None,
+    });
+    static ref WORLD: Condvar = Condvar::new();
+}
+
+/// A copy of `Threads::stop_requested` that mutators can check at safepoints without locking.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+fn lock() -> MutexGuard<'static, Threads> {
+    THREADS.lock().unwrap()
+}
+
+/// Wait on the world condition variable as a mutator. Panic if a GC thread has panicked.
+fn wait(threads: MutexGuard<'static, Threads>) -> MutexGuard<'static, Threads> {
+    let threads = check_gc_panic(threads);
+    check_gc_panic(WORLD.wait(threads).unwrap())
+}
+
+fn check_gc_panic(threads: MutexGuard<'static, Threads>) -> MutexGuard<'static, Threads> {
+    if let Some(message) = threads.gc_panic.clone() {
+        drop(threads);
+        panic!("A GC thread panicked: {}", message);
+    }
+    threads
+}
+
+/// Park the current mutator until the current (or the next) pause is over.
+fn park(mut threads: MutexGuard<'static, Threads>) -> MutexGuard<'static, Threads> {
+    threads.parked += 1;
+    WORLD.notify_all();
+    let pauses = threads.pauses;
+    while threads.pauses == pauses {
+        threads = wait(threads);
+    }
+    threads.parked -= 1;
+    threads
+}
+
+/// Register a mutator. If the world is being stopped, wait until the mutators are resumed.
+pub fn register_mutator(tls: VMMutatorThread, mutator: *mut Mutator<DummyVM>) {
+    let mut threads = lock();
+    while threads.stop_requested {
+        threads = wait(threads);
+    }
+    threads.mutators.push(MutatorThread {
+        tls,
+        mutator,
+        roots: vec![],
+    });
+}
+
+/// Unregister a mutator.
+pub fn unregister_mutator(mutator: *mut Mutator<DummyVM>) {
+    let mut threads = lock();
+    threads.mutators.retain(|t| t.mutator != mutator);
+    // The world may be stopped without this mutator now.
+    WORLD.notify_all();
+}
+
+/// A safepoint. If the GC has asked the mutators to stop, park the current mutator until they are resumed.
+pub fn safepoint() {
+    if STOP_REQUESTED.load(Ordering::Acquire) {
+        let threads = lock();
+        if threads.stop_requested {
+            drop(park(threads));
+        }
+    }
+}
+
+/// Block the current mutator for a GC it has requested, until the GC finishes.
+pub fn block_for_gc() {
+    drop(park(lock()));
+}
+
+/// Stop all the mutators, and return once every registered mutator is parked.
+pub fn stop_all_mutators() {
+    let mut threads = lock();
+    threads.stop_requested = true;
+    STOP_REQUESTED.store(true, Ordering::Release);
+    while threads.parked < threads.mutators.len() {
+        threads = WORLD.wait(threads).unwrap();
+    }
+}
+
+/// Resume the mutators parked for the current pause.
+pub fn resume_mutators() {
+    let mut threads = lock();
+    threads.stop_requested = false;
+    STOP_REQUESTED.store(false, Ordering::Release);
+    threads.pauses += 1;
+    WORLD.notify_all();
+}
+
+/// Record that a GC thread panicked, and wake up the parked mutators so they panic as well.
+pub fn gc_thread_panicked(message: String) {
+    let mut threads = lock();
+    threads.gc_panic = Some(message);
+    WORLD.notify_all();
+}
+
+/// Return the number of GC pauses so far.
+pub fn pause_count() -> usize {
+    lock().pauses
+}
+
+pub fn is_mutator(tls: VMThread) -> bool {
+    lock().mutators.iter().any(|t| t.tls.0 == tls)
+}
+
+pub fn number_of_mutators() -> usize {
+    lock().mutators.len()
+}
+
+pub fn get_mutator(tls: VMMutatorThread) -> *mut Mutator<DummyVM> {
+    let threads = lock();
+    let thread = threads.mutators.iter().find(|t| t.tls == tls);
+    thread.expect("not a mutator thread").mutator
+}
+
+pub fn reset_mutator_iterator() {
+    lock().cursor = 0;
+}
+
+pub fn next_mutator() -> Option<*mut Mutator<DummyVM>> {
+    let mut threads = lock();
+    let cursor = threads.cursor;
+    threads.cursor += 1;
+    threads.mutators.get(cursor).map(|t| t.mutator)
+}
+
+/// Return the addresses of the root slots of a mutator, or of all the mutators if `mutator` is `None`.
+/// The mutators must be parked.
+pub fn root_slots(mutator: Option<*mut Mutator<DummyVM>>) -> Vec<Address> {
+    let mut threads = lock();
+    threads
+        .mutators
+        .iter_mut()
+        .filter(|t| mutator.map_or(true, |m| t.mutator == m))
+        .flat_map(|t| t.roots.iter_mut())
+        .filter(|root| !root.is_null())
+        .map(|root| Address::from_mut_ptr(root))
+        .collect()
+}
+
+fn with_roots<T>(mutator: &Mutator<DummyVM>, f: impl FnOnce(&mut Vec<ObjectReference>) -> T) -> T {
+    let mut threads = lock();
+    let thread = threads
+        .mutators
+        .iter_mut()
+        .find(|t| std::ptr::eq(t.mutator, mutator))
+        .expect("the mutator is not registered");
+    f(&mut thread.roots)
+}
+
+/// Push an object onto the root stack of a mutator. Return its index on the stack.
+pub fn push_root(mutator: &Mutator<DummyVM>, object: ObjectReference) -> usize {
+    with_roots(mutator, |roots| {
+        roots.push(object);
+        roots.len() - 1
+    })
+}
+
+/// Pop the top object from the root stack of a mutator.
+pub fn pop_root(mutator: &Mutator<DummyVM>) -> ObjectReference {
+    with_roots(mutator, |roots| {
+        roots.pop().expect("the root stack is empty")
+    })
+}
+
+/// Return the object at `index` on the root stack of a mutator.
+pub fn get_root(mutator: &Mutator<DummyVM>, index: usize) -> ObjectReference {
+    with_roots(mutator, |roots| roots[index])
+}
+
+/// Replace the object at `index` on the root stack of a mutator.
+pub fn set_root(mutator: &Mutator<DummyVM>, index: usize, object: ObjectReference) {
+    with_roots(mutator, |roots| roots[index] = object)
+}